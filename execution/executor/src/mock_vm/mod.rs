@@ -51,6 +51,15 @@ pub static DISCARD_STATUS: Lazy<TransactionStatus> =
 pub struct MockVM;
 
 impl VMExecutor for MockVM {
+    // Mock transactions use no gas, so the limit is never reached.
+    fn execute_block_with_gas_limit(
+        transactions: Vec<Transaction>,
+        state_view: &dyn StateView,
+        _block_gas_limit: u64,
+    ) -> Result<Vec<TransactionOutput>, VMStatus> {
+        Self::execute_block(transactions, state_view)
+    }
+
     fn execute_block(
         transactions: Vec<Transaction>,
         state_view: &dyn StateView,
@@ -17,7 +17,22 @@ fn peer_to_peer(c: &mut Criterion) {
     });
 }
 
-criterion_group!(txn_benches, peer_to_peer);
+// A replay of a large block, read directly from the state or through an LRU read cache.
+fn peer_to_peer_replay(c: &mut Criterion) {
+    for (name, cache_capacity) in &[
+        ("peer_to_peer_replay", None),
+        ("peer_to_peer_replay_lru_read_cache", Some(10_000)),
+    ] {
+        c.bench_function(name, |b| {
+            let mut bencher =
+                TransactionBencher::new(any_with::<P2PTransferGen>((1_000, 1_000_000)));
+            bencher.num_transactions(5_000);
+            bencher.bench_replay(b, 3, *cache_capacity)
+        });
+    }
+}
+
+criterion_group!(txn_benches, peer_to_peer, peer_to_peer_replay);
 
 //
 // MoveVM benchmarks
@@ -8,7 +8,9 @@ use language_e2e_tests::{
     gas_costs::TXN_RESERVED,
 };
 use libra_proptest_helpers::ValueGenerator;
-use libra_types::transaction::SignedTransaction;
+use libra_state_view::StateView;
+use libra_types::transaction::{SignedTransaction, Transaction};
+use libra_vm::{read_cache::LruReadCache, LibraVM};
 use proptest::{collection::vec, strategy::Strategy};
 
 /// Benchmarking support for transactions.
@@ -66,6 +68,22 @@ where
             BatchSize::LargeInput,
         )
     }
+
+    /// Runs the bencher on a replay: the block is executed `replays` times on the same state,
+    /// through an `LruReadCache` of `cache_capacity` values if set.
+    pub fn bench_replay(&self, b: &mut Bencher, replays: usize, cache_capacity: Option<usize>) {
+        b.iter_batched(
+            || {
+                TransactionBenchState::with_size(
+                    &self.strategy,
+                    self.num_accounts,
+                    self.num_transactions,
+                )
+            },
+            |state| state.replay(replays, cache_capacity),
+            BatchSize::LargeInput,
+        )
+    }
 }
 
 struct TransactionBenchState {
@@ -136,6 +154,28 @@ impl TransactionBenchState {
             .execute_block(self.transactions)
             .expect("VM should not fail to start");
     }
+
+    /// Executes this state in a single block `replays` times, through an `LruReadCache` of
+    /// `cache_capacity` values if set.
+    fn replay(self, replays: usize, cache_capacity: Option<usize>) {
+        let cache = cache_capacity
+            .map(|capacity| LruReadCache::new(self.executor.get_state_view(), capacity));
+        let view: &dyn StateView = match &cache {
+            Some(cache) => cache,
+            None => self.executor.get_state_view(),
+        };
+        let mut vm = LibraVM::new();
+        for _ in 0..replays {
+            let block = self
+                .transactions
+                .iter()
+                .cloned()
+                .map(Transaction::UserTransaction)
+                .collect();
+            vm.execute_transaction_block(block, view)
+                .expect("VM should not fail to start");
+        }
+    }
 }
 
 /// Returns a strategy for the account universe customized for benchmarks.
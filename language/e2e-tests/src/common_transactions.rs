@@ -14,6 +14,7 @@ use libra_types::{
 };
 use move_core_types::language_storage::TypeTag;
 use once_cell::sync::Lazy;
+use vm::CompiledModule;
 
 pub static CREATE_ACCOUNT_SCRIPT: Lazy<Vec<u8>> = Lazy::new(|| {
     let code = "
@@ -120,6 +121,46 @@ pub fn empty_txn(
     )
 }
 
+/// Returns a transaction running the Move IR script `program`, compiled at the address of
+/// `sender`, with a gas unit price of 0.
+pub fn script_txn(
+    sender: &Account,
+    seq_num: u64,
+    program: &str,
+    max_gas_amount: u64,
+) -> SignedTransaction {
+    script_txn_impl(sender, seq_num, program, vec![], vec![], max_gas_amount, 0)
+}
+
+/// Like `script_txn`, with the script compiled against `extra_deps` and called with `args`, at a
+/// gas unit price of `gas_unit_price`.
+pub fn script_txn_impl(
+    sender: &Account,
+    seq_num: u64,
+    program: &str,
+    extra_deps: Vec<CompiledModule>,
+    args: Vec<TransactionArgument>,
+    max_gas_amount: u64,
+    gas_unit_price: u64,
+) -> SignedTransaction {
+    let code = Compiler {
+        address: *sender.address(),
+        extra_deps,
+        ..Compiler::default()
+    }
+    .into_script_blob("file_name", program)
+    .expect("Script compilation failed");
+    sender.create_signed_txn_with_args(
+        code,
+        vec![],
+        args,
+        seq_num,
+        max_gas_amount,
+        gas_unit_price,
+        LBR_NAME.to_owned(),
+    )
+}
+
 /// Returns a transaction to create a new account with the given arguments.
 pub fn create_account_txn(
     sender: &Account,
@@ -13,7 +13,7 @@ use libra_types::{
     transaction::ChangeSet,
     write_set::{WriteOp, WriteSet},
 };
-use libra_vm::data_cache::RemoteStorage;
+use libra_vm::{data_cache::RemoteStorage, state_store::StateStore};
 use move_core_types::{
     account_address::AccountAddress,
     language_storage::{ModuleId, TypeTag},
@@ -93,6 +93,24 @@ impl FakeDataStore {
     }
 }
 
+// This is used by the `apply_block_outputs` API.
+impl StateStore for FakeDataStore {
+    fn write_batch(&mut self, writes: Vec<(AccessPath, WriteOp)>) -> Result<()> {
+        // Since the data is in-memory, it can't fail halfway.
+        for (access_path, write_op) in writes {
+            match write_op {
+                WriteOp::Value(blob) => {
+                    self.set(access_path, blob);
+                }
+                WriteOp::Deletion => {
+                    self.remove(&access_path);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 impl ConfigStorage for FakeDataStore {
     fn fetch_config(&self, access_path: AccessPath) -> Option<Vec<u8>> {
         StateView::get(self, &access_path).unwrap_or_default()
@@ -100,15 +118,17 @@ impl ConfigStorage for FakeDataStore {
 }
 
 // This is used by the `execute_block` API.
-// TODO: only the "sync" get is implemented
 impl StateView for FakeDataStore {
     fn get(&self, access_path: &AccessPath) -> Result<Option<Vec<u8>>> {
         // Since the data is in-memory, it can't fail.
         Ok(self.data.get(access_path).cloned())
     }
 
-    fn multi_get(&self, _access_paths: &[AccessPath]) -> Result<Vec<Option<Vec<u8>>>> {
-        unimplemented!();
+    fn multi_get(&self, access_paths: &[AccessPath]) -> Result<Vec<Option<Vec<u8>>>> {
+        Ok(access_paths
+            .iter()
+            .map(|access_path| self.data.get(access_path).cloned())
+            .collect())
     }
 
     fn is_genesis(&self) -> bool {
@@ -9,18 +9,52 @@
 //! separate binary. The linker ends up repeating a lot of work for each binary to not much
 //! benefit.
 
+mod abort_message;
+mod account_layout;
 mod account_universe;
+mod argument_preprocessor;
+mod block_gas_limit;
+mod block_plan;
+mod block_prologue;
+mod block_summary;
+#[cfg(feature = "chaos")]
+mod chaos;
 mod create_account;
 mod data_store;
+#[cfg(feature = "determinism-audit")]
+mod determinism_audit;
+mod diagnostics;
+mod event_replay;
+mod execution_hooks;
 mod execution_strategies;
+mod execution_time;
 mod failed_transaction_tests;
+mod fixture;
+mod gas_currency;
 mod genesis;
+#[cfg(feature = "instruction-count")]
+mod instruction_count;
+mod max_events;
+mod metrics_snapshot;
 mod mint;
 mod module_publishing;
 mod on_chain_configs;
+mod output_cache;
+mod output_invariants;
+mod outputs_by_hash;
+mod parallel_execution;
 mod peer_to_peer;
+mod prefetch;
+mod read_log;
+mod resource_writes;
 mod rotate_key;
+mod schedule_search;
 mod scripts;
+mod simulation;
+mod state_store;
+mod storage_gas;
+#[cfg(feature = "timestamp-override")]
+mod timestamp_override;
 mod transaction_builder;
 mod transaction_fees;
 mod validator_set_management;
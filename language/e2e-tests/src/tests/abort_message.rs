@@ -0,0 +1,138 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    account::AccountData, common_transactions::script_txn_impl, executor::FakeExecutor,
+    transaction_status_eq,
+};
+use compiler::Compiler;
+use libra_state_view::StateView;
+use libra_types::{
+    access_path::AccessPath,
+    transaction::{SignedTransaction, TransactionStatus},
+    vm_status::{StatusCode, VMStatus},
+};
+use libra_vm::{abort_message::AbortMessageResolver, LibraVM};
+use move_core_types::{
+    identifier::Identifier,
+    language_storage::{ModuleId, ResourceKey, StructTag},
+};
+use vm::CompiledModule;
+
+/// Reads the message of an abort code from the `AbortMessage` resource a module publishes under
+/// its address.
+struct PublishedAbortMessages;
+
+impl AbortMessageResolver for PublishedAbortMessages {
+    fn resolve(&self, module: &ModuleId, abort_code: u64, state: &dyn StateView) -> Option<String> {
+        let tag = StructTag {
+            address: *module.address(),
+            module: module.name().to_owned(),
+            name: Identifier::new("AbortMessage").unwrap(),
+            type_params: vec![],
+        };
+        let access_path =
+            AccessPath::resource_access_path(&ResourceKey::new(*module.address(), tag));
+        let blob = state.get(&access_path).ok()??;
+        match lcs::from_bytes::<(u64, Vec<u8>)>(&blob).ok()? {
+            (code, message) if code == abort_code => String::from_utf8(message).ok(),
+            _ => None,
+        }
+    }
+}
+
+fn script_txn(
+    sender: &AccountData,
+    module: &CompiledModule,
+    seq_num: u64,
+    body: &str,
+) -> SignedTransaction {
+    let program = format!(
+        "
+        import 0x{}.M;
+
+        main(account: &signer) {{
+            {}
+        }}
+        ",
+        sender.address(),
+        body,
+    );
+    script_txn_impl(
+        sender.account(),
+        seq_num,
+        &program,
+        vec![module.clone()],
+        vec![],
+        100_000,
+        0,
+    )
+}
+
+#[test]
+fn abort_message_resolver() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    executor.add_account_data(&sender);
+
+    // The message of abort code 42 is "not enough".
+    let module_code = r#"
+        module M {
+            resource AbortMessage { code: u64, message: vector<u8> }
+
+            public publish_message(account: &signer) {
+                move_to<AbortMessage>(
+                    move(account),
+                    AbortMessage { code: 42, message: h"6e6f7420656e6f756768" }
+                );
+                return;
+            }
+
+            public fail(code: u64) {
+                abort move(code);
+            }
+        }
+    "#;
+    let module = Compiler {
+        address: *sender.address(),
+        ..Compiler::default()
+    }
+    .into_compiled_module("file_name", module_code)
+    .unwrap();
+    executor.add_module(&module.self_id(), &module);
+    let output = executor.execute_transaction(script_txn(
+        &sender,
+        &module,
+        10,
+        "M.publish_message(move(account)); return;",
+    ));
+    assert!(transaction_status_eq(
+        output.status(),
+        &TransactionStatus::Keep(VMStatus::executed())
+    ));
+    executor.apply_write_set(output.write_set());
+
+    let mut vm = LibraVM::new();
+    let abort_status = |vm: &mut LibraVM, body: &str| {
+        vm.execute_single_transaction(
+            script_txn(&sender, &module, 11, body),
+            executor.get_state_view(),
+        )
+        .unwrap()
+        .status()
+        .vm_status()
+    };
+    let status = abort_status(&mut vm, "M.fail(42); return;");
+    assert_eq!(status.major_status, StatusCode::ABORTED);
+    assert_eq!(status.message, None);
+
+    vm.set_abort_message_resolver(PublishedAbortMessages);
+    let status = abort_status(&mut vm, "M.fail(42); return;");
+    assert_eq!(status.major_status, StatusCode::ABORTED);
+    assert_eq!(status.sub_status, Some(42));
+    assert_eq!(status.message.as_deref(), Some("not enough"));
+
+    // No message for this code, nor for aborts in the script itself.
+    assert_eq!(abort_status(&mut vm, "M.fail(7); return;").message, None);
+    assert_eq!(abort_status(&mut vm, "abort 42;").message, None);
+}
@@ -0,0 +1,127 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    account::{self, AccountData},
+    common_transactions::peer_to_peer_txn,
+    executor::FakeExecutor,
+};
+use libra_types::{
+    access_path::AccessPath,
+    account_config::{lbr_type_tag, CORE_CODE_ADDRESS},
+    write_set::{WriteOp, WriteSetMut},
+};
+use libra_vm::{
+    account_layout::AccountLayout, executor_config::ExecutorConfig,
+    prefetch::derive_prefetch_hints, LibraVM,
+};
+use move_core_types::{
+    identifier::Identifier,
+    language_storage::{ResourceKey, StructTag, TypeTag},
+};
+
+/// An account module storing `(authentication_key, sequence_number)` in a `Custom::Account`
+/// resource, and balances in `Custom::Balance<Currency>`.
+fn custom_layout() -> AccountLayout {
+    fn custom_struct(name: &str, type_params: Vec<TypeTag>) -> StructTag {
+        StructTag {
+            address: CORE_CODE_ADDRESS,
+            module: Identifier::new("Custom").unwrap(),
+            name: Identifier::new(name).unwrap(),
+            type_params,
+        }
+    }
+    AccountLayout {
+        account_resource: custom_struct("Account", vec![]),
+        balance_resource: |currency| custom_struct("Balance", vec![currency]),
+        sequence_number: |blob| {
+            lcs::from_bytes::<(Vec<u8>, u64)>(blob)
+                .ok()
+                .map(|(_, sequence_number)| sequence_number)
+        },
+        authentication_key: |blob| {
+            lcs::from_bytes::<(Vec<u8>, u64)>(blob)
+                .ok()
+                .map(|(auth_key, _)| auth_key)
+        },
+        balance: |blob| lcs::from_bytes::<u64>(blob).ok(),
+    }
+}
+
+#[test]
+fn vm_reads_accounts_with_custom_layout() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let layout = custom_layout();
+    let address = *sender.address();
+    let path = |tag: StructTag| AccessPath::resource_access_path(&ResourceKey::new(address, tag));
+    let account_path = path(layout.account_resource.clone());
+    let balance_path = path((layout.balance_resource)(lbr_type_tag()));
+    executor.apply_write_set(
+        &WriteSetMut::new(vec![
+            (
+                account_path.clone(),
+                WriteOp::Value(lcs::to_bytes(&(vec![7u8; 32], 42u64)).unwrap()),
+            ),
+            (
+                balance_path.clone(),
+                WriteOp::Value(lcs::to_bytes(&500u64).unwrap()),
+            ),
+        ])
+        .freeze()
+        .unwrap(),
+    );
+
+    let standard_vm = LibraVM::new();
+    let custom_vm = LibraVM::new_with_config(ExecutorConfig {
+        account_layout: layout.clone(),
+        ..ExecutorConfig::default()
+    });
+    let state_view = executor.get_state_view();
+    let lbr = account::lbr_currency_code();
+
+    assert_eq!(
+        standard_vm
+            .account_sequence_number(state_view, address)
+            .unwrap(),
+        Some(10)
+    );
+    assert_eq!(
+        standard_vm
+            .account_balance(state_view, address, &lbr)
+            .unwrap(),
+        Some(1_000_000)
+    );
+    assert_eq!(
+        custom_vm
+            .account_sequence_number(state_view, address)
+            .unwrap(),
+        Some(42)
+    );
+    assert_eq!(
+        custom_vm.account_auth_key(state_view, address).unwrap(),
+        Some(vec![7u8; 32])
+    );
+    assert_eq!(
+        custom_vm
+            .account_balance(state_view, address, &lbr)
+            .unwrap(),
+        Some(500)
+    );
+    assert_eq!(
+        custom_vm
+            .account_sequence_number(state_view, *receiver.address())
+            .unwrap(),
+        None
+    );
+
+    // Prefetching follows the layout as well.
+    let txn = peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000);
+    let mut expected = vec![account_path, balance_path];
+    expected.sort();
+    assert_eq!(derive_prefetch_hints(&[txn], &layout), expected);
+}
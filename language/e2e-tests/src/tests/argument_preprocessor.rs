@@ -0,0 +1,102 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    account::{Account, AccountData},
+    common_transactions::peer_to_peer_txn,
+    executor::FakeExecutor,
+    gas_costs,
+};
+use compiled_stdlib::transaction_scripts::StdlibScript;
+use libra_types::{
+    account_address::AccountAddress,
+    account_config::{lbr_type_tag, LBR_NAME},
+    transaction::{SignedTransaction, TransactionArgument},
+    vm_status::StatusCode,
+};
+use libra_vm::{argument_preprocessor::ArgumentPreprocessor, LibraVM};
+
+/// Replaces the `b"@payee"` placeholder with the address of the payee.
+struct PayeePlaceholder(AccountAddress);
+
+impl ArgumentPreprocessor for PayeePlaceholder {
+    fn preprocess(&self, args: &[TransactionArgument]) -> Vec<TransactionArgument> {
+        args.iter()
+            .map(|arg| match arg {
+                TransactionArgument::U8Vector(bytes) if bytes.as_slice() == b"@payee" => {
+                    TransactionArgument::Address(self.0)
+                }
+                arg => arg.clone(),
+            })
+            .collect()
+    }
+}
+
+fn placeholder_txn(sender: &Account, seq_num: u64, amount: u64) -> SignedTransaction {
+    sender.create_signed_txn_with_args(
+        StdlibScript::PeerToPeerWithMetadata
+            .compiled_bytes()
+            .into_vec(),
+        vec![lbr_type_tag()],
+        vec![
+            TransactionArgument::U8Vector(b"@payee".to_vec()),
+            TransactionArgument::U64(amount),
+            TransactionArgument::U8Vector(vec![]),
+            TransactionArgument::U8Vector(vec![]),
+        ],
+        seq_num,
+        gas_costs::TXN_RESERVED,
+        0,
+        LBR_NAME.to_owned(),
+    )
+}
+
+#[test]
+fn argument_preprocessor() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    // The script expects an address.
+    let output = executor.execute_transaction(placeholder_txn(sender.account(), 10, 1_000));
+    assert_ne!(
+        output.status().vm_status().major_status,
+        StatusCode::EXECUTED
+    );
+
+    let mut vm = LibraVM::new();
+    vm.set_argument_preprocessor(PayeePlaceholder(*receiver.address()));
+    let output = vm
+        .execute_single_transaction(
+            placeholder_txn(sender.account(), 10, 1_000),
+            executor.get_state_view(),
+        )
+        .unwrap();
+    assert_eq!(
+        output.status().vm_status().major_status,
+        StatusCode::EXECUTED
+    );
+    // Same payment as with the address in the arguments.
+    let expected = executor.execute_transaction(peer_to_peer_txn(
+        sender.account(),
+        receiver.account(),
+        10,
+        1_000,
+    ));
+    assert_eq!(output.events(), expected.events());
+    assert_eq!(output.gas_used(), expected.gas_used());
+
+    vm.clear_argument_preprocessor();
+    let output = vm
+        .execute_single_transaction(
+            placeholder_txn(sender.account(), 10, 1_000),
+            executor.get_state_view(),
+        )
+        .unwrap();
+    assert_ne!(
+        output.status().vm_status().major_status,
+        StatusCode::EXECUTED
+    );
+}
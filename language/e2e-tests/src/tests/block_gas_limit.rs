@@ -0,0 +1,56 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{account::AccountData, common_transactions::peer_to_peer_txn, executor::FakeExecutor};
+use libra_types::transaction::{Transaction, TransactionStatus};
+use libra_vm::{LibraVM, VMExecutor};
+
+#[test]
+fn transactions_past_block_gas_limit_are_retried() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let block: Vec<_> = (10..14)
+        .map(|seq_num| {
+            Transaction::UserTransaction(peer_to_peer_txn(
+                sender.account(),
+                receiver.account(),
+                seq_num,
+                1_000,
+            ))
+        })
+        .collect();
+    let unlimited_outputs = executor.execute_transaction_block(block.clone()).unwrap();
+
+    // The limit is exactly reached by the second transaction.
+    let limit = unlimited_outputs[0].gas_used() + unlimited_outputs[1].gas_used();
+    let outputs =
+        LibraVM::execute_block_with_gas_limit(block.clone(), executor.get_state_view(), limit)
+            .unwrap();
+    assert_eq!(outputs.len(), 4);
+    assert_eq!(outputs[..2], unlimited_outputs[..2]);
+    for output in &outputs[2..] {
+        assert_eq!(output.status(), &TransactionStatus::Retry);
+    }
+
+    // The transaction crossing the limit keeps its output.
+    let limit = unlimited_outputs[0].gas_used() + 1;
+    let outputs =
+        LibraVM::execute_block_with_gas_limit(block.clone(), executor.get_state_view(), limit)
+            .unwrap();
+    assert_eq!(outputs[..2], unlimited_outputs[..2]);
+    assert_eq!(outputs[2].status(), &TransactionStatus::Retry);
+
+    // A limit not reached changes nothing.
+    let limit = unlimited_outputs
+        .iter()
+        .map(|output| output.gas_used())
+        .sum::<u64>()
+        + 1;
+    let outputs =
+        LibraVM::execute_block_with_gas_limit(block, executor.get_state_view(), limit).unwrap();
+    assert_eq!(outputs, unlimited_outputs);
+}
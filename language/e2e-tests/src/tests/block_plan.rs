@@ -0,0 +1,93 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{account::Account, common_transactions::peer_to_peer_txn};
+use libra_crypto::HashValue;
+use libra_types::{
+    block_metadata::BlockMetadata,
+    transaction::{ChangeSet, Transaction},
+    write_set::WriteSet,
+};
+use libra_vm::{
+    block_plan::{assert_distinct_senders, BlockPlan, BlockPlanStep},
+    LibraVM,
+};
+use std::collections::BTreeMap;
+
+#[test]
+fn plan_matches_block_structure() {
+    let alice = Account::new();
+    let bob = Account::new();
+    let block_id = HashValue::random();
+    let block_metadata = BlockMetadata::new(block_id, 1, 1, vec![], *alice.address());
+    let p2p = |sender: &Account, receiver: &Account, seq_num| {
+        Transaction::UserTransaction(peer_to_peer_txn(sender, receiver, seq_num, 1_000))
+    };
+    let write_set_txn = bob
+        .transaction()
+        .write_set(ChangeSet::new(WriteSet::default(), vec![]))
+        .sequence_number(0)
+        .sign();
+
+    let plan = LibraVM::plan_block(vec![
+        Transaction::BlockMetadata(block_metadata),
+        p2p(&alice, &bob, 0),
+        p2p(&alice, &bob, 1),
+        p2p(&bob, &alice, 0),
+        Transaction::UserTransaction(write_set_txn),
+        p2p(&bob, &alice, 1),
+    ]);
+
+    let mut first_senders = BTreeMap::new();
+    first_senders.insert(*alice.address(), 2);
+    first_senders.insert(*bob.address(), 1);
+    let mut second_senders = BTreeMap::new();
+    second_senders.insert(*bob.address(), 1);
+    assert_eq!(
+        plan,
+        BlockPlan {
+            steps: vec![
+                BlockPlanStep::BlockPrologue { block_id },
+                BlockPlanStep::UserTransactions {
+                    transaction_count: 3,
+                    senders: first_senders,
+                },
+                BlockPlanStep::WriteSet {
+                    sender: *bob.address(),
+                },
+                BlockPlanStep::UserTransactions {
+                    transaction_count: 1,
+                    senders: second_senders,
+                },
+            ],
+        }
+    );
+    assert_eq!(plan.transaction_count(), 6);
+}
+
+#[test]
+fn distinct_senders() {
+    let alice = Account::new();
+    let bob = Account::new();
+    let carol = Account::new();
+
+    assert_eq!(assert_distinct_senders(&[]), Ok(()));
+    assert_eq!(
+        assert_distinct_senders(&[
+            peer_to_peer_txn(&alice, &bob, 0, 1_000),
+            peer_to_peer_txn(&bob, &carol, 0, 1_000),
+            peer_to_peer_txn(&carol, &alice, 0, 1_000),
+        ]),
+        Ok(())
+    );
+    // Bob sends a second transaction before Alice does.
+    assert_eq!(
+        assert_distinct_senders(&[
+            peer_to_peer_txn(&alice, &bob, 0, 1_000),
+            peer_to_peer_txn(&bob, &carol, 0, 1_000),
+            peer_to_peer_txn(&bob, &carol, 1, 1_000),
+            peer_to_peer_txn(&alice, &bob, 1, 1_000),
+        ]),
+        Err(*bob.address())
+    );
+}
@@ -0,0 +1,205 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    account::{Account, AccountData},
+    common_transactions::peer_to_peer_txn,
+    executor::FakeExecutor,
+};
+use libra_crypto::HashValue;
+use libra_types::{
+    account_config,
+    block_metadata::{new_block_event_key, BlockMetadata, NewBlockEvent},
+    on_chain_config::{OnChainConfig, ValidatorSet},
+    transaction::{Transaction, TransactionOutput, TransactionStatus},
+    vm_status::{StatusCode, VMStatus},
+};
+use libra_vm::{
+    block_prologue::{BlockPrologueArgs, BlockPrologueInputs},
+    executor_config::ExecutorConfig,
+    LibraVM,
+};
+use move_vm_types::values::Value;
+
+fn execute_block_prologue(config: ExecutorConfig) -> TransactionOutput {
+    let executor = FakeExecutor::from_genesis_file();
+    let validator_set = ValidatorSet::fetch_config(executor.get_state_view()).unwrap();
+    let block_metadata = BlockMetadata::new(
+        HashValue::zero(),
+        5,
+        1,
+        vec![],
+        *validator_set.payload()[0].account_address(),
+    );
+    let output = LibraVM::new_with_config(config)
+        .execute_transaction_block(
+            vec![Transaction::BlockMetadata(block_metadata)],
+            executor.get_state_view(),
+        )
+        .unwrap()
+        .pop()
+        .unwrap();
+    assert!(!output.status().is_discarded());
+    output
+}
+
+fn new_block_event_round(config: ExecutorConfig) -> u64 {
+    let output = execute_block_prologue(config);
+    let event = output
+        .events()
+        .iter()
+        .find(|event| *event.key() == new_block_event_key())
+        .unwrap();
+    lcs::from_bytes::<NewBlockEvent>(event.event_data())
+        .unwrap()
+        .round()
+}
+
+#[test]
+fn block_prologue_with_custom_args() {
+    assert_eq!(new_block_event_round(ExecutorConfig::default()), 5);
+
+    // Same arguments as the standard layout, with the round shifted.
+    let shifted_round = |inputs: BlockPrologueInputs| {
+        vec![
+            Value::transaction_argument_signer_reference(inputs.vm_address),
+            Value::u64(inputs.round + 100),
+            Value::u64(inputs.timestamp_usecs),
+            Value::vector_address(inputs.previous_block_votes),
+            Value::address(inputs.proposer),
+        ]
+    };
+    let config = ExecutorConfig {
+        block_prologue_args: BlockPrologueArgs {
+            build: shifted_round,
+        },
+        ..ExecutorConfig::default()
+    };
+    assert_eq!(new_block_event_round(config), 105);
+}
+
+#[test]
+fn metered_block_prologue() {
+    let free = execute_block_prologue(ExecutorConfig::default());
+    assert_eq!(free.gas_used(), 0);
+
+    let samples_before = LibraVM::metrics_snapshot()
+        .block_prologue_gas_usage
+        .sample_count;
+    let metered = execute_block_prologue(ExecutorConfig {
+        meter_block_prologue: true,
+        ..ExecutorConfig::default()
+    });
+    assert!(metered.gas_used() > 0);
+    assert_eq!(
+        metered.status(),
+        &TransactionStatus::Keep(VMStatus::executed())
+    );
+    // Only the gas differs.
+    assert_eq!(metered.write_set(), free.write_set());
+    assert_eq!(metered.events(), free.events());
+    assert!(
+        LibraVM::metrics_snapshot()
+            .block_prologue_gas_usage
+            .sample_count
+            > samples_before
+    );
+}
+
+#[test]
+fn block_prologue_with_custom_reserved_vm_address() {
+    let executor = FakeExecutor::from_genesis_file();
+    let validator_set = ValidatorSet::fetch_config(executor.get_state_view()).unwrap();
+    let execute = |config: ExecutorConfig| {
+        let block_metadata = BlockMetadata::new(
+            HashValue::zero(),
+            5,
+            1,
+            vec![],
+            *validator_set.payload()[0].account_address(),
+        );
+        LibraVM::new_with_config(config).execute_transaction_block(
+            vec![Transaction::BlockMetadata(block_metadata)],
+            executor.get_state_view(),
+        )
+    };
+    let custom_address_config = || ExecutorConfig {
+        reserved_vm_address: account_config::libra_root_address(),
+        ..ExecutorConfig::default()
+    };
+
+    // The framework of the genesis only accepts its own reserved address as the signer.
+    let status = execute(custom_address_config()).unwrap_err();
+    assert_eq!(status.major_status, StatusCode::ABORTED);
+    assert_eq!(status.sub_status, Some(2));
+
+    // The prologue is told about the configured address.
+    let standard_signer = |inputs: BlockPrologueInputs| {
+        assert_eq!(inputs.vm_address, account_config::libra_root_address());
+        vec![
+            Value::transaction_argument_signer_reference(account_config::reserved_vm_address()),
+            Value::u64(inputs.round),
+            Value::u64(inputs.timestamp_usecs),
+            Value::vector_address(inputs.previous_block_votes),
+            Value::address(inputs.proposer),
+        ]
+    };
+    let outputs = execute(ExecutorConfig {
+        block_prologue_args: BlockPrologueArgs {
+            build: standard_signer,
+        },
+        ..custom_address_config()
+    })
+    .unwrap();
+    assert_eq!(
+        outputs[0].status(),
+        &TransactionStatus::Keep(VMStatus::executed())
+    );
+}
+
+#[test]
+fn lenient_block_prologue() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = Account::new();
+    executor.add_account_data(&sender);
+    let validator_set = ValidatorSet::fetch_config(executor.get_state_view()).unwrap();
+    let block = || {
+        vec![
+            Transaction::BlockMetadata(BlockMetadata::new(
+                HashValue::zero(),
+                5,
+                1,
+                vec![],
+                *validator_set.payload()[0].account_address(),
+            )),
+            Transaction::UserTransaction(peer_to_peer_txn(sender.account(), &receiver, 10, 1_000)),
+        ]
+    };
+    // The framework of the genesis only accepts its own reserved address as the signer, so the
+    // prologue aborts.
+    let mut vm = LibraVM::new_with_config(ExecutorConfig {
+        reserved_vm_address: account_config::libra_root_address(),
+        ..ExecutorConfig::default()
+    });
+    let status = vm
+        .execute_transaction_block(block(), executor.get_state_view())
+        .unwrap_err();
+    assert_eq!(status.major_status, StatusCode::ABORTED);
+
+    let outputs = vm
+        .execute_block_with_lenient_prologue(block(), executor.get_state_view())
+        .unwrap();
+    assert_eq!(outputs.len(), 2);
+    assert_eq!(outputs[0].status(), &TransactionStatus::Discard(status));
+    assert!(outputs[0].write_set().is_empty());
+    // The user transaction runs on top of the state without the prologue's writes.
+    assert_eq!(
+        outputs[1].status(),
+        &TransactionStatus::Keep(VMStatus::executed())
+    );
+    assert_eq!(
+        outputs[1],
+        executor.execute_transaction(peer_to_peer_txn(sender.account(), &receiver, 10, 1_000))
+    );
+}
@@ -0,0 +1,149 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    account::AccountData,
+    common_transactions::{peer_to_peer_txn, script_txn},
+    executor::FakeExecutor,
+};
+use libra_types::{
+    transaction::{SignedTransaction, Transaction},
+    vm_status::StatusCode,
+};
+use libra_vm::LibraVM;
+use std::collections::HashMap;
+
+/// A script looping long enough to use much more gas than a peer to peer payment.
+fn heavy_txn(sender: &AccountData, seq_num: u64) -> SignedTransaction {
+    let program = "
+        main() {
+            let i: u64;
+            i = 0;
+            while (copy(i) < 1000) {
+                i = move(i) + 1;
+            }
+            return;
+        }
+    ";
+    script_txn(sender.account(), seq_num, program, 1_000_000)
+}
+
+#[test]
+fn block_summary_reports_max_gas_transaction() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(10_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let block = vec![
+        peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000),
+        heavy_txn(&sender, 11),
+        peer_to_peer_txn(sender.account(), receiver.account(), 12, 1_000),
+        // Discarded: the sequence number is too old.
+        peer_to_peer_txn(sender.account(), receiver.account(), 5, 1_000),
+    ];
+    let mut vm = LibraVM::new();
+    let outputs = vm
+        .execute_transaction_block(
+            block
+                .into_iter()
+                .map(Transaction::UserTransaction)
+                .collect(),
+            executor.get_state_view(),
+        )
+        .unwrap();
+    assert!(!outputs[1].status().is_discarded());
+    assert!(outputs[1].gas_used() > 2 * outputs[0].gas_used());
+    assert!(outputs[3].status().is_discarded());
+
+    let summary = vm.block_summary();
+    assert_eq!(summary.transaction_count, 4);
+    assert_eq!(summary.kept_count, 3);
+    assert_eq!(summary.discarded_count, 1);
+    assert_eq!(
+        summary.gas_used,
+        outputs.iter().map(|output| output.gas_used()).sum::<u64>()
+    );
+    assert_eq!(
+        summary.max_gas_transaction,
+        Some((1, outputs[1].gas_used()))
+    );
+}
+
+#[test]
+fn block_summary_counts_events_of_kept_transactions() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(10_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let block = vec![
+        peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000),
+        peer_to_peer_txn(sender.account(), receiver.account(), 11, 1_000),
+        // Emits no event.
+        heavy_txn(&sender, 12),
+        // Discarded: the sequence number is too old.
+        peer_to_peer_txn(sender.account(), receiver.account(), 5, 1_000),
+    ];
+    let mut vm = LibraVM::new();
+    let outputs = vm
+        .execute_transaction_block(
+            block
+                .into_iter()
+                .map(Transaction::UserTransaction)
+                .collect(),
+            executor.get_state_view(),
+        )
+        .unwrap();
+    // Each payment emits a sent and a received payment event.
+    assert_eq!(outputs[0].events().len(), 2);
+    assert_eq!(outputs[1].events().len(), 2);
+    assert!(outputs[2].events().is_empty());
+    assert!(outputs[3].status().is_discarded());
+    assert_eq!(vm.block_summary().total_events, 4);
+}
+
+#[test]
+fn block_summary_counts_statuses() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(10_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let program = "
+        main() {
+            abort 77;
+        }
+    ";
+    let aborting_txn = script_txn(sender.account(), 11, program, 100_000);
+    let block = vec![
+        peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000),
+        peer_to_peer_txn(sender.account(), receiver.account(), 5, 1_000),
+        aborting_txn,
+        peer_to_peer_txn(sender.account(), receiver.account(), 6, 1_000),
+        peer_to_peer_txn(sender.account(), receiver.account(), 12, 1_000),
+        peer_to_peer_txn(sender.account(), receiver.account(), 20, 1_000),
+    ];
+    let mut vm = LibraVM::new();
+    vm.execute_transaction_block(
+        block
+            .into_iter()
+            .map(Transaction::UserTransaction)
+            .collect(),
+        executor.get_state_view(),
+    )
+    .unwrap();
+
+    let expected: HashMap<_, _> = vec![
+        (StatusCode::EXECUTED, 2),
+        (StatusCode::ABORTED, 1),
+        (StatusCode::SEQUENCE_NUMBER_TOO_OLD, 2),
+        (StatusCode::SEQUENCE_NUMBER_TOO_NEW, 1),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(vm.block_summary().status_counts, expected);
+}
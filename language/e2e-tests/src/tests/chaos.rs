@@ -0,0 +1,107 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{account::AccountData, common_transactions::peer_to_peer_txn, executor::FakeExecutor};
+use libra_types::transaction::{Transaction, TransactionStatus};
+use libra_vm::{
+    executor_config::{ChaosConfig, ExecutorConfig},
+    LibraVM,
+};
+use std::time::{Duration, Instant};
+
+fn execute_p2p_block(vm: &mut LibraVM) -> Duration {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let block = (10..12)
+        .map(|seq_num| {
+            Transaction::UserTransaction(peer_to_peer_txn(
+                sender.account(),
+                receiver.account(),
+                seq_num,
+                1_000,
+            ))
+        })
+        .collect();
+    let start = Instant::now();
+    let outputs = vm
+        .execute_transaction_block(block, executor.get_state_view())
+        .unwrap();
+    let elapsed = start.elapsed();
+    for output in outputs {
+        assert!(!output.status().is_discarded());
+    }
+    elapsed
+}
+
+#[test]
+fn no_latency_by_default() {
+    let mut vm = LibraVM::new();
+    execute_p2p_block(&mut vm);
+    assert_eq!(vm.injected_latency(), Duration::from_secs(0));
+}
+
+#[test]
+fn latency_injected_per_transaction() {
+    let latency = Duration::from_millis(50);
+    let mut vm = LibraVM::new_with_config(ExecutorConfig {
+        chaos: ChaosConfig {
+            transaction_latency: latency,
+        },
+        ..ExecutorConfig::default()
+    });
+    let elapsed = execute_p2p_block(&mut vm);
+    assert_eq!(vm.injected_latency(), latency * 2);
+    assert!(elapsed >= latency * 2);
+}
+
+#[test]
+fn transactions_after_tight_deadline_are_retried() {
+    let latency = Duration::from_millis(200);
+    let mut vm = LibraVM::new_with_config(ExecutorConfig {
+        chaos: ChaosConfig {
+            transaction_latency: latency,
+        },
+        ..ExecutorConfig::default()
+    });
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let block = (10..15)
+        .map(|seq_num| {
+            Transaction::UserTransaction(peer_to_peer_txn(
+                sender.account(),
+                receiver.account(),
+                seq_num,
+                1_000,
+            ))
+        })
+        .collect();
+    // At most three of the transactions can start before the deadline.
+    let outputs = vm
+        .execute_block_with_deadline(
+            block,
+            executor.get_state_view(),
+            Instant::now() + latency * 5 / 2,
+        )
+        .unwrap();
+
+    let executed = outputs
+        .iter()
+        .take_while(|output| output.status() != &TransactionStatus::Retry)
+        .count();
+    assert!(executed >= 1);
+    assert!(executed < outputs.len());
+    for output in &outputs[..executed] {
+        assert!(!output.status().is_discarded());
+    }
+    for output in &outputs[executed..] {
+        assert_eq!(output.status(), &TransactionStatus::Retry);
+    }
+}
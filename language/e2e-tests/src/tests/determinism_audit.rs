@@ -0,0 +1,42 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! With the `determinism-audit` feature every user transaction is executed twice and the outputs
+//! compared. A transaction whose output depends on anything but the state it runs against, e.g.
+//! a native iterating over a `HashMap` to build its results or a cache in the loader leaking
+//! into the write set, gets two different outputs and the block execution panics with the
+//! sender and sequence number of the transaction.
+
+use crate::{account::AccountData, common_transactions::peer_to_peer_txn, executor::FakeExecutor};
+use libra_types::transaction::Transaction;
+use libra_vm::LibraVM;
+
+#[test]
+fn deterministic_transactions_pass_the_audit() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let block = vec![
+        peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000),
+        peer_to_peer_txn(sender.account(), receiver.account(), 11, 1_000),
+        // Discarded: the sequence number is too old.
+        peer_to_peer_txn(sender.account(), receiver.account(), 5, 1_000),
+    ];
+    let mut vm = LibraVM::new();
+    let outputs = vm
+        .execute_transaction_block(
+            block
+                .into_iter()
+                .map(Transaction::UserTransaction)
+                .collect(),
+            executor.get_state_view(),
+        )
+        .unwrap();
+    assert!(!outputs[0].status().is_discarded());
+    assert!(!outputs[1].status().is_discarded());
+    assert!(outputs[2].status().is_discarded());
+    assert_eq!(vm.audited_transactions(), 3);
+}
@@ -0,0 +1,409 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    account::AccountData,
+    common_transactions::{peer_to_peer_txn, script_txn, script_txn_impl},
+    executor::FakeExecutor,
+};
+use compiler::Compiler;
+use libra_state_view::StateView;
+use libra_types::{
+    access_path::AccessPath, account_config::CORE_CODE_ADDRESS, transaction::Transaction,
+    vm_status::StatusCode,
+};
+use libra_vm::{
+    diagnostics::{failure_call_stack, GasAttribution, StackFrame},
+    executor_config::ExecutorConfig,
+    LibraVM,
+};
+use move_core_types::{
+    gas_schedule::{AbstractMemorySize, GasAlgebra},
+    identifier::Identifier,
+    language_storage::ModuleId,
+};
+use move_vm_runtime::call_trace::TraceEventKind;
+use move_vm_types::gas_schedule::calculate_intrinsic_gas;
+
+fn diagnostics_config() -> ExecutorConfig {
+    ExecutorConfig {
+        collect_diagnostics: true,
+        ..ExecutorConfig::default()
+    }
+}
+
+#[test]
+fn diagnostics_off_by_default() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let txn = peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000);
+    let mut vm = LibraVM::new();
+    vm.execute_transaction_block(
+        vec![Transaction::UserTransaction(txn)],
+        executor.get_state_view(),
+    )
+    .unwrap();
+    assert!(vm.take_diagnostics().is_empty());
+}
+
+#[test]
+fn gas_available_for_script() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let txn = peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000);
+    let max_gas_amount = txn.max_gas_amount();
+    let txn_size = AbstractMemorySize::new(txn.raw_txn_bytes_len() as u64);
+
+    let mut vm = LibraVM::new_with_config(diagnostics_config());
+    let outputs = vm
+        .execute_transaction_block(
+            vec![Transaction::UserTransaction(txn)],
+            executor.get_state_view(),
+        )
+        .unwrap();
+    assert!(!outputs[0].status().is_discarded());
+
+    let gas_constants = &vm.internals().gas_schedule().unwrap().gas_constants;
+    let intrinsic_gas = calculate_intrinsic_gas(txn_size, gas_constants).get();
+    // The prologue runs with metering disabled, so its cost is zero.
+    let prologue_gas = 0;
+
+    let diagnostics = vm.take_diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].gas_available_for_script,
+        Some(max_gas_amount - intrinsic_gas - prologue_gas)
+    );
+}
+
+#[test]
+fn gas_attribution_adds_up_to_gas_used() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let payment = peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000);
+    let txn_size = AbstractMemorySize::new(payment.raw_txn_bytes_len() as u64);
+    let aborting_txn = script_txn_impl(
+        sender.account(),
+        11,
+        "
+        main() {
+            abort 77;
+        }
+        ",
+        vec![],
+        vec![],
+        100_000,
+        1,
+    );
+
+    let mut vm = LibraVM::new_with_config(diagnostics_config());
+    let outputs = vm
+        .execute_transaction_block(
+            vec![
+                Transaction::UserTransaction(payment),
+                Transaction::UserTransaction(aborting_txn),
+            ],
+            executor.get_state_view(),
+        )
+        .unwrap();
+    assert!(outputs.iter().all(|output| !output.status().is_discarded()));
+
+    let gas_constants = &vm.internals().gas_schedule().unwrap().gas_constants;
+    let intrinsic_gas = calculate_intrinsic_gas(txn_size, gas_constants).get();
+    let diagnostics = vm.take_diagnostics();
+    let attributions: Vec<GasAttribution> = diagnostics
+        .iter()
+        .map(|diagnostics| diagnostics.gas_attribution.unwrap())
+        .collect();
+    for (attribution, output) in attributions.iter().zip(&outputs) {
+        assert_eq!(attribution.total(), output.gas_used());
+        assert!(attribution.execution > 0);
+        // The epilogue is not metered.
+        assert_eq!(attribution.epilogue, 0);
+    }
+    assert_eq!(attributions[0].prologue, intrinsic_gas);
+}
+
+#[test]
+fn gas_left_at_failure() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let payment = peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000);
+    let aborting_txn = script_txn_impl(
+        sender.account(),
+        11,
+        "
+        main() {
+            abort 77;
+        }
+        ",
+        vec![],
+        vec![],
+        100_000,
+        1,
+    );
+    let block = vec![
+        Transaction::UserTransaction(payment),
+        Transaction::UserTransaction(aborting_txn),
+    ];
+
+    let mut vm = LibraVM::new_with_config(diagnostics_config());
+    let outputs = vm
+        .execute_transaction_block(block.clone(), executor.get_state_view())
+        .unwrap();
+    assert_eq!(
+        outputs[1].status().vm_status().major_status,
+        StatusCode::ABORTED
+    );
+    let diagnostics = vm.take_diagnostics();
+    assert_eq!(diagnostics[0].gas_left_at_failure, None);
+    let attribution = diagnostics[1].gas_attribution.unwrap();
+    assert_eq!(
+        diagnostics[1].gas_left_at_failure,
+        Some(100_000 - attribution.prologue - attribution.execution)
+    );
+
+    // Instrumentation only: the outputs are the same without diagnostics.
+    assert_eq!(
+        outputs,
+        LibraVM::new()
+            .execute_transaction_block(block, executor.get_state_view())
+            .unwrap()
+    );
+}
+
+#[test]
+fn execute_script_with_breakdown() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let txn = peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000);
+    let txn_size = AbstractMemorySize::new(txn.raw_txn_bytes_len() as u64);
+    let mut vm = LibraVM::new();
+    let (output, breakdown) =
+        vm.execute_script_with_breakdown(txn.clone(), executor.get_state_view());
+    // The breakdown does not change the output.
+    assert_eq!(output, executor.execute_transaction(txn));
+
+    let breakdown = breakdown.unwrap();
+    let gas_constants = &vm.internals().gas_schedule().unwrap().gas_constants;
+    assert_eq!(
+        breakdown.intrinsic,
+        calculate_intrinsic_gas(txn_size, gas_constants).get()
+    );
+    assert!(breakdown.execution > 0);
+    assert_eq!(
+        breakdown.intrinsic + breakdown.execution + breakdown.epilogue,
+        output.gas_used()
+    );
+
+    // Discarded by the prologue: the sequence number is too old.
+    let txn = peer_to_peer_txn(sender.account(), receiver.account(), 5, 1_000);
+    let (output, breakdown) = vm.execute_script_with_breakdown(txn, executor.get_state_view());
+    assert!(output.status().is_discarded());
+    assert_eq!(breakdown, None);
+}
+
+#[test]
+fn execute_script_traced() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    executor.add_account_data(&sender);
+
+    let program = "
+        import 0x1.Signer;
+
+        main(account: &signer) {
+            let addr: address;
+            addr = Signer.address_of(move(account));
+            abort 77;
+        }
+    ";
+    let txn = script_txn(sender.account(), 10, program, 100_000);
+    let mut vm = LibraVM::new();
+    let (output, trace) = vm.execute_script_traced(txn.clone(), executor.get_state_view());
+    // Tracing does not change the output.
+    assert_eq!(output, executor.execute_transaction(txn));
+    assert_eq!(
+        output.status().vm_status().major_status,
+        StatusCode::ABORTED
+    );
+
+    let signer = Some(ModuleId::new(
+        CORE_CODE_ADDRESS,
+        Identifier::new("Signer").unwrap(),
+    ));
+    let calls: Vec<_> = trace
+        .iter()
+        .map(|event| {
+            (
+                event.kind,
+                event.module.clone(),
+                event.function.as_str(),
+                event.depth,
+            )
+        })
+        .collect();
+    assert_eq!(
+        calls,
+        vec![
+            (TraceEventKind::Enter, None, "main", 0),
+            (TraceEventKind::Enter, signer.clone(), "address_of", 1),
+            (TraceEventKind::Enter, signer.clone(), "borrow_address", 2),
+            (TraceEventKind::Exit, signer.clone(), "borrow_address", 2),
+            (TraceEventKind::Exit, signer, "address_of", 1),
+            (TraceEventKind::Error(StatusCode::ABORTED), None, "main", 0),
+        ]
+    );
+    // The gas left only goes down.
+    assert!(trace
+        .windows(2)
+        .all(|pair| pair[0].gas_remaining >= pair[1].gas_remaining));
+}
+
+#[test]
+fn failure_call_stack_of_nested_abort() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    executor.add_account_data(&sender);
+
+    let module_code = "
+        module M {
+            public outer() {
+                Self.helper();
+                Self.inner();
+                return;
+            }
+
+            helper() {
+                return;
+            }
+
+            inner() {
+                abort 42;
+            }
+        }
+    ";
+    let module = Compiler {
+        address: *sender.address(),
+        ..Compiler::default()
+    }
+    .into_compiled_module("file_name", module_code)
+    .unwrap();
+    let module_id = module.self_id();
+    executor.add_module(&module_id, &module);
+
+    let program = format!(
+        "
+        import 0x{}.M;
+
+        main() {{
+            M.outer();
+            return;
+        }}
+        ",
+        sender.address()
+    );
+    let txn = script_txn_impl(
+        sender.account(),
+        10,
+        &program,
+        vec![module],
+        vec![],
+        100_000,
+        0,
+    );
+    let (output, trace) = LibraVM::new().execute_script_traced(txn, executor.get_state_view());
+    assert_eq!(output.status().vm_status().sub_status, Some(42));
+
+    let frame = |module: Option<&ModuleId>, function: &str| StackFrame {
+        module: module.cloned(),
+        function: function.to_string(),
+    };
+    // The helper returned before the abort, so it is not on the stack.
+    assert_eq!(
+        failure_call_stack(&trace),
+        Some(vec![
+            frame(None, "main"),
+            frame(Some(&module_id), "outer"),
+            frame(Some(&module_id), "inner"),
+        ])
+    );
+    assert_eq!(failure_call_stack(&trace[..trace.len() - 1]), None);
+}
+
+#[test]
+fn diagnostics_record_state_witness() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let block = vec![
+        Transaction::UserTransaction(peer_to_peer_txn(
+            sender.account(),
+            receiver.account(),
+            10,
+            1_000,
+        )),
+        Transaction::UserTransaction(peer_to_peer_txn(
+            sender.account(),
+            receiver.account(),
+            11,
+            1_000,
+        )),
+    ];
+    let mut vm = LibraVM::new_with_config(diagnostics_config());
+    vm.execute_transaction_block(block.clone(), executor.get_state_view())
+        .unwrap();
+    assert!(vm
+        .take_diagnostics()
+        .iter()
+        .all(|diagnostics| diagnostics.state_witness.is_empty()));
+
+    let mut vm = LibraVM::new_with_config(ExecutorConfig {
+        record_state_witness: true,
+        ..diagnostics_config()
+    });
+    let outputs = vm
+        .execute_transaction_block(block, executor.get_state_view())
+        .unwrap();
+    let diagnostics = vm.take_diagnostics();
+    let account_path = sender.make_account_access_path();
+    let account_bytes = |witness: &[(AccessPath, Vec<u8>)]| {
+        witness
+            .iter()
+            .find(|(path, _)| *path == account_path)
+            .map(|(_, bytes)| bytes.clone())
+    };
+    assert_eq!(
+        account_bytes(&diagnostics[0].state_witness),
+        executor.get_state_view().get(&account_path).unwrap()
+    );
+    // The second transaction reads the account as written by the first one.
+    executor.apply_write_set(outputs[0].write_set());
+    assert_eq!(
+        account_bytes(&diagnostics[1].state_witness),
+        executor.get_state_view().get(&account_path).unwrap()
+    );
+}
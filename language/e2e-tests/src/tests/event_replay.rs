@@ -0,0 +1,89 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{account::AccountData, common_transactions::peer_to_peer_txn, executor::FakeExecutor};
+use libra_types::transaction::{Transaction, TransactionOutput};
+use libra_vm::{
+    event_consistency::EventConsistencyCheck, executor_config::ExecutorConfig, LibraVM,
+};
+
+fn event_sequence_numbers(outputs: &[TransactionOutput]) -> Vec<Vec<(Vec<u8>, u64)>> {
+    outputs
+        .iter()
+        .map(|output| {
+            output
+                .events()
+                .iter()
+                .map(|event| (event.key().to_vec(), event.sequence_number()))
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn replayed_block_has_identical_event_sequence_numbers() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(10_000_000, 10);
+    let receiver = AccountData::new(1_000_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let block = || {
+        vec![
+            peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000),
+            peer_to_peer_txn(receiver.account(), sender.account(), 10, 500),
+            peer_to_peer_txn(sender.account(), receiver.account(), 11, 2_000),
+            peer_to_peer_txn(sender.account(), receiver.account(), 12, 3_000),
+        ]
+    };
+
+    let first = executor.execute_block(block()).unwrap();
+    let second = executor.execute_block(block()).unwrap();
+    for output in &first {
+        assert!(!output.status().is_discarded());
+    }
+    assert_eq!(first, second);
+    assert_eq!(
+        event_sequence_numbers(&first),
+        event_sequence_numbers(&second)
+    );
+
+    // Events on the same handle get consecutive sequence numbers, in block order, starting from
+    // the counter stored in the account.
+    let sent: Vec<_> = first
+        .iter()
+        .flat_map(|output| output.events())
+        .filter(|event| event.key().as_bytes() == sender.sent_events_key())
+        .map(|event| event.sequence_number())
+        .collect();
+    let start = sender.sent_events_count();
+    assert_eq!(sent, vec![start, start + 1, start + 2]);
+}
+
+#[test]
+fn executed_block_passes_event_consistency_check() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(10_000_000, 10);
+    let receiver = AccountData::new(1_000_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let block = (10..13)
+        .map(|seq_num| {
+            Transaction::UserTransaction(peer_to_peer_txn(
+                sender.account(),
+                receiver.account(),
+                seq_num,
+                1_000,
+            ))
+        })
+        .collect();
+    let mut vm = LibraVM::new_with_config(ExecutorConfig {
+        event_consistency_check: EventConsistencyCheck::Fail,
+        ..ExecutorConfig::default()
+    });
+    let outputs = vm
+        .execute_transaction_block(block, executor.get_state_view())
+        .unwrap();
+    assert_eq!(outputs.len(), 3);
+}
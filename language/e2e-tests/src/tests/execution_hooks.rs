@@ -0,0 +1,224 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{account::AccountData, common_transactions::peer_to_peer_txn, executor::FakeExecutor};
+use libra_state_view::StateView;
+use libra_types::{
+    contract_event::ContractEvent,
+    transaction::{Transaction, TransactionStatus},
+    vm_status::{StatusCode, VMStatus},
+    write_set::{WriteOp, WriteSet},
+};
+use libra_vm::{event_sink::EventSink, write_set_validator::WriteSetValidator, LibraVM};
+use move_core_types::account_address::AccountAddress;
+use std::{
+    cell::Cell,
+    sync::{Arc, Mutex},
+};
+
+#[test]
+fn block_hooks_fire_with_block_state() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+    let account_path = sender.make_account_access_path();
+    let initial_account = executor.get_state_view().get(&account_path).unwrap();
+
+    let block = (10..12)
+        .map(|seq_num| {
+            Transaction::UserTransaction(peer_to_peer_txn(
+                sender.account(),
+                receiver.account(),
+                seq_num,
+                1_000,
+            ))
+        })
+        .collect();
+    let pre_block_calls = Cell::new(0);
+    let post_block_calls = Cell::new(0);
+    let outputs = LibraVM::new()
+        .execute_block_with_hooks(
+            block,
+            executor.get_state_view(),
+            |cache| {
+                pre_block_calls.set(pre_block_calls.get() + 1);
+                // Nothing executed yet.
+                assert_eq!(cache.get(&account_path).unwrap(), initial_account);
+            },
+            |cache, outputs| {
+                post_block_calls.set(post_block_calls.get() + 1);
+                assert_eq!(outputs.len(), 2);
+                // The cache reflects the writes of the last transaction.
+                let last_write = outputs[1]
+                    .write_set()
+                    .iter()
+                    .find(|(path, _)| *path == account_path)
+                    .map(|(_, op)| op.clone());
+                match last_write {
+                    Some(WriteOp::Value(blob)) => {
+                        assert_eq!(cache.get(&account_path).unwrap(), Some(blob))
+                    }
+                    op => panic!("unexpected write to the sender account {:?}", op),
+                }
+            },
+        )
+        .unwrap();
+    assert_eq!(outputs.len(), 2);
+    assert_eq!(pre_block_calls.get(), 1);
+    assert_eq!(post_block_calls.get(), 1);
+}
+
+#[test]
+fn write_set_observer_sees_kept_outputs() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let block = vec![
+        peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000),
+        // Discarded: the sequence number is too old.
+        peer_to_peer_txn(sender.account(), receiver.account(), 5, 1_000),
+        peer_to_peer_txn(sender.account(), receiver.account(), 11, 1_000),
+    ];
+    let observed = Arc::new(Mutex::new(vec![]));
+    let mut vm = LibraVM::new();
+    {
+        let observed = Arc::clone(&observed);
+        vm.set_write_set_observer(move |write_set, events| {
+            observed
+                .lock()
+                .unwrap()
+                .push((write_set.clone(), events.to_vec()))
+        });
+    }
+    let outputs = vm
+        .execute_transaction_block(
+            block
+                .into_iter()
+                .map(Transaction::UserTransaction)
+                .collect(),
+            executor.get_state_view(),
+        )
+        .unwrap();
+    assert!(outputs[1].status().is_discarded());
+
+    let expected: Vec<_> = [&outputs[0], &outputs[2]]
+        .iter()
+        .map(|output| (output.write_set().clone(), output.events().to_vec()))
+        .collect();
+    assert_eq!(*observed.lock().unwrap(), expected);
+}
+
+struct SharedEvents(Arc<Mutex<Vec<ContractEvent>>>);
+
+impl EventSink for SharedEvents {
+    fn accept(&mut self, event: ContractEvent) {
+        self.0.lock().unwrap().push(event);
+    }
+}
+
+#[test]
+fn event_sink_receives_events_in_emission_order() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let block: Vec<_> = vec![
+        peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000),
+        peer_to_peer_txn(sender.account(), receiver.account(), 11, 2_000),
+        peer_to_peer_txn(receiver.account(), sender.account(), 10, 3_000),
+    ]
+    .into_iter()
+    .map(Transaction::UserTransaction)
+    .collect();
+    let expected: Vec<_> = LibraVM::new()
+        .execute_transaction_block(block.clone(), executor.get_state_view())
+        .unwrap()
+        .iter()
+        .flat_map(|output| output.events().to_vec())
+        .collect();
+    assert_eq!(expected.len(), 6);
+
+    let received = Arc::new(Mutex::new(vec![]));
+    let mut vm = LibraVM::new();
+    vm.set_event_sink(SharedEvents(Arc::clone(&received)));
+    let outputs = vm
+        .execute_transaction_block(block, executor.get_state_view())
+        .unwrap();
+    assert!(outputs.iter().all(|output| output.events().is_empty()));
+    assert_eq!(*received.lock().unwrap(), expected);
+}
+
+/// Only lets `owner` modify the resources stored under its address.
+struct OwnerOnly {
+    owner: AccountAddress,
+}
+
+impl WriteSetValidator for OwnerOnly {
+    fn validate(&self, sender: AccountAddress, write_set: &WriteSet) -> Result<(), VMStatus> {
+        if sender != self.owner
+            && write_set
+                .iter()
+                .any(|(access_path, _)| access_path.address == self.owner)
+        {
+            return Err(VMStatus::new(StatusCode::REJECTED_WRITE_SET, None, None));
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn write_set_validator_discards_rejected_outputs() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let owner = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&owner);
+    executor.add_account_data(&receiver);
+
+    let block: Vec<_> = vec![
+        // Deposits to the owner.
+        peer_to_peer_txn(sender.account(), owner.account(), 10, 1_000),
+        peer_to_peer_txn(owner.account(), receiver.account(), 10, 1_000),
+        // The sequence number of the sender was not bumped by the rejected payment.
+        peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000),
+    ]
+    .into_iter()
+    .map(Transaction::UserTransaction)
+    .collect();
+    let mut vm = LibraVM::new();
+    vm.set_write_set_validator(OwnerOnly {
+        owner: *owner.address(),
+    });
+    let outputs = vm
+        .execute_transaction_block(block.clone(), executor.get_state_view())
+        .unwrap();
+    assert_eq!(
+        outputs[0].status(),
+        &TransactionStatus::Discard(VMStatus::new(StatusCode::REJECTED_WRITE_SET, None, None))
+    );
+    assert!(outputs[0].write_set().is_empty());
+    assert_eq!(
+        outputs[1].status(),
+        &TransactionStatus::Keep(VMStatus::executed())
+    );
+    assert_eq!(
+        outputs[2].status(),
+        &TransactionStatus::Keep(VMStatus::executed())
+    );
+
+    // Without the validator, the first payment goes through and the last one is too old.
+    vm.clear_write_set_validator();
+    let outputs = vm
+        .execute_transaction_block(block, executor.get_state_view())
+        .unwrap();
+    assert!(!outputs[0].status().is_discarded());
+    assert!(outputs[2].status().is_discarded());
+}
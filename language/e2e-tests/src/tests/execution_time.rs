@@ -0,0 +1,156 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{account::AccountData, common_transactions::peer_to_peer_txn, executor::FakeExecutor};
+use libra_crypto::HashValue;
+use libra_types::{
+    block_metadata::BlockMetadata,
+    on_chain_config::{OnChainConfig, ValidatorSet},
+    transaction::{Transaction, TransactionStatus},
+};
+use libra_vm::LibraVM;
+use std::time::{Duration, Instant};
+
+#[test]
+fn estimate_block_duration_after_warmup() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(10_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let block = |seq_num| {
+        vec![Transaction::UserTransaction(peer_to_peer_txn(
+            sender.account(),
+            receiver.account(),
+            seq_num,
+            1_000,
+        ))]
+    };
+
+    let mut vm = LibraVM::new();
+    // Nothing has been observed yet.
+    assert_eq!(
+        vm.estimate_block_duration(&block(10)),
+        Duration::from_nanos(0)
+    );
+
+    for seq_num in 10..13 {
+        let outputs = vm
+            .execute_transaction_block(block(seq_num), executor.get_state_view())
+            .unwrap();
+        assert!(!outputs[0].status().is_discarded());
+        executor.apply_write_set(outputs[0].write_set());
+    }
+
+    assert!(vm.estimate_block_duration(&block(13)) > Duration::from_nanos(0));
+}
+
+#[test]
+fn transactions_past_deadline_are_retried() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(10_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let block: Vec<_> = (10..13)
+        .map(|seq_num| {
+            Transaction::UserTransaction(peer_to_peer_txn(
+                sender.account(),
+                receiver.account(),
+                seq_num,
+                1_000,
+            ))
+        })
+        .collect();
+
+    // A distant deadline does not change the outputs.
+    let mut vm = LibraVM::new();
+    let expected = vm
+        .execute_transaction_block(block.clone(), executor.get_state_view())
+        .unwrap();
+    let outputs = vm
+        .execute_block_with_deadline(
+            block.clone(),
+            executor.get_state_view(),
+            Instant::now() + Duration::from_secs(3600),
+        )
+        .unwrap();
+    assert_eq!(outputs, expected);
+
+    // Nothing is started once the deadline has passed.
+    let outputs = vm
+        .execute_block_with_deadline(block, executor.get_state_view(), Instant::now())
+        .unwrap();
+    assert_eq!(outputs.len(), 3);
+    for output in outputs {
+        assert_eq!(output.status(), &TransactionStatus::Retry);
+    }
+}
+
+#[test]
+fn execute_block_with_profile_times_each_phase() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(10_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+    let validator_set = ValidatorSet::fetch_config(executor.get_state_view()).unwrap();
+    let block_metadata = BlockMetadata::new(
+        HashValue::zero(),
+        1,
+        1,
+        vec![],
+        *validator_set.payload()[0].account_address(),
+    );
+
+    let block = vec![
+        Transaction::BlockMetadata(block_metadata),
+        Transaction::UserTransaction(peer_to_peer_txn(
+            sender.account(),
+            receiver.account(),
+            10,
+            1_000,
+        )),
+        Transaction::UserTransaction(peer_to_peer_txn(
+            sender.account(),
+            receiver.account(),
+            11,
+            1_000,
+        )),
+    ];
+    let mut vm = LibraVM::new();
+    let (outputs, profile) = vm
+        .execute_block_with_profile(block.clone(), executor.get_state_view())
+        .unwrap();
+    assert_eq!(
+        outputs,
+        vm.execute_transaction_block(block, executor.get_state_view())
+            .unwrap()
+    );
+    let stacks: Vec<_> = profile.samples().map(|(stack, _)| stack).collect();
+    assert_eq!(
+        stacks,
+        vec![
+            "execute_block;block_prologue",
+            "execute_block;config_load",
+            "execute_block;script;epilogue",
+            "execute_block;script;execution",
+            "execute_block;script;prologue",
+        ]
+    );
+
+    // Each stack is on its own line, followed by its time.
+    let folded = profile.to_string();
+    assert_eq!(folded.lines().count(), stacks.len());
+    for (line, (stack, micros)) in folded.lines().zip(profile.samples()) {
+        assert_eq!(line, format!("{} {}", stack, micros));
+    }
+
+    // Each profile only covers its own block.
+    let (_, profile) = vm
+        .execute_block_with_profile(vec![], executor.get_state_view())
+        .unwrap();
+    assert_eq!(profile.samples().count(), 0);
+}
@@ -3,11 +3,20 @@
 
 use crate::{
     account::{self, Account, AccountData},
-    common_transactions::peer_to_peer_txn,
+    common_transactions::{peer_to_peer_txn, script_txn},
     executor::FakeExecutor,
 };
-use libra_types::vm_status::{StatusCode, VMStatus};
-use libra_vm::{data_cache::StateViewCache, transaction_metadata::TransactionMetadata, LibraVM};
+use libra_types::{
+    transaction::{SignedTransaction, Transaction},
+    vm_status::{StatusCode, VMStatus},
+};
+use libra_vm::{
+    data_cache::StateViewCache,
+    diagnostics::{failure_reason, FailureReason},
+    executor_config::ExecutorConfig,
+    transaction_metadata::TransactionMetadata,
+    LibraVM,
+};
 use move_core_types::gas_schedule::{GasAlgebra, GasPrice, GasUnits};
 use move_vm_types::gas_schedule::zero_cost_schedule;
 
@@ -64,6 +73,39 @@ fn failed_transaction_cleanup_test() {
     );
 }
 
+#[test]
+fn failed_transaction_cleanup_keeps_original_status() {
+    let mut fake_executor = FakeExecutor::from_genesis_file();
+    // The sender can't pay for the gas, so the failure epilogue aborts.
+    let sender = AccountData::new(1_000, 10);
+    fake_executor.add_account_data(&sender);
+
+    let mut libra_vm = LibraVM::new();
+    let data_cache = StateViewCache::new(fake_executor.get_state_view());
+    libra_vm.load_configs(fake_executor.get_state_view());
+
+    let mut txn_data = TransactionMetadata::default();
+    txn_data.sender = *sender.address();
+    txn_data.sequence_number = 10;
+    txn_data.max_gas_amount = GasUnits::new(100_000);
+    txn_data.gas_unit_price = GasPrice::new(1);
+
+    let output = libra_vm.failed_transaction_cleanup(
+        VMStatus::new(StatusCode::ABORTED, Some(77), None),
+        &zero_cost_schedule(),
+        GasUnits::new(0),
+        &txn_data,
+        &data_cache,
+        &account::lbr_currency_code(),
+    );
+    assert!(output.status().is_discarded());
+    let message = output.status().vm_status().message.unwrap();
+    assert!(message.ends_with(&format!(
+        "failure epilogue run after {}",
+        VMStatus::new(StatusCode::ABORTED, Some(77), None)
+    )));
+}
+
 #[test]
 fn non_existent_sender() {
     let mut executor = FakeExecutor::from_genesis_file();
@@ -86,3 +128,161 @@ fn non_existent_sender() {
         StatusCode::SENDING_ACCOUNT_DOES_NOT_EXIST,
     );
 }
+
+fn emit_then_abort_txn(sender: &AccountData, seq_num: u64) -> SignedTransaction {
+    let program = "
+        import 0x1.Event;
+
+        main(account: &signer) {
+            let handle: Event.EventHandle<u64>;
+            handle = Event.new_event_handle<u64>(move(account));
+            Event.emit_event<u64>(&mut handle, 42);
+            Event.destroy_handle<u64>(move(handle));
+            abort 77;
+        }
+    ";
+    script_txn(sender.account(), seq_num, program, 100_000)
+}
+
+#[test]
+fn failed_transaction_keeps_pre_failure_events() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    executor.add_account_data(&sender);
+    let txn = emit_then_abort_txn(&sender, 10);
+
+    // By default the events emitted before the abort are dropped.
+    let default_output = executor.execute_transaction(txn.clone());
+    assert_eq!(
+        default_output.status().vm_status().major_status,
+        StatusCode::ABORTED
+    );
+
+    let mut vm = LibraVM::new_with_config(ExecutorConfig {
+        collect_diagnostics: true,
+        keep_failed_transaction_events: true,
+        ..ExecutorConfig::default()
+    });
+    let output = vm
+        .execute_transaction_block(
+            vec![Transaction::UserTransaction(txn)],
+            executor.get_state_view(),
+        )
+        .unwrap()
+        .pop()
+        .unwrap();
+    // The output is unchanged: the events emitted before the abort are kept apart from it.
+    assert_eq!(output, default_output);
+
+    let failed_events = vm.take_failed_transaction_events();
+    assert_eq!(failed_events.len(), 1);
+    assert_eq!(failed_events[0].len(), 1);
+    let event = &failed_events[0][0];
+    assert_eq!(lcs::from_bytes::<u64>(event.event_data()).unwrap(), 42);
+    // The abort rolled the event back, so its sequence number was never committed.
+    assert_eq!(event.sequence_number(), 0);
+    assert!(!output.events().contains(event));
+    assert_eq!(
+        vm.take_diagnostics()[0].pre_failure_events,
+        failed_events[0]
+    );
+}
+
+#[test]
+fn failure_reasons() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let executed = executor.execute_transaction(peer_to_peer_txn(
+        sender.account(),
+        receiver.account(),
+        10,
+        1,
+    ));
+    assert_eq!(failure_reason(&executed), None);
+
+    let discarded =
+        executor.execute_transaction(peer_to_peer_txn(sender.account(), receiver.account(), 9, 1));
+    assert!(discarded.status().is_discarded());
+    assert_eq!(failure_reason(&discarded), None);
+
+    let aborted = executor.execute_transaction(emit_then_abort_txn(&sender, 10));
+    assert_eq!(failure_reason(&aborted), Some(FailureReason::Aborted(77)));
+
+    let out_of_gas = executor.execute_transaction(script_txn(
+        sender.account(),
+        10,
+        "
+        main() {
+            loop {}
+            return;
+        }
+        ",
+        100_000,
+    ));
+    assert_eq!(failure_reason(&out_of_gas), Some(FailureReason::OutOfGas));
+
+    let arithmetic_error = executor.execute_transaction(script_txn(
+        sender.account(),
+        10,
+        "
+        main() {
+            _ = to_u8(256u64);
+            return;
+        }
+        ",
+        100_000,
+    ));
+    assert_eq!(
+        arithmetic_error.status().vm_status().major_status,
+        StatusCode::ARITHMETIC_ERROR
+    );
+    assert_eq!(
+        failure_reason(&arithmetic_error),
+        Some(FailureReason::ExecutionError)
+    );
+}
+
+#[test]
+fn strict_block_fails_on_first_discard() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+    let block = |seq_nums: &[u64]| -> Vec<Transaction> {
+        seq_nums
+            .iter()
+            .map(|seq_num| {
+                Transaction::UserTransaction(peer_to_peer_txn(
+                    sender.account(),
+                    receiver.account(),
+                    *seq_num,
+                    1_000,
+                ))
+            })
+            .collect()
+    };
+
+    let mut vm = LibraVM::new();
+    assert_eq!(
+        vm.execute_block_strict(block(&[10, 11]), executor.get_state_view()),
+        vm.execute_transaction_block(block(&[10, 11]), executor.get_state_view())
+    );
+
+    // The replay of the first transaction is discarded.
+    let outputs = vm
+        .execute_transaction_block(block(&[10, 10, 11]), executor.get_state_view())
+        .unwrap();
+    assert_eq!(outputs.len(), 3);
+    assert!(outputs[1].status().is_discarded());
+    assert_eq!(
+        vm.execute_block_strict(block(&[10, 10, 11]), executor.get_state_view())
+            .unwrap_err()
+            .major_status,
+        StatusCode::SEQUENCE_NUMBER_TOO_OLD
+    );
+}
@@ -0,0 +1,67 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{account::AccountData, common_transactions::peer_to_peer_txn, executor::FakeExecutor};
+use libra_types::transaction::{Transaction, TransactionOutput};
+use libra_vm::{
+    fixture::{run_fixture, BlockFixture, FixtureMismatch, FixtureRecorder},
+    LibraVM,
+};
+
+#[test]
+fn record_and_replay_fixture() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let block: Vec<_> = (10..12)
+        .map(|seq_num| {
+            Transaction::UserTransaction(peer_to_peer_txn(
+                sender.account(),
+                receiver.account(),
+                seq_num,
+                1_000,
+            ))
+        })
+        .collect();
+    let mut recorder = FixtureRecorder::new(LibraVM::new());
+    let fixture = recorder
+        .record(block.clone(), executor.get_state_view())
+        .unwrap();
+    assert_eq!(fixture.transactions, block);
+    assert_eq!(fixture.outputs.len(), 2);
+
+    let fixture = BlockFixture::from_bytes(&fixture.to_bytes().unwrap()).unwrap();
+    assert_eq!(run_fixture(&fixture), Ok(()));
+
+    // A change in the outputs is reported with the first output that differs.
+    let mut changed = fixture.clone();
+    let output = &changed.outputs[1];
+    let expected = TransactionOutput::new(
+        output.write_set().clone(),
+        output.events().to_vec(),
+        output.gas_used() + 1,
+        output.status().clone(),
+    );
+    changed.outputs[1] = expected.clone();
+    assert_eq!(
+        run_fixture(&changed),
+        Err(FixtureMismatch::Output {
+            index: 1,
+            expected: Box::new(expected),
+            actual: Box::new(fixture.outputs[1].clone()),
+        })
+    );
+
+    let mut changed = fixture;
+    changed.outputs.pop();
+    assert_eq!(
+        run_fixture(&changed),
+        Err(FixtureMismatch::OutputCount {
+            expected: 1,
+            actual: 2,
+        })
+    );
+}
@@ -0,0 +1,132 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    account::AccountData, compile::compile_script_with_address, executor::FakeExecutor,
+    transaction_status_eq,
+};
+use libra_types::{
+    account_config::LBR_NAME,
+    transaction::{SignedTransaction, Transaction, TransactionStatus},
+    vm_status::{StatusCode, VMStatus},
+};
+use libra_vm::{gas_currency::GasCurrencyResolver, LibraVM};
+use move_core_types::identifier::Identifier;
+
+/// Resolves a currency code that is not a valid Move identifier to LBR.
+struct TestnetCurrencies;
+
+impl GasCurrencyResolver for TestnetCurrencies {
+    fn resolve(&self, gas_currency_code: &str) -> Option<Identifier> {
+        if gas_currency_code == "lbr-testnet" {
+            Some(Identifier::new(LBR_NAME).unwrap())
+        } else {
+            None
+        }
+    }
+}
+
+fn txn_with_gas_currency(
+    sender: &AccountData,
+    seq_num: u64,
+    gas_currency_code: &str,
+) -> SignedTransaction {
+    let program = "
+        main() {
+            return;
+        }
+    ";
+    let script = compile_script_with_address(sender.address(), "file_name", program, vec![]);
+    sender.account().create_signed_txn_impl(
+        *sender.address(),
+        script,
+        seq_num,
+        100_000,
+        1,
+        gas_currency_code.to_owned(),
+    )
+}
+
+#[test]
+fn gas_currency_resolver() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    executor.add_account_data(&sender);
+    let block = vec![Transaction::UserTransaction(txn_with_gas_currency(
+        &sender,
+        10,
+        "lbr-testnet",
+    ))];
+
+    // Without a resolver the code is rejected.
+    let mut vm = LibraVM::new();
+    let outputs = vm
+        .execute_transaction_block(block.clone(), executor.get_state_view())
+        .unwrap();
+    assert!(transaction_status_eq(
+        outputs[0].status(),
+        &TransactionStatus::Discard(VMStatus::new(StatusCode::INVALID_GAS_SPECIFIER, None, None))
+    ));
+
+    vm.set_gas_currency_resolver(TestnetCurrencies);
+    let outputs = vm
+        .execute_transaction_block(block, executor.get_state_view())
+        .unwrap();
+    assert!(transaction_status_eq(
+        outputs[0].status(),
+        &TransactionStatus::Keep(VMStatus::executed())
+    ));
+    // The gas is paid in LBR.
+    executor.apply_write_set(outputs[0].write_set());
+    assert_eq!(
+        executor
+            .read_balance_resource(sender.account(), Identifier::new(LBR_NAME).unwrap())
+            .unwrap()
+            .coin(),
+        1_000_000 - outputs[0].gas_used()
+    );
+
+    // Codes the resolver does not know still go through the default path.
+    let txn = txn_with_gas_currency(&sender, 11, LBR_NAME);
+    let output = vm
+        .execute_single_transaction(txn, executor.get_state_view())
+        .unwrap();
+    assert!(transaction_status_eq(
+        output.status(),
+        &TransactionStatus::Keep(VMStatus::executed())
+    ));
+}
+
+#[test]
+fn block_summary_reports_unresolved_gas_currency() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    executor.add_account_data(&sender);
+    let block: Vec<_> = (10..13)
+        .map(|seq_num| {
+            Transaction::UserTransaction(txn_with_gas_currency(&sender, seq_num, "lbr-testnet"))
+        })
+        .collect();
+
+    // Without the resolver, no transaction of the block can pay for gas.
+    let mut vm = LibraVM::new();
+    let outputs = vm
+        .execute_transaction_block(block.clone(), executor.get_state_view())
+        .unwrap();
+    assert!(outputs.iter().all(|output| output.status().is_discarded()));
+    assert_eq!(vm.block_summary().discarded_count, 3);
+    assert_eq!(vm.block_summary().unresolved_gas_currency, vec![0, 1, 2]);
+
+    // Other discards are not reported.
+    let mut block = block;
+    block.push(Transaction::UserTransaction(txn_with_gas_currency(
+        &sender, 5, LBR_NAME,
+    )));
+    vm.set_gas_currency_resolver(TestnetCurrencies);
+    let outputs = vm
+        .execute_transaction_block(block, executor.get_state_view())
+        .unwrap();
+    assert!(outputs[3].status().is_discarded());
+    assert_eq!(vm.block_summary().discarded_count, 1);
+    assert!(vm.block_summary().unresolved_gas_currency.is_empty());
+}
@@ -0,0 +1,62 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{account::AccountData, common_transactions::script_txn, executor::FakeExecutor};
+use libra_types::transaction::SignedTransaction;
+use libra_vm::{executor_config::ExecutorConfig, LibraVM};
+
+fn loop_txn(sender: &AccountData, iterations: u64) -> SignedTransaction {
+    let program = format!(
+        "
+        main() {{
+            let i: u64;
+            i = 0;
+            while (copy(i) < {}) {{
+                i = move(i) + 1;
+            }}
+            return;
+        }}
+        ",
+        iterations,
+    );
+    script_txn(sender.account(), 10, &program, 100_000)
+}
+
+#[test]
+fn instructions_executed_follow_gas() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    executor.add_account_data(&sender);
+
+    let mut vm = LibraVM::new_with_config(ExecutorConfig {
+        collect_diagnostics: true,
+        ..ExecutorConfig::default()
+    });
+    let (instructions, gas): (Vec<_>, Vec<_>) = [10, 20, 30]
+        .iter()
+        .map(|iterations| {
+            let output = vm
+                .execute_single_transaction(
+                    loop_txn(&sender, *iterations),
+                    executor.get_state_view(),
+                )
+                .unwrap();
+            let diagnostics = vm.take_diagnostics().pop().unwrap();
+            (
+                diagnostics.instructions_executed.unwrap(),
+                output.gas_used(),
+            )
+        })
+        .unzip();
+
+    // Each iteration runs the same instructions and costs the same gas.
+    let per_iteration = instructions[1] - instructions[0];
+    assert!(per_iteration > 0);
+    assert_eq!(instructions[2] - instructions[1], per_iteration);
+    let gas_per_iteration = gas[1] - gas[0];
+    assert!(gas_per_iteration > 0);
+    assert_eq!(gas[2] - gas[1], gas_per_iteration);
+    // The script does nothing but the loop besides its initialization and return.
+    assert!(instructions[0] > 10 * per_iteration);
+    assert!(instructions[0] < 11 * per_iteration);
+}
@@ -0,0 +1,106 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    account::AccountData, common_transactions::script_txn_impl, executor::FakeExecutor,
+    transaction_status_eq,
+};
+use libra_types::{
+    account_config::libra_root_address,
+    on_chain_config::{OnChainConfig, TransactionLimits},
+    transaction::{SignedTransaction, TransactionStatus},
+    vm_status::{StatusCode, VMStatus},
+};
+use libra_vm::LibraVM;
+use move_vm_types::values::Value;
+
+// Emits `n` events on a new handle of the sender.
+fn emit_txn(sender: &AccountData, n: u64) -> SignedTransaction {
+    let program = format!(
+        "
+        import 0x1.Event;
+
+        main(account: &signer) {{
+            let handle: Event.EventHandle<u64>;
+            let i: u64;
+            handle = Event.new_event_handle<u64>(move(account));
+            i = 0;
+            while (copy(i) < {}) {{
+                Event.emit_event<u64>(&mut handle, copy(i));
+                i = move(i) + 1;
+            }}
+            Event.destroy_handle<u64>(move(handle));
+            return;
+        }}
+        ",
+        n,
+    );
+    script_txn_impl(sender.account(), 10, &program, vec![], vec![], 100_000, 1)
+}
+
+#[test]
+fn max_events_per_transaction() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    executor.add_account_data(&sender);
+
+    // Genesis sets no limit, so any number of events can be emitted.
+    let txn = emit_txn(&sender, 10);
+    let unlimited_output = executor.execute_transaction(txn.clone());
+    let txn_over_limit = emit_txn(&sender, 100);
+    let unlimited_output_over_limit = executor.execute_transaction(txn_over_limit.clone());
+    assert!(transaction_status_eq(
+        unlimited_output_over_limit.status(),
+        &TransactionStatus::Keep(VMStatus::executed())
+    ));
+
+    // The libra root account sets the limit, which takes effect with the reconfiguration.
+    executor.new_block();
+    executor.exec(
+        "TransactionLimits",
+        "set_max_events_per_transaction",
+        vec![],
+        vec![
+            Value::transaction_argument_signer_reference(libra_root_address()),
+            Value::u64(10),
+        ],
+        &libra_root_address(),
+    );
+    assert_eq!(
+        TransactionLimits::fetch_config(executor.get_state_view()),
+        Some(TransactionLimits {
+            max_events_per_transaction: 10,
+        })
+    );
+    let mut vm = LibraVM::new();
+    let output = vm
+        .execute_single_transaction(txn, executor.get_state_view())
+        .unwrap();
+    assert!(transaction_status_eq(
+        output.status(),
+        &TransactionStatus::Keep(VMStatus::executed())
+    ));
+    assert_eq!(output, unlimited_output);
+
+    let output = vm
+        .execute_single_transaction(txn_over_limit, executor.get_state_view())
+        .unwrap();
+    let status = output.status().vm_status();
+    assert_eq!(status.major_status, StatusCode::MAX_EVENTS_REACHED);
+    assert_eq!(
+        status.message.as_deref(),
+        Some("max events: 10, 100 events emitted")
+    );
+    // The sender still pays for the events, and none of them is kept.
+    assert!(!output.status().is_discarded());
+    assert_eq!(output.gas_used(), unlimited_output_over_limit.gas_used());
+    assert!(output.events().len() < 10);
+    executor.apply_write_set(output.write_set());
+    assert_eq!(
+        executor
+            .read_account_resource(sender.account())
+            .unwrap()
+            .sequence_number(),
+        11
+    );
+}
@@ -0,0 +1,102 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    account::{self, Account, AccountData},
+    common_transactions::peer_to_peer_txn,
+    executor::FakeExecutor,
+};
+use libra_types::{
+    account_config::LBR_NAME,
+    transaction::{ChangeSet, TransactionPayload},
+};
+use libra_vm::LibraVM;
+
+#[test]
+fn metrics_snapshot_reflects_executed_block() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let before = LibraVM::metrics_snapshot();
+    let outputs = executor
+        .execute_block(vec![
+            peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000),
+            // Discarded: the sequence number is too old.
+            peer_to_peer_txn(sender.account(), receiver.account(), 5, 1_000),
+        ])
+        .unwrap();
+    assert!(!outputs[0].status().is_discarded());
+    assert!(outputs[1].status().is_discarded());
+    let after = LibraVM::metrics_snapshot();
+
+    // Other tests may execute transactions concurrently, so only lower bounds hold.
+    assert!(after.transactions_executed["success"] > before.transactions_executed["success"]);
+    assert!(after.transactions_executed["discarded"] > before.transactions_executed["discarded"]);
+    assert!(after.txn_total_gas_usage.sample_count > before.txn_total_gas_usage.sample_count);
+    assert!(after.txn_total_seconds.sample_count >= before.txn_total_seconds.sample_count + 2);
+}
+
+#[test]
+fn total_seconds_by_payload() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+    let libra_root = Account::new_libra_root();
+    let writeset_txn = libra_root.create_signed_txn_impl(
+        *libra_root.address(),
+        TransactionPayload::WriteSet(ChangeSet::new(
+            AccountData::new(0, 10).to_writeset(),
+            vec![],
+        )),
+        1,
+        100_000,
+        0,
+        LBR_NAME.to_owned(),
+    );
+
+    let before = LibraVM::metrics_snapshot().txn_total_seconds_by_payload;
+    let outputs = executor
+        .execute_block(vec![
+            peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000),
+            // Discarded: the sequence number is too old.
+            peer_to_peer_txn(sender.account(), receiver.account(), 5, 1_000),
+            writeset_txn,
+        ])
+        .unwrap();
+    assert!(!outputs[0].status().is_discarded());
+    assert!(outputs[1].status().is_discarded());
+    assert!(!outputs[2].status().is_discarded());
+    let after = LibraVM::metrics_snapshot().txn_total_seconds_by_payload;
+
+    // Other tests may execute transactions concurrently, so only lower bounds hold.
+    assert!(after["script"].sample_count >= before["script"].sample_count + 2);
+    assert!(after["write_set"].sample_count > before["write_set"].sample_count);
+    assert!(after.contains_key("module"));
+}
+
+#[test]
+fn gas_used_is_counted_by_currency() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+    let lbr = account::lbr_currency_code();
+
+    let before = LibraVM::gas_used_in_currency(&lbr);
+    let output = executor.execute_transaction(peer_to_peer_txn(
+        sender.account(),
+        receiver.account(),
+        10,
+        1_000,
+    ));
+    assert!(!output.status().is_discarded());
+    assert!(output.gas_used() > 0);
+    // Other tests may execute transactions concurrently, so only a lower bound holds.
+    assert!(LibraVM::gas_used_in_currency(&lbr) >= before + output.gas_used());
+}
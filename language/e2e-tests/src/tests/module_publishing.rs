@@ -4,16 +4,25 @@
 use crate::{
     account::{Account, AccountData},
     assert_prologue_parity, assert_status_eq,
+    common_transactions::peer_to_peer_txn,
     compile::compile_module_with_address,
     executor::FakeExecutor,
     transaction_status_eq,
 };
+use compiler::Compiler;
 use libra_types::{
     account_config::{self, LBR_NAME},
     on_chain_config::VMPublishingOption,
-    transaction::TransactionStatus,
+    transaction::{Module, SignedTransaction, Transaction, TransactionPayload, TransactionStatus},
     vm_status::{StatusCode, StatusType, VMStatus},
 };
+use libra_vm::{
+    diagnostics::{publishing_failure, PublishingFailure},
+    executor_config::ExecutorConfig,
+    LibraVM,
+};
+use move_core_types::{identifier::Identifier, language_storage::ModuleId};
+use vm::CompiledModule;
 
 // A module with an address different from the sender's address should be rejected
 #[test]
@@ -311,3 +320,210 @@ pub fn test_publishing_allow_modules() {
         &TransactionStatus::Keep(VMStatus::executed())
     );
 }
+
+/// Compiles module `M<index>`, which calls into `M<index - 1>` if `dependency` is set, and a
+/// transaction publishing it.
+fn chained_module_txn(
+    sender: &AccountData,
+    index: u64,
+    dependency: Option<CompiledModule>,
+) -> (CompiledModule, SignedTransaction) {
+    let program = match dependency {
+        Some(_) => format!(
+            "
+            module M{index} {{
+                import 0x{address}.M{previous};
+
+                public f() {{
+                    M{previous}.f();
+                    return;
+                }}
+            }}
+            ",
+            index = index,
+            address = sender.address(),
+            previous = index - 1,
+        ),
+        None => format!(
+            "
+            module M{index} {{
+                public f() {{
+                    return;
+                }}
+            }}
+            ",
+            index = index,
+        ),
+    };
+    let compiler = Compiler {
+        address: *sender.address(),
+        extra_deps: dependency.into_iter().collect(),
+        ..Compiler::default()
+    };
+    let module = compiler
+        .into_compiled_module("file_name", &program)
+        .expect("Module compilation failed");
+    let mut module_blob = vec![];
+    module
+        .serialize(&mut module_blob)
+        .expect("Module must serialize");
+    let txn = sender.account().create_signed_txn_impl(
+        *sender.address(),
+        TransactionPayload::Module(Module::new(module_blob)),
+        10 + index,
+        100_000,
+        1,
+        LBR_NAME.to_owned(),
+    );
+    (module, txn)
+}
+
+#[test]
+fn max_module_dependency_depth() {
+    let mut executor = FakeExecutor::from_genesis_with_options(VMPublishingOption::Open);
+    let sender = AccountData::new(1_000_000, 10);
+    executor.add_account_data(&sender);
+
+    // M2 depends on M1, which depends on M0.
+    let mut vm = LibraVM::new_with_config(ExecutorConfig {
+        max_module_dependency_depth: 1,
+        ..ExecutorConfig::default()
+    });
+    let mut dependency = None;
+    for index in 0..2 {
+        let (module, txn) = chained_module_txn(&sender, index, dependency);
+        let output = vm
+            .execute_single_transaction(txn, executor.get_state_view())
+            .unwrap();
+        assert!(transaction_status_eq(
+            output.status(),
+            &TransactionStatus::Keep(VMStatus::executed())
+        ));
+        executor.apply_write_set(output.write_set());
+        dependency = Some(module);
+    }
+
+    let (_, txn) = chained_module_txn(&sender, 2, dependency);
+    let output = vm
+        .execute_single_transaction(txn.clone(), executor.get_state_view())
+        .unwrap();
+    assert!(transaction_status_eq(
+        output.status(),
+        &TransactionStatus::Keep(VMStatus::new(
+            StatusCode::MAX_DEPENDENCY_DEPTH_REACHED,
+            None,
+            None
+        ))
+    ));
+
+    // The default limit lets it through.
+    assert!(transaction_status_eq(
+        executor.execute_transaction(txn).status(),
+        &TransactionStatus::Keep(VMStatus::executed())
+    ));
+}
+
+#[test]
+fn diagnostics_report_published_modules() {
+    let mut executor = FakeExecutor::from_genesis_with_options(VMPublishingOption::Open);
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let (_, module_txn) = chained_module_txn(&sender, 0, None);
+    let block = vec![
+        Transaction::UserTransaction(module_txn),
+        Transaction::UserTransaction(peer_to_peer_txn(
+            sender.account(),
+            receiver.account(),
+            11,
+            1_000,
+        )),
+    ];
+    let mut vm = LibraVM::new_with_config(ExecutorConfig {
+        collect_diagnostics: true,
+        ..ExecutorConfig::default()
+    });
+    let outputs = vm
+        .execute_transaction_block(block, executor.get_state_view())
+        .unwrap();
+    assert!(outputs.iter().all(|output| transaction_status_eq(
+        output.status(),
+        &TransactionStatus::Keep(VMStatus::executed())
+    )));
+
+    let diagnostics = vm.take_diagnostics();
+    assert_eq!(
+        diagnostics[0].published_modules,
+        vec![ModuleId::new(
+            *sender.address(),
+            Identifier::new("M0").unwrap()
+        )]
+    );
+    // Scripts do not publish anything.
+    assert!(diagnostics[1].published_modules.is_empty());
+}
+
+#[test]
+fn publishing_failures_are_classified() {
+    let sender = AccountData::new(1_000_000, 10);
+    let (module, txn) = chained_module_txn(&sender, 0, None);
+
+    let mut executor = FakeExecutor::from_genesis_with_options(VMPublishingOption::CustomScripts);
+    executor.add_account_data(&sender);
+    assert_eq!(
+        publishing_failure(&executor.execute_transaction(txn.clone())),
+        Some(PublishingFailure::PublishingDisabled)
+    );
+
+    let mut executor = FakeExecutor::from_genesis_with_options(VMPublishingOption::Open);
+    executor.add_account_data(&sender);
+    assert_eq!(
+        publishing_failure(&executor.execute_transaction(txn.clone())),
+        None
+    );
+
+    // M1 depends on M0, which is not published under this sender.
+    let other_sender = AccountData::new(1_000_000, 11);
+    executor.add_account_data(&other_sender);
+    let (other_module, _) = chained_module_txn(&other_sender, 0, None);
+    let (_, dependent_txn) = chained_module_txn(&other_sender, 1, Some(other_module));
+    assert_eq!(
+        publishing_failure(&executor.execute_transaction(dependent_txn)),
+        Some(PublishingFailure::MissingDependency)
+    );
+
+    // The module of the other sender can't be published by this one.
+    let mut module_blob = vec![];
+    chained_module_txn(&other_sender, 0, None)
+        .0
+        .serialize(&mut module_blob)
+        .unwrap();
+    let module_txn = |blob: Vec<u8>| {
+        sender.account().create_signed_txn_impl(
+            *sender.address(),
+            TransactionPayload::Module(Module::new(blob)),
+            10,
+            100_000,
+            1,
+            LBR_NAME.to_owned(),
+        )
+    };
+    assert_eq!(
+        publishing_failure(&executor.execute_transaction(module_txn(module_blob))),
+        Some(PublishingFailure::VerificationFailed(
+            StatusCode::MODULE_ADDRESS_DOES_NOT_MATCH_SENDER
+        ))
+    );
+    match publishing_failure(&executor.execute_transaction(module_txn(vec![0xde, 0xad]))) {
+        Some(PublishingFailure::Malformed(_)) => (),
+        failure => panic!("unexpected publishing failure: {:?}", failure),
+    }
+
+    executor.add_module(&module.self_id(), &module);
+    assert_eq!(
+        publishing_failure(&executor.execute_transaction(txn)),
+        Some(PublishingFailure::DuplicateModule)
+    );
+}
@@ -11,11 +11,11 @@ use crate::{
 use compiled_stdlib::transaction_scripts::StdlibScript;
 use libra_types::{
     account_config::LBR_NAME,
-    on_chain_config::LibraVersion,
-    transaction::{TransactionArgument, TransactionStatus},
-    vm_status::VMStatus,
+    on_chain_config::{new_epoch_event_key, LibraVersion},
+    transaction::{Transaction, TransactionArgument, TransactionStatus},
+    vm_status::{StatusCode, VMStatus},
 };
-use libra_vm::LibraVM;
+use libra_vm::{executor_config::ExecutorConfig, LibraVM};
 use transaction_builder::encode_update_dual_attestation_limit_script;
 
 #[test]
@@ -49,6 +49,61 @@ fn initial_libra_version() {
     );
 }
 
+#[test]
+fn user_reconfiguration_can_be_rejected() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let account = Account::new_genesis_account(libra_types::on_chain_config::config_address());
+    let txn = account.create_signed_txn_with_args(
+        StdlibScript::UpdateLibraVersion.compiled_bytes().into_vec(),
+        vec![],
+        vec![TransactionArgument::U64(2)],
+        1,
+        TXN_RESERVED,
+        0,
+        LBR_NAME.to_owned(),
+    );
+    executor.new_block();
+
+    // Allowed by default: the output is kept and starts a new epoch.
+    let output = LibraVM::new()
+        .execute_transaction_block(
+            vec![Transaction::UserTransaction(txn.clone())],
+            executor.get_state_view(),
+        )
+        .unwrap()
+        .remove(0);
+    assert!(transaction_status_eq(
+        output.status(),
+        &TransactionStatus::Keep(VMStatus::executed())
+    ));
+    assert!(output
+        .events()
+        .iter()
+        .any(|event| *event.key() == new_epoch_event_key()));
+    assert!(LibraVM::is_reconfiguration(&output));
+
+    let mut vm = LibraVM::new_with_config(ExecutorConfig {
+        allow_user_reconfiguration: false,
+        ..ExecutorConfig::default()
+    });
+    let output = vm
+        .execute_transaction_block(
+            vec![Transaction::UserTransaction(txn)],
+            executor.get_state_view(),
+        )
+        .unwrap()
+        .remove(0);
+    assert!(transaction_status_eq(
+        output.status(),
+        &TransactionStatus::Discard(VMStatus::new(
+            StatusCode::UNEXPECTED_RECONFIGURATION,
+            None,
+            None
+        ))
+    ));
+    assert!(!LibraVM::is_reconfiguration(&output));
+}
+
 #[test]
 fn updated_limit_allows_txn() {
     // create a FakeExecutor with a genesis from file
@@ -89,3 +144,50 @@ fn updated_limit_allows_txn() {
     assert_eq!(3_999_990, sender_balance.coin());
     assert_eq!(1_000_010, receiver_balance.coin());
 }
+
+#[test]
+fn transactions_after_user_reconfiguration_read_stale_configs() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+    let account = Account::new_genesis_account(libra_types::on_chain_config::config_address());
+    let update_version = account.create_signed_txn_with_args(
+        StdlibScript::UpdateLibraVersion.compiled_bytes().into_vec(),
+        vec![],
+        vec![TransactionArgument::U64(2)],
+        1,
+        TXN_RESERVED,
+        0,
+        LBR_NAME.to_owned(),
+    );
+    executor.new_block();
+
+    let mut vm = LibraVM::new_with_config(ExecutorConfig {
+        stale_config_check: true,
+        ..ExecutorConfig::default()
+    });
+    let outputs = vm
+        .execute_transaction_block(
+            vec![
+                Transaction::UserTransaction(update_version),
+                Transaction::UserTransaction(peer_to_peer_txn(
+                    sender.account(),
+                    receiver.account(),
+                    10,
+                    1_000,
+                )),
+            ],
+            executor.get_state_view(),
+        )
+        .unwrap();
+    assert!(outputs.iter().all(|output| !output.status().is_discarded()));
+
+    // The configs are loaded once for all the user transactions, so the payment ran with the
+    // configs from before the version update.
+    assert_eq!(
+        vm.take_stale_config_transactions(),
+        vec![(*sender.address(), 10)]
+    );
+}
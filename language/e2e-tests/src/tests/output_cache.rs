@@ -0,0 +1,57 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{account::AccountData, common_transactions::peer_to_peer_txn, executor::FakeExecutor};
+use libra_types::{
+    transaction::{Transaction, TransactionStatus},
+    vm_status::StatusCode,
+};
+use libra_vm::{executor_config::ExecutorConfig, LibraVM};
+
+#[test]
+fn output_cache_hit_and_miss() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+    let txn = Transaction::UserTransaction(peer_to_peer_txn(
+        sender.account(),
+        receiver.account(),
+        10,
+        1_000,
+    ));
+
+    let mut vm = LibraVM::new_with_config(ExecutorConfig {
+        output_cache_capacity: Some(16),
+        ..ExecutorConfig::default()
+    });
+    let output = vm
+        .execute_transaction_cached(txn.clone(), executor.get_state_view(), 0)
+        .unwrap();
+    assert!(!output.status().is_discarded());
+    let cache = vm.output_cache().unwrap();
+    assert_eq!((cache.hits(), cache.misses(), cache.len()), (0, 1, 1));
+
+    // Same transaction on the same state: served from the cache.
+    let cached_output = vm
+        .execute_transaction_cached(txn.clone(), executor.get_state_view(), 0)
+        .unwrap();
+    assert_eq!(cached_output, output);
+    let cache = vm.output_cache().unwrap();
+    assert_eq!((cache.hits(), cache.misses()), (1, 1));
+
+    // Once the state changed, the transaction is executed again.
+    executor.apply_write_set(output.write_set());
+    let new_output = vm
+        .execute_transaction_cached(txn, executor.get_state_view(), 1)
+        .unwrap();
+    match new_output.status() {
+        TransactionStatus::Discard(status) => {
+            assert_eq!(status.major_status, StatusCode::SEQUENCE_NUMBER_TOO_OLD)
+        }
+        status => panic!("unexpected status {:?}", status),
+    }
+    let cache = vm.output_cache().unwrap();
+    assert_eq!((cache.hits(), cache.misses(), cache.len()), (1, 2, 2));
+}
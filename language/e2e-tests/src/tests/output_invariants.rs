@@ -0,0 +1,88 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{account::AccountData, common_transactions::peer_to_peer_txn, executor::FakeExecutor};
+use libra_types::{
+    transaction::{TransactionOutput, TransactionStatus},
+    vm_status::{StatusCode, VMStatus},
+};
+use libra_vm::{
+    output_invariants::{validate_output_invariants, OutputInvariantViolation},
+    LibraVM,
+};
+
+#[test]
+fn output_invariants() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+    let mut vm = LibraVM::new();
+    vm.load_configs(executor.get_state_view());
+    let params = vm.intrinsic_gas_params().unwrap();
+
+    // Outputs produced by the executor are valid.
+    let txn = peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000);
+    let kept = executor.execute_transaction(txn.clone());
+    assert!(!kept.status().is_discarded());
+    assert_eq!(validate_output_invariants(&kept, &txn, &params), Ok(()));
+
+    let old_txn = peer_to_peer_txn(sender.account(), receiver.account(), 9, 1_000);
+    let discarded = executor.execute_transaction(old_txn.clone());
+    assert!(discarded.status().is_discarded());
+    assert_eq!(
+        validate_output_invariants(&discarded, &old_txn, &params),
+        Ok(())
+    );
+
+    // Synthetic invalid outputs.
+    let discard_status = || {
+        TransactionStatus::Discard(VMStatus::new(
+            StatusCode::SEQUENCE_NUMBER_TOO_OLD,
+            None,
+            None,
+        ))
+    };
+    let charged_discard =
+        TransactionOutput::new(discarded.write_set().clone(), vec![], 5, discard_status());
+    assert_eq!(
+        validate_output_invariants(&charged_discard, &old_txn, &params),
+        Err(OutputInvariantViolation::DiscardedWithGasUsed { gas_used: 5 })
+    );
+
+    let writing_discard =
+        TransactionOutput::new(kept.write_set().clone(), vec![], 0, discard_status());
+    assert_eq!(
+        validate_output_invariants(&writing_discard, &txn, &params),
+        Err(OutputInvariantViolation::DiscardedWithWrites)
+    );
+
+    let free_keep = TransactionOutput::new(
+        kept.write_set().clone(),
+        kept.events().to_vec(),
+        0,
+        kept.status().clone(),
+    );
+    assert_eq!(
+        validate_output_invariants(&free_keep, &txn, &params),
+        Err(OutputInvariantViolation::BelowIntrinsicGas {
+            gas_used: 0,
+            intrinsic_gas: params.intrinsic_gas(txn.raw_txn_bytes_len() as u64),
+        })
+    );
+
+    let greedy_keep = TransactionOutput::new(
+        kept.write_set().clone(),
+        kept.events().to_vec(),
+        txn.max_gas_amount() + 1,
+        kept.status().clone(),
+    );
+    assert_eq!(
+        validate_output_invariants(&greedy_keep, &txn, &params),
+        Err(OutputInvariantViolation::AboveMaxGasAmount {
+            gas_used: txn.max_gas_amount() + 1,
+            max_gas_amount: txn.max_gas_amount(),
+        })
+    );
+}
@@ -0,0 +1,56 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{account::AccountData, common_transactions::peer_to_peer_txn, executor::FakeExecutor};
+use libra_crypto::hash::CryptoHash;
+use libra_types::transaction::Transaction;
+use libra_vm::LibraVM;
+
+#[test]
+fn outputs_are_keyed_by_transaction_hash() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let block: Vec<_> = vec![
+        peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000),
+        peer_to_peer_txn(receiver.account(), sender.account(), 10, 2_000),
+        // Discarded: the sequence number is too old.
+        peer_to_peer_txn(sender.account(), receiver.account(), 5, 1_000),
+    ]
+    .into_iter()
+    .map(Transaction::UserTransaction)
+    .collect();
+    let outputs = executor.execute_transaction_block(block.clone()).unwrap();
+
+    let outputs_by_hash = LibraVM::new()
+        .execute_block_by_hash(block.clone(), executor.get_state_view())
+        .unwrap();
+    assert_eq!(outputs_by_hash.len(), block.len());
+    for (txn, output) in block.iter().zip(&outputs) {
+        assert_eq!(outputs_by_hash.get(&txn.hash()), Some(output));
+    }
+}
+
+#[test]
+fn duplicate_transactions_keep_the_first_output() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let txn = Transaction::UserTransaction(peer_to_peer_txn(
+        sender.account(),
+        receiver.account(),
+        10,
+        1_000,
+    ));
+    let outputs_by_hash = LibraVM::new()
+        .execute_block_by_hash(vec![txn.clone(), txn.clone()], executor.get_state_view())
+        .unwrap();
+    assert_eq!(outputs_by_hash.len(), 1);
+    assert!(!outputs_by_hash[&txn.hash()].status().is_discarded());
+}
@@ -0,0 +1,219 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    account::{self, AccountData},
+    common_transactions::peer_to_peer_txn,
+    executor::FakeExecutor,
+};
+use libra_types::{transaction::Transaction, vm_status::StatusCode};
+use libra_vm::{conflict_tracker::ConflictResolver, LibraVM};
+use std::cell::RefCell;
+
+#[test]
+fn parallel_execution_matches_sequential_execution() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let accounts: Vec<_> = (0..6)
+        .map(|_| {
+            let account = AccountData::new(1_000_000, 10);
+            executor.add_account_data(&account);
+            account
+        })
+        .collect();
+
+    let mut block = vec![];
+    // Independent payments between distinct pairs of accounts.
+    for pair in accounts[..4].chunks(2) {
+        block.push(peer_to_peer_txn(
+            pair[0].account(),
+            pair[1].account(),
+            10,
+            1_000,
+        ));
+    }
+    // Payments from the same sender, each one depending on the previous one.
+    for seq_num in 10..13 {
+        block.push(peer_to_peer_txn(
+            accounts[4].account(),
+            accounts[5].account(),
+            seq_num,
+            1_000,
+        ));
+    }
+    // A payment to an account written by the earlier payments.
+    block.push(peer_to_peer_txn(
+        accounts[5].account(),
+        accounts[0].account(),
+        10,
+        1_000,
+    ));
+    // Discarded: the sequence number is too old.
+    block.push(peer_to_peer_txn(
+        accounts[0].account(),
+        accounts[1].account(),
+        5,
+        1_000,
+    ));
+    let block: Vec<_> = block
+        .into_iter()
+        .map(Transaction::UserTransaction)
+        .collect();
+
+    let sequential_outputs = executor.execute_transaction_block(block.clone()).unwrap();
+    let mut vm = LibraVM::new();
+    let parallel_outputs = vm
+        .execute_block_parallel(block, executor.get_state_view())
+        .unwrap();
+    assert_eq!(parallel_outputs, sequential_outputs);
+    assert!(parallel_outputs[..8]
+        .iter()
+        .all(|output| !output.status().is_discarded()));
+    assert!(parallel_outputs[8].status().is_discarded());
+}
+
+#[test]
+fn parallel_execution_of_a_dependency_chain() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let accounts: Vec<_> = (0..4)
+        .map(|_| {
+            let account = AccountData::new(1_000_000, 10);
+            executor.add_account_data(&account);
+            account
+        })
+        .collect();
+
+    // Each payment is sent by the receiver of the previous one. The second one conflicts with the
+    // first, and the third one only reads a balance the second one writes: its speculative output
+    // is stale even though it doesn't conflict with the first payment.
+    let block: Vec<_> = accounts
+        .windows(2)
+        .map(|pair| {
+            Transaction::UserTransaction(peer_to_peer_txn(
+                pair[0].account(),
+                pair[1].account(),
+                10,
+                1_000,
+            ))
+        })
+        .collect();
+    let sequential_outputs = executor.execute_transaction_block(block.clone()).unwrap();
+    let parallel_outputs = LibraVM::new()
+        .execute_block_parallel(block, executor.get_state_view())
+        .unwrap();
+    assert_eq!(parallel_outputs, sequential_outputs);
+    assert!(parallel_outputs
+        .iter()
+        .all(|output| !output.status().is_discarded()));
+}
+
+/// Executes the conflicting transactions in reverse block order, recording the runs it is given.
+#[derive(Default)]
+struct Descending {
+    runs: RefCell<Vec<Vec<usize>>>,
+}
+
+impl ConflictResolver for Descending {
+    fn resolution_order(&self, conflicting: &[usize]) -> Vec<usize> {
+        self.runs.borrow_mut().push(conflicting.to_vec());
+        conflicting.iter().rev().cloned().collect()
+    }
+}
+
+#[test]
+fn custom_conflict_resolution_order() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let accounts: Vec<_> = (0..4)
+        .map(|_| {
+            let account = AccountData::new(1_000_000, 10);
+            executor.add_account_data(&account);
+            account
+        })
+        .collect();
+
+    // The last two payments both read an account the first one writes, but not any account the
+    // other one writes: they can be executed again in any order.
+    let block: Vec<_> = vec![
+        peer_to_peer_txn(accounts[0].account(), accounts[1].account(), 10, 1_000),
+        peer_to_peer_txn(accounts[2].account(), accounts[0].account(), 10, 1_000),
+        peer_to_peer_txn(accounts[3].account(), accounts[1].account(), 10, 1_000),
+    ]
+    .into_iter()
+    .map(Transaction::UserTransaction)
+    .collect();
+    let sequential_outputs = executor.execute_transaction_block(block.clone()).unwrap();
+    let resolver = Descending::default();
+    let outputs = LibraVM::new()
+        .execute_block_parallel_with_resolver(block.clone(), executor.get_state_view(), &resolver)
+        .unwrap();
+    assert_eq!(resolver.runs.into_inner(), vec![vec![1, 2]]);
+    assert_eq!(outputs, sequential_outputs);
+
+    // The resolution order is deterministic.
+    let again = LibraVM::new()
+        .execute_block_parallel_with_resolver(
+            block,
+            executor.get_state_view(),
+            &Descending::default(),
+        )
+        .unwrap();
+    assert_eq!(again, outputs);
+}
+
+#[test]
+fn dependent_conflicts_are_executed_in_block_order() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    // The last two payments conflict with the first one and with each other: executed in reverse,
+    // the last one would come before the one it depends on.
+    let block: Vec<_> = (10..13)
+        .map(|seq_num| {
+            Transaction::UserTransaction(peer_to_peer_txn(
+                sender.account(),
+                receiver.account(),
+                seq_num,
+                1_000,
+            ))
+        })
+        .collect();
+    let sequential_outputs = executor.execute_transaction_block(block.clone()).unwrap();
+    let resolver = Descending::default();
+    let outputs = LibraVM::new()
+        .execute_block_parallel_with_resolver(block, executor.get_state_view(), &resolver)
+        .unwrap();
+    assert_eq!(resolver.runs.into_inner(), vec![vec![1, 2]]);
+    assert_eq!(outputs, sequential_outputs);
+    assert!(outputs.iter().all(|output| !output.status().is_discarded()));
+}
+
+#[test]
+fn predict_read_set_of_payment() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    let bystander = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+    executor.add_account_data(&bystander);
+    let txn = peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000);
+
+    let mut vm = LibraVM::new();
+    assert_eq!(
+        vm.predict_read_set(executor.get_state_view(), &txn)
+            .unwrap_err()
+            .major_status,
+        StatusCode::VM_STARTUP_FAILURE
+    );
+    vm.load_configs(executor.get_state_view());
+    let reads = vm
+        .predict_read_set(executor.get_state_view(), &txn)
+        .unwrap();
+    assert!(reads.contains(&sender.make_account_access_path()));
+    assert!(reads.contains(&sender.make_balance_access_path(account::lbr_currency_code())));
+    assert!(reads.contains(&receiver.make_balance_access_path(account::lbr_currency_code())));
+    assert!(!reads.contains(&bystander.make_account_access_path()));
+    assert!(!reads.contains(&bystander.make_balance_access_path(account::lbr_currency_code())));
+}
@@ -3,20 +3,22 @@
 
 use crate::{
     account::{self, Account, AccountData},
-    common_transactions::peer_to_peer_txn,
+    common_transactions::{peer_to_peer_txn, script_txn},
     executor::FakeExecutor,
     gas_costs, transaction_status_eq,
 };
 use compiled_stdlib::transaction_scripts::StdlibScript;
 use libra_types::{
     account_config::{self, ReceivedPaymentEvent, SentPaymentEvent, LBR_NAME},
+    event::EventKey,
     on_chain_config::VMPublishingOption,
     transaction::{
-        Script, SignedTransaction, TransactionArgument, TransactionOutput, TransactionPayload,
-        TransactionStatus,
+        Script, SignedTransaction, Transaction, TransactionArgument, TransactionOutput,
+        TransactionPayload, TransactionStatus,
     },
     vm_status::{StatusCode, VMStatus},
 };
+use libra_vm::{executor_config::ExecutorConfig, LibraVM};
 use std::{convert::TryFrom, time::Instant};
 use vm::file_format::{Bytecode, CompiledScript};
 
@@ -581,3 +583,127 @@ fn many_to_one_peer_to_peer() {
     println!("EXECUTION TIME: {}", execution_time);
     print_accounts(&executor, &accounts);
 }
+
+#[test]
+fn net_balance_change_of_transfer() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    let bystander = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+    executor.add_account_data(&bystander);
+
+    let vm = LibraVM::new();
+    let lbr = account::lbr_currency_code();
+    let output = executor.execute_transaction(peer_to_peer_txn(
+        sender.account(),
+        receiver.account(),
+        10,
+        1_000,
+    ));
+    let state_view = executor.get_state_view();
+    let change = |account: &AccountData| {
+        vm.net_balance_change(state_view, &output, *account.address(), &lbr)
+            .unwrap()
+    };
+    // The gas price is 0, the sender only pays for the transfer.
+    assert_eq!(change(&sender), -1_000);
+    assert_eq!(change(&receiver), 1_000);
+    assert_eq!(change(&bystander), 0);
+    assert_eq!(
+        vm.net_balance_change(
+            state_view,
+            &output,
+            *receiver.address(),
+            &account::coin1_currency_code()
+        )
+        .unwrap(),
+        0
+    );
+}
+
+#[test]
+fn canonical_event_order_is_stable() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+    let txn = peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000);
+
+    let execute = |config: ExecutorConfig| {
+        LibraVM::new_with_config(config)
+            .execute_transaction_block(
+                vec![Transaction::UserTransaction(txn.clone())],
+                executor.get_state_view(),
+            )
+            .unwrap()
+            .pop()
+            .unwrap()
+            .events()
+            .to_vec()
+    };
+    let canonical_config = || ExecutorConfig {
+        canonical_event_order: true,
+        ..ExecutorConfig::default()
+    };
+    let canonical = execute(canonical_config());
+    assert_eq!(canonical.len(), 2);
+    assert!(canonical.windows(2).all(|pair| {
+        (pair[0].key(), pair[0].sequence_number()) < (pair[1].key(), pair[1].sequence_number())
+    }));
+    for _ in 0..3 {
+        assert_eq!(execute(canonical_config()), canonical);
+    }
+
+    // The same events, possibly in another order.
+    let mut emitted = execute(ExecutorConfig::default());
+    emitted.sort_by_key(|event| (*event.key(), event.sequence_number()));
+    assert_eq!(emitted, canonical);
+}
+
+#[test]
+fn group_events_of_repeated_payments_by_key() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let program = format!(
+        r#"
+        import 0x1.LBR;
+        import 0x1.LibraAccount;
+
+        main(account: &signer) {{
+            let with_cap: LibraAccount.WithdrawCapability;
+            with_cap = LibraAccount.extract_withdraw_capability(move(account));
+            LibraAccount.pay_from<LBR.LBR>(&with_cap, 0x{0}, 100, h"", h"");
+            LibraAccount.pay_from<LBR.LBR>(&with_cap, 0x{0}, 200, h"", h"");
+            LibraAccount.restore_withdraw_capability(move(with_cap));
+            return;
+        }}
+        "#,
+        receiver.address()
+    );
+    let txn = script_txn(sender.account(), 10, &program, 100_000);
+    let output = executor.execute_transaction(txn);
+    assert!(transaction_status_eq(
+        output.status(),
+        &TransactionStatus::Keep(VMStatus::executed())
+    ));
+
+    let events_by_key = LibraVM::group_events_by_key(&output);
+    assert_eq!(events_by_key.len(), 2);
+    for key in &[sender.sent_events_key(), receiver.received_events_key()] {
+        let events = &events_by_key[&EventKey::try_from(*key).unwrap()];
+        assert_eq!(
+            events
+                .iter()
+                .map(|event| event.sequence_number())
+                .collect::<Vec<_>>(),
+            vec![0, 1]
+        );
+    }
+}
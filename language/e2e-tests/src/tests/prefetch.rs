@@ -0,0 +1,89 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    account::AccountData, common_transactions::peer_to_peer_txn, data_store::FakeDataStore,
+    executor::FakeExecutor,
+};
+use anyhow::Result;
+use libra_state_view::StateView;
+use libra_types::{
+    access_path::AccessPath,
+    transaction::{Transaction, TransactionOutput},
+};
+use libra_vm::{executor_config::ExecutorConfig, LibraVM};
+use std::cell::Cell;
+
+/// Counts the round trips to the wrapped store.
+struct CountingStateView<'a> {
+    data_store: &'a FakeDataStore,
+    gets: Cell<usize>,
+    multi_gets: Cell<usize>,
+}
+
+impl<'a> CountingStateView<'a> {
+    fn new(data_store: &'a FakeDataStore) -> Self {
+        Self {
+            data_store,
+            gets: Cell::new(0),
+            multi_gets: Cell::new(0),
+        }
+    }
+}
+
+impl<'a> StateView for CountingStateView<'a> {
+    fn get(&self, access_path: &AccessPath) -> Result<Option<Vec<u8>>> {
+        self.gets.set(self.gets.get() + 1);
+        self.data_store.get(access_path)
+    }
+
+    fn multi_get(&self, access_paths: &[AccessPath]) -> Result<Vec<Option<Vec<u8>>>> {
+        self.multi_gets.set(self.multi_gets.get() + 1);
+        self.data_store.multi_get(access_paths)
+    }
+
+    fn is_genesis(&self) -> bool {
+        self.data_store.is_genesis()
+    }
+}
+
+fn execute(
+    block: Vec<Transaction>,
+    data_store: &FakeDataStore,
+    prefetch: bool,
+) -> (Vec<TransactionOutput>, usize, usize) {
+    let state_view = CountingStateView::new(data_store);
+    let mut vm = LibraVM::new_with_config(ExecutorConfig {
+        prefetch,
+        ..ExecutorConfig::default()
+    });
+    let outputs = vm.execute_transaction_block(block, &state_view).unwrap();
+    (outputs, state_view.gets.get(), state_view.multi_gets.get())
+}
+
+#[test]
+fn prefetch_reduces_store_round_trips() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let receiver = AccountData::new(1_000_000, 10);
+    executor.add_account_data(&receiver);
+    let senders: Vec<_> = (0..3).map(|_| AccountData::new(1_000_000, 10)).collect();
+    let mut block = vec![];
+    for sender in &senders {
+        executor.add_account_data(sender);
+        block.push(Transaction::UserTransaction(peer_to_peer_txn(
+            sender.account(),
+            receiver.account(),
+            10,
+            1_000,
+        )));
+    }
+
+    let (outputs, gets, multi_gets) = execute(block.clone(), executor.get_state_view(), false);
+    assert_eq!(multi_gets, 0);
+    let (prefetched_outputs, prefetched_gets, prefetched_multi_gets) =
+        execute(block, executor.get_state_view(), true);
+    assert_eq!(prefetched_multi_gets, 1);
+
+    assert_eq!(outputs, prefetched_outputs);
+    assert!(prefetched_gets + prefetched_multi_gets < gets);
+}
@@ -0,0 +1,42 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{account::AccountData, common_transactions::peer_to_peer_txn, executor::FakeExecutor};
+use libra_types::transaction::Transaction;
+use libra_vm::{read_log::ReadLog, LibraVM};
+
+#[test]
+fn recorded_block_replays_identically() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let block: Vec<_> = (10..13)
+        .map(|seq_num| {
+            Transaction::UserTransaction(peer_to_peer_txn(
+                sender.account(),
+                receiver.account(),
+                seq_num,
+                1_000,
+            ))
+        })
+        .collect();
+    let mut vm = LibraVM::new();
+    // Warm up the code cache: the recording must still capture the modules.
+    vm.execute_transaction_block(block.clone(), executor.get_state_view())
+        .unwrap();
+    let (outputs, log) = vm
+        .execute_block_recorded(block.clone(), executor.get_state_view())
+        .unwrap();
+    assert!(outputs.iter().all(|output| !output.status().is_discarded()));
+    assert!(!log.reads().is_empty());
+
+    // The log survives a round trip and is all a fresh VM needs to reproduce the outputs.
+    let log = ReadLog::from_bytes(&log.to_bytes().unwrap()).unwrap();
+    let replayed = LibraVM::new()
+        .execute_transaction_block(block, &log)
+        .unwrap();
+    assert_eq!(replayed, outputs);
+}
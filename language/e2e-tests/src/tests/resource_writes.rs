@@ -0,0 +1,172 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    account::AccountData, common_transactions::script_txn_impl, executor::FakeExecutor,
+    transaction_status_eq,
+};
+use compiler::Compiler;
+use libra_types::{
+    transaction::{SignedTransaction, TransactionStatus},
+    vm_status::{StatusCode, VMStatus},
+};
+use libra_vm::{executor_config::ExecutorConfig, LibraVM};
+use vm::CompiledModule;
+
+fn churn_module(sender: &AccountData) -> CompiledModule {
+    let module_code = "
+        module M {
+            import 0x1.Signer;
+            resource T { v: u64 }
+
+            public churn(account: &signer, n: u64) acquires T {
+                let i: u64;
+                let v: u64;
+                move_to<T>(copy(account), T { v: 0 });
+                i = 0;
+                while (copy(i) < copy(n)) {
+                    T { v } = move_from<T>(Signer.address_of(copy(account)));
+                    move_to<T>(copy(account), T { v: move(v) + 1 });
+                    i = move(i) + 1;
+                }
+                return;
+            }
+
+            public bump(account: &signer, n: u64) acquires T {
+                let i: u64;
+                let t_ref: &mut Self.T;
+                let v_ref: &mut u64;
+                let v: u64;
+                move_to<T>(copy(account), T { v: 0 });
+                i = 0;
+                while (copy(i) < copy(n)) {
+                    t_ref = borrow_global_mut<T>(Signer.address_of(copy(account)));
+                    v_ref = &mut move(t_ref).v;
+                    v = *copy(v_ref);
+                    *move(v_ref) = move(v) + 1;
+                    i = move(i) + 1;
+                }
+                return;
+            }
+        }
+    ";
+    Compiler {
+        address: *sender.address(),
+        ..Compiler::default()
+    }
+    .into_compiled_module("file_name", module_code)
+    .unwrap()
+}
+
+// Calls `M.<function>` with `n`. `M.churn` moves `M.T` to and from the sender 1 + 2 * `n` times,
+// `M.bump` moves it to the sender once and borrows it mutably `n` times.
+fn churn_txn(
+    sender: &AccountData,
+    module: &CompiledModule,
+    function: &str,
+    n: u64,
+) -> SignedTransaction {
+    let program = format!(
+        "
+        import 0x{}.M;
+
+        main(account: &signer) {{
+            M.{}(move(account), {});
+            return;
+        }}
+        ",
+        sender.address(),
+        function,
+        n,
+    );
+    script_txn_impl(
+        sender.account(),
+        10,
+        &program,
+        vec![module.clone()],
+        vec![],
+        100_000,
+        0,
+    )
+}
+
+#[test]
+fn max_resource_writes() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    executor.add_account_data(&sender);
+    let module = churn_module(&sender);
+    executor.add_module(&module.self_id(), &module);
+
+    let mut vm = LibraVM::new_with_config(ExecutorConfig {
+        max_resource_writes: Some(10),
+        ..ExecutorConfig::default()
+    });
+    let txn = churn_txn(&sender, &module, "churn", 4);
+    let output = vm
+        .execute_single_transaction(txn.clone(), executor.get_state_view())
+        .unwrap();
+    assert!(transaction_status_eq(
+        output.status(),
+        &TransactionStatus::Keep(VMStatus::executed())
+    ));
+    assert_eq!(output, executor.execute_transaction(txn));
+
+    // Only the last value is written, but the limit counts every move.
+    let txn = churn_txn(&sender, &module, "churn", 5);
+    let unlimited_output = executor.execute_transaction(txn.clone());
+    assert!(transaction_status_eq(
+        unlimited_output.status(),
+        &TransactionStatus::Keep(VMStatus::executed())
+    ));
+    let output = vm
+        .execute_single_transaction(txn, executor.get_state_view())
+        .unwrap();
+    let status = output.status().vm_status();
+    assert_eq!(status.major_status, StatusCode::MAX_RESOURCE_WRITES_REACHED);
+    assert!(status
+        .message
+        .as_ref()
+        .unwrap()
+        .starts_with("max writes: 10, 11 writes to "));
+    // The sender still pays for the moves.
+    assert!(!output.status().is_discarded());
+    assert!(output.gas_used() > 0);
+}
+
+#[test]
+fn max_resource_writes_counts_mutable_borrows() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    executor.add_account_data(&sender);
+    let module = churn_module(&sender);
+    executor.add_module(&module.self_id(), &module);
+
+    let mut vm = LibraVM::new_with_config(ExecutorConfig {
+        max_resource_writes: Some(10),
+        ..ExecutorConfig::default()
+    });
+    let txn = churn_txn(&sender, &module, "bump", 9);
+    let output = vm
+        .execute_single_transaction(txn.clone(), executor.get_state_view())
+        .unwrap();
+    assert!(transaction_status_eq(
+        output.status(),
+        &TransactionStatus::Keep(VMStatus::executed())
+    ));
+    assert_eq!(output, executor.execute_transaction(txn));
+
+    // The resource is changed in place, but every mutable borrow counts as a write.
+    let txn = churn_txn(&sender, &module, "bump", 10);
+    let output = vm
+        .execute_single_transaction(txn, executor.get_state_view())
+        .unwrap();
+    let status = output.status().vm_status();
+    assert_eq!(status.major_status, StatusCode::MAX_RESOURCE_WRITES_REACHED);
+    assert!(status
+        .message
+        .as_ref()
+        .unwrap()
+        .starts_with("max writes: 10, 11 writes to "));
+    assert!(!output.status().is_discarded());
+}
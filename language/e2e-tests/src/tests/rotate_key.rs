@@ -13,9 +13,11 @@ use libra_crypto::{
     PrivateKey, SigningKey, Uniform,
 };
 use libra_types::{
+    account_address::AccountAddress,
     transaction::{authenticator::AuthenticationKey, SignedTransaction, TransactionStatus},
     vm_status::{StatusCode, VMStatus},
 };
+use libra_vm::LibraVM;
 
 #[test]
 fn rotate_ed25519_key() {
@@ -67,6 +69,38 @@ fn rotate_ed25519_key() {
     );
 }
 
+#[test]
+fn vm_reads_account_auth_key() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    executor.add_account_data(&sender);
+
+    let vm = LibraVM::new();
+    assert_eq!(
+        vm.account_auth_key(executor.get_state_view(), *sender.address())
+            .unwrap(),
+        Some(sender.account().auth_key()),
+    );
+
+    // Rotating the key is reflected in the VM's view of the account.
+    let privkey = Ed25519PrivateKey::generate_for_testing();
+    let new_key_hash = AuthenticationKey::ed25519(&privkey.public_key()).to_vec();
+    let output =
+        executor.execute_and_apply(rotate_key_txn(sender.account(), new_key_hash.clone(), 10));
+    assert!(!output.status().is_discarded());
+    assert_eq!(
+        vm.account_auth_key(executor.get_state_view(), *sender.address())
+            .unwrap(),
+        Some(new_key_hash),
+    );
+
+    assert_eq!(
+        vm.account_auth_key(executor.get_state_view(), AccountAddress::random())
+            .unwrap(),
+        None,
+    );
+}
+
 #[test]
 fn rotate_ed25519_multisig_key() {
     let mut executor = FakeExecutor::from_genesis_file();
@@ -126,6 +160,40 @@ fn rotate_ed25519_multisig_key() {
     );
 }
 
+#[test]
+fn multisig_discard_reports_invalid_signer() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    executor.add_account_data(&sender);
+
+    // create a 2-of-3 multisig policy
+    let mut keygen = KeyGen::from_seed([9u8; 32]);
+    let (privkey0, pubkey0) = keygen.generate_keypair();
+    let (_, pubkey1) = keygen.generate_keypair();
+    let (privkey2, pubkey2) = keygen.generate_keypair();
+    let multi_ed_public_key =
+        MultiEd25519PublicKey::new(vec![pubkey0, pubkey1, pubkey2], 2).unwrap();
+    let new_auth_key = AuthenticationKey::multi_ed25519(&multi_ed_public_key);
+
+    // The signature of key 2 is for another transaction.
+    let txn = raw_rotate_key_txn(*sender.address(), new_auth_key.to_vec(), 10);
+    let other_txn = raw_rotate_key_txn(*sender.address(), new_auth_key.to_vec(), 11);
+    let signature = MultiEd25519Signature::new(vec![
+        (privkey0.sign(&txn), 0),
+        (privkey2.sign(&other_txn), 2),
+    ])
+    .unwrap();
+    let signed_txn = SignedTransaction::new_multisig(txn, multi_ed_public_key, signature);
+    let output = executor.execute_transaction(signed_txn);
+    match output.status() {
+        TransactionStatus::Discard(status) => {
+            assert_eq!(status.major_status, StatusCode::INVALID_SIGNATURE);
+            assert_eq!(status.sub_status, Some(2));
+        }
+        status => panic!("Unexpected status: {:?}", status),
+    }
+}
+
 #[test]
 
 fn rotate_shared_ed25519_public_key() {}
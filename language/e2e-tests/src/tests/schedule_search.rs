@@ -0,0 +1,64 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    account::AccountData, common_transactions::peer_to_peer_txn, executor::FakeExecutor,
+    transaction_status_eq,
+};
+use libra_types::{
+    transaction::{Transaction, TransactionPayload, TransactionStatus},
+    vm_status::{StatusCode, VMStatus},
+};
+use libra_vm::LibraVM;
+
+#[test]
+fn min_viable_schedule_lets_block_succeed() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let txn = peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000);
+    let gas_used = executor.execute_transaction(txn.clone()).gas_used();
+    let mut vm = LibraVM::new();
+    vm.load_configs(executor.get_state_view());
+    let intrinsic_gas = vm
+        .intrinsic_gas_params()
+        .unwrap()
+        .intrinsic_gas(txn.raw_txn_bytes_len() as u64);
+    assert!(gas_used > intrinsic_gas + 1);
+
+    // Only leave half of the gas the script needs.
+    let script = match txn.payload() {
+        TransactionPayload::Script(script) => script.clone(),
+        _ => panic!("peer to peer transaction must be a script"),
+    };
+    let limited_txn = sender
+        .account()
+        .transaction()
+        .script(script)
+        .sequence_number(10)
+        .max_gas_amount(intrinsic_gas + (gas_used - intrinsic_gas) / 2)
+        .sign();
+    assert!(transaction_status_eq(
+        executor.execute_transaction(limited_txn.clone()).status(),
+        &TransactionStatus::Keep(VMStatus::new(StatusCode::OUT_OF_GAS, None, None))
+    ));
+
+    let block = vec![Transaction::UserTransaction(limited_txn)];
+    let schedule = vm
+        .min_viable_schedule(executor.get_state_view(), block.clone())
+        .unwrap();
+    assert_ne!(&schedule, vm.internals().gas_schedule().unwrap());
+
+    let mut scaled_vm = LibraVM::new();
+    scaled_vm.set_gas_schedule_override(Some(schedule));
+    let outputs = scaled_vm
+        .execute_transaction_block(block, executor.get_state_view())
+        .unwrap();
+    assert!(transaction_status_eq(
+        outputs[0].status(),
+        &TransactionStatus::Keep(VMStatus::executed())
+    ));
+}
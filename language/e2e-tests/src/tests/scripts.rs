@@ -1,10 +1,16 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{account, account::AccountData, executor::FakeExecutor, gas_costs};
+use crate::{
+    account, account::AccountData, compile::compile_script_with_address, executor::FakeExecutor,
+    gas_costs, transaction_status_eq,
+};
 use libra_types::{
-    account_address::AccountAddress, account_config, on_chain_config::VMPublishingOption,
-    transaction::TransactionStatus, vm_status::StatusCode,
+    account_address::AccountAddress,
+    account_config,
+    on_chain_config::VMPublishingOption,
+    transaction::{TransactionArgument, TransactionPayload, TransactionStatus},
+    vm_status::{StatusCode, VMStatus},
 };
 use move_core_types::identifier::Identifier;
 use vm::file_format::{
@@ -277,3 +283,59 @@ fn script_bad_sig_function_dep() {
     assert_eq!(balance, updated_sender_balance.coin());
     assert_eq!(11, updated_sender.sequence_number());
 }
+
+#[test]
+fn script_vector_args() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    executor.add_account_data(&sender);
+
+    let recipients = vec![AccountAddress::random(), AccountAddress::random()];
+    let program = format!(
+        "
+        import 0x1.Vector;
+
+        main(account: &signer, amounts: vector<u64>, recipients: vector<address>) {{
+            assert(Vector.length<u64>(&amounts) == Vector.length<address>(&recipients), 1);
+            if (Vector.length<address>(&recipients) > 0) {{
+                assert(*Vector.borrow<u64>(&amounts, 1) == 20, 2);
+                assert(*Vector.borrow<address>(&recipients, 1) == 0x{}, 3);
+            }}
+            return;
+        }}
+        ",
+        recipients[1],
+    );
+    let code = match compile_script_with_address(sender.address(), "file_name", &program, vec![]) {
+        TransactionPayload::Script(script) => script.code().to_vec(),
+        _ => unreachable!(),
+    };
+    let script_txn = |seq_num, amounts: Vec<u64>, recipients: Vec<AccountAddress>| {
+        sender.account().create_signed_txn_with_args(
+            code.clone(),
+            vec![],
+            vec![
+                TransactionArgument::U64Vector(amounts),
+                TransactionArgument::AddressVector(recipients),
+            ],
+            seq_num,
+            gas_costs::TXN_RESERVED,
+            1,
+            account_config::LBR_NAME.to_owned(),
+        )
+    };
+
+    let output = executor.execute_transaction(script_txn(10, vec![10, 20], recipients));
+    assert!(transaction_status_eq(
+        output.status(),
+        &TransactionStatus::Keep(VMStatus::executed())
+    ));
+    executor.apply_write_set(output.write_set());
+
+    // The element type of a vector argument is part of its variant, so empty lists work too.
+    let output = executor.execute_transaction(script_txn(11, vec![], vec![]));
+    assert!(transaction_status_eq(
+        output.status(),
+        &TransactionStatus::Keep(VMStatus::executed())
+    ));
+}
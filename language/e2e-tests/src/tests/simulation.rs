@@ -0,0 +1,521 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    account::{self, Account, AccountData, AccountRoleSpecifier},
+    common_transactions::{peer_to_peer_txn, script_txn, script_txn_impl},
+    executor::FakeExecutor,
+    transaction_status_eq,
+};
+use anyhow::Result;
+use libra_crypto::{ed25519::Ed25519PrivateKey, Uniform};
+use libra_state_view::StateView;
+use libra_types::{
+    access_path::AccessPath,
+    account_config::lbr_type_tag,
+    test_helpers::transaction_test_helpers,
+    transaction::{SignedTransaction, Transaction, TransactionStatus},
+    vm_status::{StatusCode, VMStatus},
+};
+use libra_vm::{
+    read_cache::{LruReadCache, SharedReadCache},
+    LibraVM,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use transaction_builder::encode_peer_to_peer_with_metadata_script;
+
+#[test]
+fn simulate_second_transaction_of_batch() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    // The second transaction of a batch can't run before the first one.
+    let second = peer_to_peer_txn(sender.account(), receiver.account(), 11, 1_000);
+    assert!(transaction_status_eq(
+        executor.execute_transaction(second.clone()).status(),
+        &TransactionStatus::Discard(VMStatus::new(
+            StatusCode::SEQUENCE_NUMBER_TOO_NEW,
+            None,
+            None
+        ))
+    ));
+
+    // Simulating it at the current sequence number of the sender passes the prologue.
+    let mut vm = LibraVM::new();
+    let output =
+        vm.simulate_transaction_at_sequence_number(second.clone(), executor.get_state_view(), 10);
+    assert!(transaction_status_eq(
+        output.status(),
+        &TransactionStatus::Keep(VMStatus::executed())
+    ));
+    let first = peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000);
+    assert_eq!(
+        output.gas_used(),
+        executor.execute_transaction(first).gas_used()
+    );
+
+    // The override does not bypass the prologue.
+    let output = vm.simulate_transaction_at_sequence_number(second, executor.get_state_view(), 9);
+    assert!(transaction_status_eq(
+        output.status(),
+        &TransactionStatus::Discard(VMStatus::new(
+            StatusCode::SEQUENCE_NUMBER_TOO_OLD,
+            None,
+            None
+        ))
+    ));
+}
+
+#[test]
+fn execute_single_transaction_matches_block_execution() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let mut vm = LibraVM::new();
+    for seq_num in &[10, 11, 9] {
+        let txn = peer_to_peer_txn(sender.account(), receiver.account(), *seq_num, 1_000);
+        let output = vm
+            .execute_single_transaction(txn.clone(), executor.get_state_view())
+            .unwrap();
+        assert_eq!(output, executor.execute_transaction(txn));
+    }
+
+    // Signature failures are discarded rather than reported as errors.
+    let private_key = Ed25519PrivateKey::generate_for_testing();
+    let program = encode_peer_to_peer_with_metadata_script(
+        lbr_type_tag(),
+        *receiver.address(),
+        100,
+        vec![],
+        vec![],
+    );
+    let badly_signed_txn = transaction_test_helpers::get_test_unchecked_txn(
+        *sender.address(),
+        10,
+        &private_key,
+        sender.account().pubkey.clone(),
+        Some(program),
+    );
+    let output = vm
+        .execute_single_transaction(badly_signed_txn, executor.get_state_view())
+        .unwrap();
+    assert!(transaction_status_eq(
+        output.status(),
+        &TransactionStatus::Discard(VMStatus::new(StatusCode::INVALID_SIGNATURE, None, None))
+    ));
+}
+
+fn loop_txn(sender: &AccountData, seq_num: u64, gas_unit_price: u64) -> SignedTransaction {
+    loop_txn_with_max_gas(sender, seq_num, gas_unit_price, 100_000)
+}
+
+fn loop_txn_with_max_gas(
+    sender: &AccountData,
+    seq_num: u64,
+    gas_unit_price: u64,
+    max_gas_amount: u64,
+) -> SignedTransaction {
+    let program = "
+        main() {
+            let i: u64;
+            i = 0;
+            while (copy(i) < 100) {
+                i = move(i) + 1;
+            }
+            return;
+        }
+    ";
+    script_txn_impl(
+        sender.account(),
+        seq_num,
+        program,
+        vec![],
+        vec![],
+        max_gas_amount,
+        gas_unit_price,
+    )
+}
+
+#[test]
+fn simulate_user_transaction_reports_gas_without_prologue_checks() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let funded = AccountData::new(1_000_000, 10);
+    let unfunded = AccountData::new(0, 10);
+    executor.add_account_data(&funded);
+    executor.add_account_data(&unfunded);
+
+    let valid_txn = loop_txn(&funded, 10, 0);
+    let output = executor.execute_transaction(valid_txn);
+    assert!(transaction_status_eq(
+        output.status(),
+        &TransactionStatus::Keep(VMStatus::executed())
+    ));
+
+    // The sender can't pay for the gas and the sequence number is wrong.
+    let invalid_txn = loop_txn(&unfunded, 20, 1);
+    assert!(executor
+        .execute_transaction(invalid_txn.clone())
+        .status()
+        .is_discarded());
+
+    let mut vm = LibraVM::new();
+    let simulated = vm.simulate_user_transaction(&invalid_txn, executor.get_state_view());
+    assert!(transaction_status_eq(
+        simulated.status(),
+        &TransactionStatus::Keep(VMStatus::executed())
+    ));
+    assert_eq!(simulated.gas_used(), output.gas_used());
+}
+
+#[test]
+fn simulate_transaction_with_foreign_fee() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    // The sender only holds Coin1, the transaction pays for gas in LBR.
+    let sender = AccountData::with_account(
+        Account::new(),
+        1_000_000,
+        account::coin1_currency_code(),
+        10,
+        AccountRoleSpecifier::ParentVASP,
+    );
+    executor.add_account_data(&sender);
+    let txn = loop_txn(&sender, 10, 2);
+
+    let mut vm = LibraVM::new();
+    // 1.5 Coin1 per LBR.
+    let exchange_rate = 3 << 31;
+    let simulation = vm
+        .simulate_transaction_with_foreign_fee(
+            txn.clone(),
+            executor.get_state_view(),
+            &account::coin1_currency_code(),
+            exchange_rate,
+        )
+        .unwrap();
+    assert!(transaction_status_eq(
+        simulation.output.status(),
+        &TransactionStatus::Keep(VMStatus::executed())
+    ));
+    let gas_used = simulation.output.gas_used();
+    assert_eq!(simulation.fee, gas_used * 2);
+    assert_eq!(simulation.foreign_fee, gas_used * 3);
+
+    // The converted fee is the one deducted from the Coin1 balance.
+    executor.apply_write_set(simulation.output.write_set());
+    let balance = executor
+        .read_balance_resource(sender.account(), account::coin1_currency_code())
+        .unwrap();
+    assert_eq!(balance.coin(), 1_000_000 - simulation.foreign_fee);
+
+    let status = vm
+        .simulate_transaction_with_foreign_fee(
+            txn,
+            executor.get_state_view(),
+            &account::coin1_currency_code(),
+            0,
+        )
+        .unwrap_err();
+    assert_eq!(status.major_status, StatusCode::INVALID_GAS_SPECIFIER);
+}
+
+#[test]
+fn validate_transaction_runs_the_prologue_only() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let vm = LibraVM::new();
+    let txn = peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000);
+    assert_eq!(
+        vm.validate_transaction(&txn, executor.get_state_view()),
+        None
+    );
+    // The script did not run: the transaction is still valid.
+    assert_eq!(
+        vm.validate_transaction(&txn, executor.get_state_view()),
+        None
+    );
+    assert!(transaction_status_eq(
+        executor.execute_transaction(txn).status(),
+        &TransactionStatus::Keep(VMStatus::executed())
+    ));
+
+    let old_txn = peer_to_peer_txn(sender.account(), receiver.account(), 9, 1_000);
+    assert_eq!(
+        vm.validate_transaction(&old_txn, executor.get_state_view())
+            .unwrap()
+            .major_status,
+        StatusCode::SEQUENCE_NUMBER_TOO_OLD
+    );
+
+    // The sender can't pay for the gas.
+    let unfunded = AccountData::new(0, 10);
+    executor.add_account_data(&unfunded);
+    assert_eq!(
+        vm.validate_transaction(&loop_txn(&unfunded, 10, 1), executor.get_state_view())
+            .unwrap()
+            .major_status,
+        StatusCode::INSUFFICIENT_BALANCE_FOR_TRANSACTION_FEE
+    );
+
+    // A script that aborts passes, as its body is not executed.
+    let program = "
+        main() {
+            abort 77;
+        }
+    ";
+    let txn = script_txn(sender.account(), 10, program, 100_000);
+    assert_eq!(
+        vm.validate_transaction(&txn, executor.get_state_view()),
+        None
+    );
+    assert_eq!(
+        executor
+            .execute_transaction(txn)
+            .status()
+            .vm_status()
+            .major_status,
+        StatusCode::ABORTED
+    );
+}
+
+#[test]
+fn validate_batch_against_the_same_state() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let badly_signed_txn = transaction_test_helpers::get_test_unchecked_txn(
+        *sender.address(),
+        10,
+        &Ed25519PrivateKey::generate_for_testing(),
+        sender.account().pubkey.clone(),
+        Some(encode_peer_to_peer_with_metadata_script(
+            lbr_type_tag(),
+            *receiver.address(),
+            100,
+            vec![],
+            vec![],
+        )),
+    );
+    let txns = vec![
+        peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000),
+        // Validated against the same state as the first one, so not rejected as a replay.
+        peer_to_peer_txn(sender.account(), receiver.account(), 10, 2_000),
+        peer_to_peer_txn(receiver.account(), sender.account(), 10, 1_000),
+        peer_to_peer_txn(receiver.account(), sender.account(), 9, 1_000),
+        badly_signed_txn,
+    ];
+    let results = LibraVM::new().validate_batch(txns.clone(), executor.get_state_view());
+    assert_eq!(
+        results.iter().map(|(txn, _)| txn).collect::<Vec<_>>(),
+        txns.iter().collect::<Vec<_>>()
+    );
+    let statuses: Vec<_> = results
+        .into_iter()
+        .map(|(_, status)| status.map(|status| status.major_status))
+        .collect();
+    assert_eq!(
+        statuses,
+        vec![
+            None,
+            None,
+            None,
+            Some(StatusCode::SEQUENCE_NUMBER_TOO_OLD),
+            Some(StatusCode::INVALID_SIGNATURE),
+        ]
+    );
+}
+
+/// Counts the reads that reach the wrapped view.
+struct CountingView<'a> {
+    view: &'a (dyn StateView + Sync),
+    reads: AtomicUsize,
+}
+
+impl<'a> StateView for CountingView<'a> {
+    fn get(&self, access_path: &AccessPath) -> Result<Option<Vec<u8>>> {
+        self.reads.fetch_add(1, Ordering::SeqCst);
+        self.view.get(access_path)
+    }
+
+    fn multi_get(&self, access_paths: &[AccessPath]) -> Result<Vec<Option<Vec<u8>>>> {
+        access_paths.iter().map(|ap| self.get(ap)).collect()
+    }
+
+    fn is_genesis(&self) -> bool {
+        self.view.is_genesis()
+    }
+}
+
+#[test]
+fn validate_batches_through_a_shared_read_cache() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+    let txns = vec![
+        peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000),
+        peer_to_peer_txn(sender.account(), receiver.account(), 11, 1_000),
+        peer_to_peer_txn(receiver.account(), sender.account(), 9, 1_000),
+    ];
+    let statuses = |results: Vec<(SignedTransaction, Option<VMStatus>)>| {
+        results
+            .into_iter()
+            .map(|(_, status)| status.map(|status| status.major_status))
+            .collect::<Vec<_>>()
+    };
+    let vm = LibraVM::new();
+    let expected = statuses(vm.validate_batch(txns.clone(), executor.get_state_view()));
+    assert_eq!(
+        expected,
+        vec![None, None, Some(StatusCode::SEQUENCE_NUMBER_TOO_OLD)]
+    );
+
+    let view = CountingView {
+        view: executor.get_state_view(),
+        reads: AtomicUsize::new(0),
+    };
+    let cache = SharedReadCache::new(&view);
+    assert_eq!(statuses(vm.validate_batch(txns.clone(), &cache)), expected);
+    let first_reads = view.reads.load(Ordering::SeqCst);
+    assert!(first_reads > 0);
+    assert!(!cache.is_empty());
+
+    // The second batch only reads cached values.
+    assert_eq!(statuses(vm.validate_batch(txns, &cache)), expected);
+    assert_eq!(view.reads.load(Ordering::SeqCst), first_reads);
+}
+
+#[test]
+fn execute_blocks_through_an_lru_read_cache() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+    let txns = vec![
+        peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000),
+        peer_to_peer_txn(receiver.account(), sender.account(), 10, 1_000),
+    ];
+    let block = || {
+        txns.iter()
+            .cloned()
+            .map(Transaction::UserTransaction)
+            .collect::<Vec<_>>()
+    };
+    let expected = executor.execute_block(txns.clone()).unwrap();
+
+    let view = CountingView {
+        view: executor.get_state_view(),
+        reads: AtomicUsize::new(0),
+    };
+    let mut vm = LibraVM::new();
+    let cache = LruReadCache::new(&view, 1_000);
+    assert_eq!(
+        vm.execute_transaction_block(block(), &cache).unwrap(),
+        expected
+    );
+    let first_reads = view.reads.load(Ordering::SeqCst);
+    let (hits, misses) = cache.hits_and_misses();
+    assert_eq!(misses as usize, first_reads);
+    assert_eq!(cache.len(), first_reads);
+
+    // The second run only reads cached values.
+    assert_eq!(
+        vm.execute_transaction_block(block(), &cache).unwrap(),
+        expected
+    );
+    assert_eq!(view.reads.load(Ordering::SeqCst), first_reads);
+    let (second_hits, second_misses) = cache.hits_and_misses();
+    assert!(second_hits > hits);
+    assert_eq!(second_misses, misses);
+
+    // Invalidated paths are read again.
+    for output in &expected {
+        cache.invalidate(output.write_set());
+    }
+    assert!(cache.len() < first_reads);
+    assert_eq!(
+        vm.execute_transaction_block(block(), &cache).unwrap(),
+        expected
+    );
+    assert!(view.reads.load(Ordering::SeqCst) > first_reads);
+
+    // The least recently read values make room for the new ones.
+    let small_cache = LruReadCache::new(&view, 2);
+    assert_eq!(
+        vm.execute_transaction_block(block(), &small_cache).unwrap(),
+        expected
+    );
+    assert_eq!(small_cache.len(), 2);
+    let uncached = LruReadCache::new(&view, 0);
+    assert_eq!(
+        vm.execute_transaction_block(block(), &uncached).unwrap(),
+        expected
+    );
+    assert!(uncached.is_empty());
+}
+
+#[test]
+fn estimate_gas() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let mut vm = LibraVM::new();
+    let txn = peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000);
+    let estimate = vm.estimate_gas(&txn, executor.get_state_view()).unwrap();
+    let gas_used = executor.execute_transaction(txn).gas_used();
+    assert!(estimate >= gas_used);
+    assert!(estimate <= gas_used + gas_used / 10);
+
+    // The max gas amount of the transaction is too low for it to complete.
+    let loop_gas_used = executor
+        .execute_transaction(loop_txn(&sender, 10, 1))
+        .gas_used();
+    let starved_txn = loop_txn_with_max_gas(&sender, 10, 1, loop_gas_used - 1);
+    assert_eq!(
+        executor
+            .execute_transaction(starved_txn.clone())
+            .status()
+            .vm_status()
+            .major_status,
+        StatusCode::OUT_OF_GAS
+    );
+    let estimate = vm
+        .estimate_gas(&starved_txn, executor.get_state_view())
+        .unwrap();
+    assert!(estimate >= loop_gas_used);
+    assert!(transaction_status_eq(
+        executor
+            .execute_transaction(loop_txn_with_max_gas(&sender, 10, 1, estimate))
+            .status(),
+        &TransactionStatus::Keep(VMStatus::executed())
+    ));
+
+    let program = "
+        main() {
+            abort 77;
+        }
+    ";
+    let aborting_txn = script_txn(sender.account(), 10, program, 100_000);
+    let status = vm
+        .estimate_gas(&aborting_txn, executor.get_state_view())
+        .unwrap_err();
+    assert_eq!(status.major_status, StatusCode::ABORTED);
+    assert_eq!(status.sub_status, Some(77));
+}
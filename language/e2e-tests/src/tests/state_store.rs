@@ -0,0 +1,129 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    account::{self, AccountData},
+    common_transactions::peer_to_peer_txn,
+    data_store::{FakeDataStore, GENESIS_CHANGE_SET},
+    executor::FakeExecutor,
+};
+use anyhow::Result;
+use libra_state_view::StateView;
+use libra_types::{access_path::AccessPath, transaction::Transaction, write_set::WriteOp};
+use libra_vm::{
+    state_store::{apply_block_outputs, InMemoryStateView, StateStore},
+    LibraVM,
+};
+
+/// Counts the batches written to the wrapped store.
+#[derive(Default)]
+struct CountingStore {
+    data_store: FakeDataStore,
+    batches: usize,
+}
+
+impl StateStore for CountingStore {
+    fn write_batch(&mut self, writes: Vec<(AccessPath, WriteOp)>) -> Result<()> {
+        self.batches += 1;
+        self.data_store.write_batch(writes)
+    }
+}
+
+#[test]
+fn apply_mixed_block_outputs() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let txns = vec![
+        peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000),
+        // Discarded: the sequence number is too old.
+        peer_to_peer_txn(sender.account(), receiver.account(), 5, 1_000),
+        peer_to_peer_txn(sender.account(), receiver.account(), 11, 2_000),
+    ];
+    let outputs = executor.execute_block(txns).unwrap();
+    assert!(!outputs[0].status().is_discarded());
+    assert!(outputs[1].status().is_discarded());
+    assert!(!outputs[2].status().is_discarded());
+
+    let mut store = CountingStore::default();
+    apply_block_outputs(&mut store, &outputs).unwrap();
+    assert_eq!(store.batches, 1);
+
+    // Applying the kept outputs one by one gives the same state.
+    for output in &outputs {
+        if !output.status().is_discarded() {
+            executor.apply_write_set(output.write_set());
+        }
+    }
+    for output in &outputs {
+        for (access_path, _) in output.write_set() {
+            assert_eq!(
+                store.data_store.get(access_path).unwrap(),
+                executor.read_from_access_path(access_path),
+            );
+        }
+    }
+
+    // The last write to the sender's account wins.
+    let sender_path = sender.make_account_access_path();
+    let last_write = outputs[2]
+        .write_set()
+        .iter()
+        .find(|(access_path, _)| access_path == &sender_path)
+        .map(|(_, write_op)| write_op.clone());
+    match last_write {
+        Some(WriteOp::Value(blob)) => {
+            assert_eq!(store.data_store.get(&sender_path).unwrap(), Some(blob))
+        }
+        _ => panic!("the sender's account should be written"),
+    }
+}
+
+#[test]
+fn execute_blocks_against_in_memory_state() {
+    let mut state = InMemoryStateView::default();
+    state.apply_write_set(GENESIS_CHANGE_SET.write_set());
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    state.apply_write_set(&sender.to_writeset());
+    state.apply_write_set(&receiver.to_writeset());
+
+    let mut vm = LibraVM::new();
+    let lbr = account::lbr_currency_code();
+    let p2p = |seq_num, amount| {
+        Transaction::UserTransaction(peer_to_peer_txn(
+            sender.account(),
+            receiver.account(),
+            seq_num,
+            amount,
+        ))
+    };
+    // The second block builds on the state left by the first one.
+    for seq_num in 10..12 {
+        let outputs = vm
+            .execute_block_and_apply(vec![p2p(seq_num, 1_000)], &mut state)
+            .unwrap();
+        assert!(!outputs[0].status().is_discarded());
+    }
+    assert_eq!(
+        vm.account_sequence_number(&state, *sender.address())
+            .unwrap(),
+        Some(12)
+    );
+    assert_eq!(
+        vm.account_balance(&state, *receiver.address(), &lbr)
+            .unwrap(),
+        Some(102_000)
+    );
+
+    // Discarded transactions leave the state untouched.
+    let before = state.clone();
+    let outputs = vm
+        .execute_block_and_apply(vec![p2p(5, 1_000)], &mut state)
+        .unwrap();
+    assert!(outputs[0].status().is_discarded());
+    assert_eq!(state, before);
+}
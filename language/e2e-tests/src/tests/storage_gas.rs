@@ -0,0 +1,201 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    account::AccountData,
+    common_transactions::{peer_to_peer_txn, script_txn_impl},
+    executor::FakeExecutor,
+};
+use compiler::Compiler;
+use libra_types::{
+    account_config::libra_root_address,
+    on_chain_config::{OnChainConfig, StorageGasConfig},
+    transaction::{
+        SignedTransaction, Transaction, TransactionArgument, TransactionOutput, TransactionStatus,
+    },
+    vm_status::{StatusCode, VMStatus},
+};
+use libra_vm::{
+    executor_config::ExecutorConfig,
+    storage_gas::{PerAccessGasModel, PerByteGasModel, StorageGasModel},
+    LibraVM,
+};
+use move_vm_types::values::Value;
+use std::sync::Arc;
+use vm::CompiledModule;
+
+fn gas_used(
+    executor: &FakeExecutor,
+    txn: &SignedTransaction,
+    storage_gas_model: Option<Arc<dyn StorageGasModel>>,
+) -> u64 {
+    let mut vm = LibraVM::new_with_config(ExecutorConfig {
+        storage_gas_model,
+        ..ExecutorConfig::default()
+    });
+    let outputs = vm
+        .execute_transaction_block(
+            vec![Transaction::UserTransaction(txn.clone())],
+            executor.get_state_view(),
+        )
+        .unwrap();
+    assert!(!outputs[0].status().is_discarded());
+    outputs[0].gas_used()
+}
+
+#[test]
+fn per_access_vs_per_byte_storage_gas() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+    let txn = peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000);
+
+    let base_gas = gas_used(&executor, &txn, None);
+    assert_eq!(
+        base_gas,
+        executor.execute_transaction(txn.clone()).gas_used()
+    );
+
+    let per_access = |gas_per_access| {
+        gas_used(
+            &executor,
+            &txn,
+            Some(Arc::new(PerAccessGasModel { gas_per_access })),
+        )
+    };
+    let accesses = per_access(1) - base_gas;
+    assert!(accesses > 0);
+    assert_eq!(per_access(3), base_gas + 3 * accesses);
+
+    let per_byte_gas = gas_used(
+        &executor,
+        &txn,
+        Some(Arc::new(PerByteGasModel { gas_per_byte: 1 })),
+    );
+    // Every resource read by a peer to peer payment is more than one byte long.
+    assert!(per_byte_gas - base_gas > accesses);
+}
+
+fn store_module(sender: &AccountData) -> CompiledModule {
+    let module_code = "
+        module M {
+            resource T { v: vector<u8> }
+
+            public store(account: &signer, v: vector<u8>) {
+                move_to<T>(move(account), T { v: move(v) });
+                return;
+            }
+        }
+    ";
+    Compiler {
+        address: *sender.address(),
+        ..Compiler::default()
+    }
+    .into_compiled_module("file_name", module_code)
+    .unwrap()
+}
+
+// Stores `size` bytes under the sender.
+fn store_txn(
+    sender: &AccountData,
+    module: &CompiledModule,
+    size: usize,
+    max_gas_amount: u64,
+) -> SignedTransaction {
+    let program = format!(
+        "
+        import 0x{}.M;
+
+        main(account: &signer, v: vector<u8>) {{
+            M.store(move(account), move(v));
+            return;
+        }}
+        ",
+        sender.address(),
+    );
+    script_txn_impl(
+        sender.account(),
+        10,
+        &program,
+        vec![module.clone()],
+        vec![TransactionArgument::U8Vector(vec![7; size])],
+        max_gas_amount,
+        0,
+    )
+}
+
+fn execute(executor: &FakeExecutor, txn: SignedTransaction) -> TransactionOutput {
+    LibraVM::new()
+        .execute_single_transaction(txn, executor.get_state_view())
+        .unwrap()
+}
+
+#[test]
+fn write_set_storage_gas() {
+    let sender = AccountData::new(1_000_000, 10);
+    let module = store_module(&sender);
+    // The same state, with the charge left off as in genesis or turned on by the libra root
+    // account.
+    let executor_with = |charge_written_bytes| {
+        let mut executor = FakeExecutor::from_genesis_file();
+        executor.add_account_data(&sender);
+        executor.add_module(&module.self_id(), &module);
+        executor.new_block();
+        if charge_written_bytes {
+            executor.exec(
+                "StorageGasConfig",
+                "set_charge_written_bytes",
+                vec![],
+                vec![
+                    Value::transaction_argument_signer_reference(libra_root_address()),
+                    Value::bool(true),
+                ],
+                &libra_root_address(),
+            );
+        }
+        assert_eq!(
+            StorageGasConfig::fetch_config(executor.get_state_view()),
+            Some(StorageGasConfig {
+                charge_written_bytes,
+            })
+        );
+        executor
+    };
+    let uncharged = executor_with(false);
+    let charged = executor_with(true);
+    let executor = |charge| if charge { &charged } else { &uncharged };
+    let kept = TransactionStatus::Keep(VMStatus::executed());
+
+    let gas_used = |size, charge| {
+        let output = execute(
+            executor(charge),
+            store_txn(&sender, &module, size, 1_000_000),
+        );
+        assert_eq!(output.status(), &kept);
+        output.gas_used()
+    };
+    let small = 8;
+    let large = 3_000;
+    let small_storage_gas = gas_used(small, true) - gas_used(small, false);
+    let large_storage_gas = gas_used(large, true) - gas_used(large, false);
+    assert!(small_storage_gas > 0);
+    // The large value alone is 3000 bytes.
+    assert!(large_storage_gas >= small_storage_gas + (large - small) as u64);
+
+    // Enough gas for the small write, and to run the large one without its storage gas.
+    let max_gas_amount = gas_used(small, true).max(gas_used(large, false)) + 1_000;
+    let output = execute(&charged, store_txn(&sender, &module, small, max_gas_amount));
+    assert_eq!(output.status(), &kept);
+
+    let txn = store_txn(&sender, &module, large, max_gas_amount);
+    assert_eq!(execute(&uncharged, txn.clone()).status(), &kept);
+    let output = execute(&charged, txn);
+    assert_eq!(
+        output.status().vm_status().major_status,
+        StatusCode::OUT_OF_GAS
+    );
+    assert!(!output.status().is_discarded());
+    assert_eq!(output.gas_used(), max_gas_amount);
+}
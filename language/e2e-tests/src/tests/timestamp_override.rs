@@ -0,0 +1,57 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{account::AccountData, common_transactions::peer_to_peer_txn, executor::FakeExecutor};
+use libra_crypto::HashValue;
+use libra_types::{
+    block_metadata::BlockMetadata,
+    on_chain_config::{OnChainConfig, ValidatorSet},
+    transaction::{Transaction, TransactionOutput},
+};
+use libra_vm::{executor_config::ExecutorConfig, LibraVM};
+
+fn execute_block_at(
+    timestamp_usecs: u64,
+    timestamp_override: Option<u64>,
+) -> Vec<TransactionOutput> {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+    let validator_set = ValidatorSet::fetch_config(executor.get_state_view()).unwrap();
+    let block_metadata = BlockMetadata::new(
+        HashValue::zero(),
+        1,
+        timestamp_usecs,
+        vec![],
+        *validator_set.payload()[0].account_address(),
+    );
+    let block = vec![
+        Transaction::BlockMetadata(block_metadata),
+        Transaction::UserTransaction(peer_to_peer_txn(
+            sender.account(),
+            receiver.account(),
+            10,
+            1_000,
+        )),
+    ];
+    let outputs = LibraVM::new_with_config(ExecutorConfig {
+        timestamp_override,
+        ..ExecutorConfig::default()
+    })
+    .execute_transaction_block(block, executor.get_state_view())
+    .unwrap();
+    assert!(outputs.iter().all(|output| !output.status().is_discarded()));
+    outputs
+}
+
+#[test]
+fn pinned_timestamp_gives_identical_outputs() {
+    // The time is part of the outputs.
+    assert_ne!(execute_block_at(1, None), execute_block_at(2, None));
+
+    let pinned = execute_block_at(1, Some(1_000));
+    assert_eq!(execute_block_at(2, Some(1_000)), pinned);
+    assert_eq!(execute_block_at(1_000, None), pinned);
+}
@@ -1,18 +1,30 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{account::Account, executor::FakeExecutor, gas_costs};
+use crate::{
+    account::{self, Account, AccountData},
+    common_transactions::{empty_txn, script_txn, script_txn_impl},
+    executor::FakeExecutor,
+    gas_costs,
+};
 use compiled_stdlib::transaction_scripts::StdlibScript;
 use libra_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, Uniform};
 use libra_types::{
-    account_config::{self, BurnEvent, COIN1_NAME},
-    transaction::{authenticator::AuthenticationKey, TransactionArgument},
+    account_config::{self, BurnEvent, COIN1_NAME, LBR_NAME},
+    transaction::{authenticator::AuthenticationKey, SignedTransaction, TransactionArgument},
     vm_status::StatusCode,
 };
+use libra_vm::{
+    executor_config::ExecutorConfig,
+    fees::{gas_refund, transaction_fee},
+    LibraVM,
+};
 use move_core_types::{
+    gas_schedule::{AbstractMemorySize, GasAlgebra, GasUnits},
     identifier::Identifier,
     language_storage::{StructTag, TypeTag},
 };
+use move_vm_types::gas_schedule::CostStrategy;
 use std::convert::TryFrom;
 use transaction_builder::{
     encode_burn_txn_fees_script, encode_create_testing_account_script, encode_testnet_mint_script,
@@ -90,3 +102,194 @@ fn burn_txn_fees() {
         .iter()
         .for_each(|event| assert_eq!(event.amount(), gas_used));
 }
+
+#[test]
+fn intrinsic_gas_params_match_charged_gas() {
+    let executor = FakeExecutor::from_genesis_file();
+    let mut vm = LibraVM::new();
+    vm.load_configs(executor.get_state_view());
+    let params = vm.intrinsic_gas_params().unwrap();
+    let gas_schedule = vm.internals().gas_schedule().unwrap();
+
+    let max_gas = params.intrinsic_gas(params.max_transaction_size_in_bytes);
+    for size in &[
+        0,
+        1,
+        params.large_transaction_cutoff,
+        params.large_transaction_cutoff + 1,
+        params.large_transaction_cutoff + 100,
+        params.max_transaction_size_in_bytes,
+    ] {
+        let mut cost_strategy = CostStrategy::transaction(gas_schedule, GasUnits::new(max_gas));
+        cost_strategy
+            .charge_intrinsic_gas(AbstractMemorySize::new(*size))
+            .unwrap();
+        let charged = max_gas - cost_strategy.remaining_gas().get();
+        assert_eq!(params.intrinsic_gas(*size), charged, "size {}", size);
+    }
+}
+
+#[test]
+fn max_gas_per_transaction_bounds_the_budget() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    executor.add_account_data(&sender);
+    let mut vm = LibraVM::new();
+    vm.load_configs(executor.get_state_view());
+    let max_gas = vm.max_gas_per_transaction().unwrap();
+
+    let txn_with_max_gas = |max_gas_amount| {
+        let program = "
+            main() {
+                return;
+            }
+        ";
+        script_txn(sender.account(), 10, program, max_gas_amount)
+    };
+    assert_eq!(
+        vm.validate_transaction(&txn_with_max_gas(max_gas), executor.get_state_view()),
+        None
+    );
+    assert_eq!(
+        vm.validate_transaction(&txn_with_max_gas(max_gas + 1), executor.get_state_view())
+            .unwrap()
+            .major_status,
+        StatusCode::MAX_GAS_UNITS_EXCEEDS_MAX_GAS_UNITS_BOUND
+    );
+}
+
+fn script_txn_with_budget(sender: &AccountData, seq_num: u64, program: &str) -> SignedTransaction {
+    script_txn_impl(
+        sender.account(),
+        seq_num,
+        program,
+        vec![],
+        vec![],
+        100_000,
+        2,
+    )
+}
+
+#[test]
+fn gas_refund_of_unused_budget() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    executor.add_account_data(&sender);
+
+    let txn = script_txn_with_budget(
+        &sender,
+        10,
+        "
+        main() {
+            return;
+        }
+        ",
+    );
+    let output = executor.execute_transaction(txn.clone());
+    assert!(!output.status().is_discarded());
+    assert!(output.gas_used() < txn.max_gas_amount());
+    assert_eq!(transaction_fee(&output, &txn), output.gas_used() * 2);
+    assert_eq!(
+        gas_refund(&output, &txn),
+        (txn.max_gas_amount() - output.gas_used()) * 2
+    );
+    // Only the fee is taken from the sender.
+    executor.apply_write_set(output.write_set());
+    let balance = executor
+        .read_balance_resource(sender.account(), account::lbr_currency_code())
+        .unwrap();
+    assert_eq!(balance.coin(), 1_000_000 - transaction_fee(&output, &txn));
+
+    // A transaction running out of gas is charged its whole budget.
+    let txn = script_txn_with_budget(
+        &sender,
+        11,
+        "
+        main() {
+            loop {}
+            return;
+        }
+        ",
+    );
+    let output = executor.execute_transaction(txn.clone());
+    assert_eq!(
+        output.status().vm_status().major_status,
+        StatusCode::OUT_OF_GAS
+    );
+    assert_eq!(gas_refund(&output, &txn), 0);
+
+    // Nothing is charged for a discarded transaction.
+    let txn = script_txn_with_budget(&sender, 5, "main() { return; }");
+    let output = executor.execute_transaction(txn.clone());
+    assert!(output.status().is_discarded());
+    assert_eq!(transaction_fee(&output, &txn), 0);
+    assert_eq!(gas_refund(&output, &txn), 200_000);
+}
+
+#[test]
+fn first_transaction_gas_grace() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 0);
+    executor.add_account_data(&sender);
+    let balance = |executor: &FakeExecutor| {
+        executor
+            .read_balance_resource(sender.account(), account::lbr_currency_code())
+            .unwrap()
+            .coin()
+    };
+    let txn = |seq_num| {
+        empty_txn(
+            sender.account(),
+            seq_num,
+            gas_costs::TXN_RESERVED,
+            1,
+            LBR_NAME.to_owned(),
+        )
+    };
+
+    let mut vm = LibraVM::new_with_config(ExecutorConfig {
+        first_transaction_gas_grace: true,
+        ..ExecutorConfig::default()
+    });
+    // The simulations waive the fee too, in whatever currency it is paid.
+    let simulation = vm
+        .simulate_transaction_with_foreign_fee(
+            txn(0),
+            executor.get_state_view(),
+            &account::lbr_currency_code(),
+            1 << 32,
+        )
+        .unwrap();
+    assert!(simulation.output.gas_used() > 0);
+    assert_eq!((simulation.fee, simulation.foreign_fee), (0, 0));
+
+    let output = vm
+        .execute_single_transaction(txn(0), executor.get_state_view())
+        .unwrap();
+    assert_eq!(
+        output.status().vm_status().major_status,
+        StatusCode::EXECUTED
+    );
+    // The gas used is reported, but not charged.
+    assert!(output.gas_used() > 0);
+    executor.apply_write_set(output.write_set());
+    assert_eq!(balance(&executor), 1_000_000);
+    assert_eq!(
+        executor
+            .read_account_resource(sender.account())
+            .unwrap()
+            .sequence_number(),
+        1
+    );
+
+    // The next transactions are charged.
+    let output = vm
+        .execute_single_transaction(txn(1), executor.get_state_view())
+        .unwrap();
+    assert_eq!(
+        output.status().vm_status().major_status,
+        StatusCode::EXECUTED
+    );
+    executor.apply_write_set(output.write_set());
+    assert_eq!(balance(&executor), 1_000_000 - output.gas_used());
+}
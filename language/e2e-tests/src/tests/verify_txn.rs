@@ -4,6 +4,7 @@
 use crate::{
     account::{Account, AccountData},
     assert_prologue_disparity, assert_prologue_parity, assert_status_eq,
+    common_transactions::peer_to_peer_txn,
     compile::compile_module_with_address,
     executor::FakeExecutor,
     transaction_status_eq,
@@ -21,6 +22,7 @@ use libra_types::{
     },
     vm_status::{StatusCode, StatusType, VMStatus},
 };
+use libra_vm::{executor_config::ExecutorConfig, LibraVM};
 use move_core_types::gas_schedule::{GasAlgebra, GasConstants};
 use transaction_builder::encode_peer_to_peer_with_metadata_script;
 
@@ -392,6 +394,32 @@ pub fn test_whitelist() {
     );
 }
 
+#[test]
+fn is_script_allowed_checks_the_allowlist() {
+    let executor = FakeExecutor::whitelist_genesis();
+    let allowed = encode_peer_to_peer_with_metadata_script(
+        lbr_type_tag(),
+        account_config::libra_root_address(),
+        100,
+        vec![],
+        vec![],
+    );
+    let disallowed = Script::new(vec![], vec![], vec![]);
+
+    let mut vm = LibraVM::new();
+    assert_eq!(
+        vm.is_script_allowed(&allowed).unwrap_err().major_status,
+        StatusCode::VM_STARTUP_FAILURE
+    );
+    vm.load_configs(executor.get_state_view());
+    assert_eq!(vm.is_script_allowed(&allowed), Ok(true));
+    assert_eq!(vm.is_script_allowed(&disallowed), Ok(false));
+
+    let executor = FakeExecutor::from_genesis_with_options(VMPublishingOption::CustomScripts);
+    vm.load_configs(executor.get_state_view());
+    assert_eq!(vm.is_script_allowed(&disallowed), Ok(true));
+}
+
 #[test]
 pub fn test_arbitrary_script_execution() {
     // create a FakeExecutor with a genesis from file
@@ -664,3 +692,62 @@ fn test_dependency_fails_verification() {
         _ => panic!("Failed to find missing dependency in bytecode verifier"),
     }
 }
+
+#[test]
+fn configured_max_transaction_size() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+    let mut vm = LibraVM::new_with_config(ExecutorConfig {
+        max_transaction_size_in_bytes: Some(1_024),
+        ..ExecutorConfig::default()
+    });
+
+    let txn = peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000);
+    assert!(txn.raw_txn_bytes_len() <= 1_024);
+    assert_eq!(
+        vm.validate_transaction(&txn, executor.get_state_view()),
+        None
+    );
+
+    // Well below the bound of the gas schedule, but above the configured one.
+    let program = encode_peer_to_peer_with_metadata_script(
+        lbr_type_tag(),
+        *receiver.address(),
+        1_000,
+        vec![0; 2_048],
+        vec![],
+    );
+    let oversized_txn = sender.account().create_signed_txn_impl(
+        *sender.address(),
+        TransactionPayload::Script(program),
+        10,
+        400_000,
+        0,
+        LBR_NAME.to_owned(),
+    );
+    assert!(transaction_status_eq(
+        executor.execute_transaction(oversized_txn.clone()).status(),
+        &TransactionStatus::Keep(VMStatus::executed())
+    ));
+    assert_eq!(
+        vm.validate_transaction(&oversized_txn, executor.get_state_view())
+            .unwrap()
+            .major_status,
+        StatusCode::EXCEEDED_MAX_TRANSACTION_SIZE
+    );
+    let output = vm
+        .execute_single_transaction(oversized_txn, executor.get_state_view())
+        .unwrap();
+    assert!(transaction_status_eq(
+        output.status(),
+        &TransactionStatus::Discard(VMStatus::new(
+            StatusCode::EXCEEDED_MAX_TRANSACTION_SIZE,
+            None,
+            None
+        ))
+    ));
+    assert_eq!(output.gas_used(), 0);
+}
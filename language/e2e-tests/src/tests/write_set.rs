@@ -7,17 +7,19 @@ use crate::{
     executor::FakeExecutor,
 };
 use libra_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, Uniform};
+use libra_state_view::StateView;
 use libra_types::{
     access_path::AccessPath,
     account_config::{lbr_type_tag, CORE_CODE_ADDRESS, LBR_NAME},
     contract_event::ContractEvent,
     on_chain_config::new_epoch_event_key,
     transaction::{
-        authenticator::AuthenticationKey, ChangeSet, TransactionPayload, TransactionStatus,
+        authenticator::AuthenticationKey, ChangeSet, Script, TransactionPayload, TransactionStatus,
     },
     vm_status::{StatusCode, VMStatus},
     write_set::{WriteOp, WriteSetMut},
 };
+use libra_vm::{data_cache::StateViewCache, executor_config::ExecutorConfig, LibraVM};
 use move_core_types::{
     identifier::Identifier,
     language_storage::{ResourceKey, StructTag},
@@ -78,6 +80,8 @@ fn verify_and_execute_writeset() {
         output.status(),
         &TransactionStatus::Keep(VMStatus::executed())
     );
+    // The write set epilogue starts a new epoch.
+    assert!(LibraVM::is_reconfiguration(&output));
     assert!(executor
         .verify_transaction(writeset_txn.clone())
         .status()
@@ -133,6 +137,164 @@ fn verify_and_execute_writeset() {
     assert_eq!(err.sub_status, Some(11));
 }
 
+#[test]
+fn execute_single_writeset_transaction() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let genesis_account = Account::new_libra_root();
+    executor.new_block();
+
+    let new_account_data = AccountData::new(0, 10);
+    let writeset_txn = genesis_account.create_signed_txn_impl(
+        *genesis_account.address(),
+        TransactionPayload::WriteSet(ChangeSet::new(new_account_data.to_writeset(), vec![])),
+        1,
+        100_000,
+        0,
+        LBR_NAME.to_owned(),
+    );
+
+    // Outside of a block, the write set goes through the same prologue and epilogue.
+    let output = LibraVM::new()
+        .execute_single_transaction(writeset_txn.clone(), executor.get_state_view())
+        .unwrap();
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Keep(VMStatus::executed())
+    );
+    assert!(LibraVM::is_reconfiguration(&output));
+    assert_eq!(output, executor.execute_transaction(writeset_txn));
+
+    // The write set prologue still checks the sequence number.
+    let stale_txn = genesis_account.create_signed_txn_impl(
+        *genesis_account.address(),
+        TransactionPayload::WriteSet(ChangeSet::new(new_account_data.to_writeset(), vec![])),
+        0,
+        100_000,
+        0,
+        LBR_NAME.to_owned(),
+    );
+    let output = LibraVM::new()
+        .execute_single_transaction(stale_txn, executor.get_state_view())
+        .unwrap();
+    assert_eq!(
+        output.status().vm_status().major_status,
+        StatusCode::SEQUENCE_NUMBER_TOO_OLD
+    );
+}
+
+#[test]
+fn record_write_set_origins() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let genesis_account = Account::new_libra_root();
+    executor.new_block();
+
+    let write_set = AccountData::new(0, 10).to_writeset();
+    let writeset_txn = genesis_account.create_signed_txn_impl(
+        *genesis_account.address(),
+        TransactionPayload::WriteSet(ChangeSet::new(write_set.clone(), vec![])),
+        1,
+        100_000,
+        0,
+        LBR_NAME.to_owned(),
+    );
+
+    let mut vm = LibraVM::new();
+    vm.execute_single_transaction(writeset_txn.clone(), executor.get_state_view())
+        .unwrap();
+    assert!(vm.take_write_set_origins().is_empty());
+
+    let mut vm = LibraVM::new_with_config(ExecutorConfig {
+        record_write_set_origins: true,
+        ..ExecutorConfig::default()
+    });
+    let output = vm
+        .execute_single_transaction(writeset_txn.clone(), executor.get_state_view())
+        .unwrap();
+    assert_eq!(output, executor.execute_transaction(writeset_txn));
+    let origins = vm.take_write_set_origins();
+    assert_eq!(origins.len(), 1);
+    let origins = &origins[0];
+    assert_eq!(&origins.sender, genesis_account.address());
+    assert_eq!(origins.sequence_number, 1);
+    assert_eq!(origins.payload, write_set);
+    // The framework writes include the bumped sequence number of the sender.
+    assert!(!origins.epilogue.is_empty());
+    assert!(origins
+        .epilogue
+        .iter()
+        .chain(origins.payload.iter())
+        .eq(output.write_set().iter()));
+    assert!(vm.take_write_set_origins().is_empty());
+
+    // Nothing is recorded for a discarded write set transaction.
+    let stale_txn = genesis_account.create_signed_txn_impl(
+        *genesis_account.address(),
+        TransactionPayload::WriteSet(ChangeSet::new(write_set, vec![])),
+        0,
+        100_000,
+        0,
+        LBR_NAME.to_owned(),
+    );
+    let output = vm
+        .execute_single_transaction(stale_txn, executor.get_state_view())
+        .unwrap();
+    assert!(output.status().is_discarded());
+    assert!(vm.take_write_set_origins().is_empty());
+}
+
+#[test]
+fn process_genesis_write_set_without_signature() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let libra_root = Account::new_libra_root();
+    executor.new_block();
+    let libra_root_sequence_number = executor
+        .read_account_resource(&libra_root)
+        .unwrap()
+        .sequence_number();
+
+    let new_account_data = AccountData::new(0, 10);
+    let write_set = new_account_data.to_writeset();
+    let mut vm = LibraVM::new();
+    let script = Script::new(vec![], vec![], vec![]);
+    assert_eq!(
+        vm.is_script_allowed(&script).unwrap_err().major_status,
+        StatusCode::VM_STARTUP_FAILURE
+    );
+    let mut cache = StateViewCache::new(executor.get_state_view());
+    let output = vm
+        .process_genesis_write_set(&mut cache, ChangeSet::new(write_set.clone(), vec![]))
+        .unwrap();
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Keep(VMStatus::executed())
+    );
+    // The write set epilogue still runs, and the configs are loaded.
+    assert!(LibraVM::is_reconfiguration(&output));
+    assert!(vm.is_script_allowed(&script).is_ok());
+    for (access_path, write_op) in write_set.iter() {
+        assert!(output
+            .write_set()
+            .iter()
+            .any(|(ap, op)| ap == access_path && op == write_op));
+        match write_op {
+            WriteOp::Value(blob) => {
+                assert_eq!(cache.get(access_path).unwrap().as_ref(), Some(blob))
+            }
+            WriteOp::Deletion => assert_eq!(cache.get(access_path).unwrap(), None),
+        }
+    }
+
+    // No sequence number is bumped.
+    executor.apply_write_set(output.write_set());
+    assert_eq!(
+        executor
+            .read_account_resource(&libra_root)
+            .unwrap()
+            .sequence_number(),
+        libra_root_sequence_number
+    );
+}
+
 #[test]
 fn bad_writesets() {
     // create a FakeExecutor with a genesis from file
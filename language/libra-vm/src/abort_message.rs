@@ -0,0 +1,17 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Human readable messages for the abort codes of Move modules.
+
+use libra_state_view::StateView;
+use move_core_types::language_storage::ModuleId;
+
+/// Looks up the message a module gives to one of its abort codes, e.g. in an error description
+/// resource the module publishes. Set with `LibraVM::set_abort_message_resolver`.
+///
+/// The message ends up in the status of the aborted transaction, which is part of its output:
+/// every validator must use the same resolver, and it must only depend on `state`.
+pub trait AbortMessageResolver: Send + Sync {
+    /// Returns the message of `abort_code` in `module`, or `None` if the module has none.
+    fn resolve(&self, module: &ModuleId, abort_code: u64, state: &dyn StateView) -> Option<String>;
+}
@@ -0,0 +1,70 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Description of where the executor finds the data of an account.
+//!
+//! The sequence number and balance checks themselves are done by the Move prologue and epilogue.
+//! This layout is what the executor uses when it reads account data directly: to answer account
+//! queries and to predict the reads of a transaction. Chains shipping a customized account module
+//! can describe its resources here instead of patching the VM.
+
+use libra_types::account_config::{AccountResource, BalanceResource};
+use move_core_types::{
+    language_storage::{StructTag, TypeTag},
+    move_resource::MoveResource,
+};
+use std::fmt;
+
+/// The resources holding the data of an account, and how to decode the fields the executor reads.
+/// Decoders return `None` if the blob is not a valid value of the resource.
+#[derive(Clone)]
+pub struct AccountLayout {
+    /// The resource holding the sequence number and the authentication key.
+    pub account_resource: StructTag,
+    /// The resource holding the balance in the currency with the given type.
+    pub balance_resource: fn(TypeTag) -> StructTag,
+    pub sequence_number: fn(&[u8]) -> Option<u64>,
+    pub authentication_key: fn(&[u8]) -> Option<Vec<u8>>,
+    pub balance: fn(&[u8]) -> Option<u64>,
+}
+
+impl AccountLayout {
+    /// The layout of the account module shipped with the Libra framework.
+    pub fn standard() -> Self {
+        Self {
+            account_resource: AccountResource::struct_tag(),
+            balance_resource: BalanceResource::struct_tag_for_currency,
+            sequence_number: |blob| {
+                lcs::from_bytes::<AccountResource>(blob)
+                    .ok()
+                    .map(|account| account.sequence_number())
+            },
+            authentication_key: |blob| {
+                lcs::from_bytes::<AccountResource>(blob)
+                    .ok()
+                    .map(|account| account.authentication_key().to_vec())
+            },
+            balance: |blob| {
+                lcs::from_bytes::<BalanceResource>(blob)
+                    .ok()
+                    .map(|balance| balance.coin())
+            },
+        }
+    }
+}
+
+// Function pointers taking a reference have no `Debug` implementation, so only the account
+// resource is printed.
+impl fmt::Debug for AccountLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AccountLayout")
+            .field("account_resource", &self.account_resource)
+            .finish()
+    }
+}
+
+impl Default for AccountLayout {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
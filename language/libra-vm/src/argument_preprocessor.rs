@@ -0,0 +1,19 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Rewriting of the arguments of scripts before they run.
+
+use libra_types::transaction::TransactionArgument;
+
+/// Transforms the arguments of every script before they are converted to Move values, e.g. to
+/// expand a placeholder into a concrete address for front ends that don't encode arguments
+/// canonically. Set with `LibraVM::set_argument_preprocessor`; without one, scripts get their
+/// arguments as signed.
+///
+/// The script is verified against, executed and metered with the returned arguments. The
+/// intrinsic gas is still charged on the size of the signed transaction. The returned arguments
+/// determine the output: every validator must use the same preprocessor.
+pub trait ArgumentPreprocessor: Send + Sync {
+    /// Returns the arguments to run the script with in place of `args`.
+    fn preprocess(&self, args: &[TransactionArgument]) -> Vec<TransactionArgument>;
+}
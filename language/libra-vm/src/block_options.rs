@@ -0,0 +1,151 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The variants of `LibraVM::execute_transaction_block` that change how the transactions of a
+//! block are executed, each one setting some `BlockOptions`.
+
+use crate::{conflict_tracker::ConflictResolver, execution_time::BlockDeadline, LibraVM};
+use libra_state_view::StateView;
+use libra_types::{
+    transaction::{Transaction, TransactionOutput, TransactionStatus},
+    vm_status::VMStatus,
+};
+use std::time::Instant;
+
+/// Per call options of a block execution.
+#[derive(Default)]
+pub(crate) struct BlockOptions<'a> {
+    pub(crate) deadline: Option<BlockDeadline>,
+    /// Execute the user transactions speculatively in parallel, reading from this view, which
+    /// must be the one the block is executed on.
+    pub(crate) parallel_view: Option<&'a (dyn StateView + Sync)>,
+    /// Order of execution of the runs of independent conflicting transactions, in block order by
+    /// default.
+    pub(crate) conflict_resolver: Option<&'a dyn ConflictResolver>,
+    /// Once the user transactions used this much gas, the remaining ones are retried.
+    pub(crate) block_gas_limit: Option<u64>,
+    /// Gas used by the user transactions executed so far.
+    pub(crate) block_gas_used: u64,
+    /// Fail the block with the status of the first discarded transaction. Only used for
+    /// sequential execution, so the first discard found is the first one of the block.
+    pub(crate) strict: bool,
+    /// Record a failing block prologue as a discarded output instead of failing the block.
+    pub(crate) lenient_block_prologue: bool,
+}
+
+impl<'a> BlockOptions<'a> {
+    pub(crate) fn gas_limit_reached(&self) -> bool {
+        self.block_gas_limit
+            .map_or(false, |limit| self.block_gas_used >= limit)
+    }
+
+    pub(crate) fn check_discard(&self, output: &TransactionOutput) -> Result<(), VMStatus> {
+        match output.status() {
+            TransactionStatus::Discard(status) if self.strict => Err(status.clone()),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl LibraVM {
+    /// Like `execute_transaction_block`, but gives up on the transactions that are not expected
+    /// to complete before `deadline`. A transaction is started only if the time left covers its
+    /// expected duration, based on the execution times observed so far (see
+    /// `estimate_block_duration`). The first transaction that can't be started and all the
+    /// following ones get a `Retry` output. Which transactions are retried depends on timing, so
+    /// this must not be used where outputs have to be deterministic.
+    pub fn execute_block_with_deadline(
+        &mut self,
+        transactions: Vec<Transaction>,
+        state_view: &dyn StateView,
+        deadline: Instant,
+    ) -> Result<Vec<TransactionOutput>, VMStatus> {
+        self.execute_block_with_options(
+            transactions,
+            state_view,
+            BlockOptions {
+                deadline: Some(BlockDeadline::new(deadline)),
+                ..BlockOptions::default()
+            },
+        )
+    }
+
+    /// Like `execute_transaction_block`, but fails with the status of the first discarded
+    /// transaction instead of returning a discard output for it, e.g. for genesis or migration
+    /// pipelines where a discard is a bug. The transactions after it are not executed. Blocks
+    /// executed for consensus must keep their discarded transactions, so this must not be used
+    /// there.
+    pub fn execute_block_strict(
+        &mut self,
+        transactions: Vec<Transaction>,
+        state_view: &dyn StateView,
+    ) -> Result<Vec<TransactionOutput>, VMStatus> {
+        self.execute_block_with_options(
+            transactions,
+            state_view,
+            BlockOptions {
+                strict: true,
+                ..BlockOptions::default()
+            },
+        )
+    }
+
+    /// Like `execute_transaction_block`, but a block prologue that fails gets a discarded output
+    /// with its status instead of failing the whole block, and the transactions after it are
+    /// still executed, without its writes. Meant for debugging a bad block: a failing prologue
+    /// must fail the block for consensus, so this must not be used there.
+    pub fn execute_block_with_lenient_prologue(
+        &mut self,
+        transactions: Vec<Transaction>,
+        state_view: &dyn StateView,
+    ) -> Result<Vec<TransactionOutput>, VMStatus> {
+        self.execute_block_with_options(
+            transactions,
+            state_view,
+            BlockOptions {
+                lenient_block_prologue: true,
+                ..BlockOptions::default()
+            },
+        )
+    }
+
+    /// Like `execute_transaction_block`, but the user transactions between two block prologues
+    /// or write sets are executed speculatively in parallel. The outputs are then committed in
+    /// block order, and a transaction that read a value written by an earlier transaction of the
+    /// chunk is executed again before the next one is committed.
+    pub fn execute_block_parallel(
+        &mut self,
+        transactions: Vec<Transaction>,
+        state_view: &(dyn StateView + Sync),
+    ) -> Result<Vec<TransactionOutput>, VMStatus> {
+        self.execute_block_with_options(
+            transactions,
+            state_view,
+            BlockOptions {
+                parallel_view: Some(state_view),
+                ..BlockOptions::default()
+            },
+        )
+    }
+
+    /// Like `execute_block_parallel`, with each run of consecutive conflicting transactions
+    /// executed again in the order returned by `resolver`. The order is only followed if the
+    /// transactions of the run are independent of each other, so the outputs, returned in block
+    /// order, are always the ones of `execute_block_parallel`.
+    pub fn execute_block_parallel_with_resolver(
+        &mut self,
+        transactions: Vec<Transaction>,
+        state_view: &(dyn StateView + Sync),
+        resolver: &dyn ConflictResolver,
+    ) -> Result<Vec<TransactionOutput>, VMStatus> {
+        self.execute_block_with_options(
+            transactions,
+            state_view,
+            BlockOptions {
+                parallel_view: Some(state_view),
+                conflict_resolver: Some(resolver),
+                ..BlockOptions::default()
+            },
+        )
+    }
+}
@@ -0,0 +1,99 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A description of how the executor is going to split and run a block, computed without
+//! executing anything.
+
+use crate::libra_transaction_executor::TransactionBlock;
+use libra_crypto::HashValue;
+use libra_types::{account_address::AccountAddress, transaction::SignedTransaction};
+use std::collections::{BTreeMap, HashSet};
+
+/// One chunk of a block, as produced by `chunk_block_transactions`. Every step other than
+/// `UserTransactions` is a serialization point: it has to run on its own, after everything before
+/// it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BlockPlanStep {
+    BlockPrologue {
+        block_id: HashValue,
+    },
+    WaypointWriteSet,
+    WriteSet {
+        sender: AccountAddress,
+    },
+    UserTransactions {
+        transaction_count: usize,
+        /// Number of transactions sent by each sender in the chunk.
+        senders: BTreeMap<AccountAddress, usize>,
+    },
+}
+
+impl BlockPlanStep {
+    pub fn transaction_count(&self) -> usize {
+        match self {
+            BlockPlanStep::UserTransactions {
+                transaction_count, ..
+            } => *transaction_count,
+            BlockPlanStep::BlockPrologue { .. }
+            | BlockPlanStep::WaypointWriteSet
+            | BlockPlanStep::WriteSet { .. } => 1,
+        }
+    }
+}
+
+impl From<&TransactionBlock> for BlockPlanStep {
+    fn from(block: &TransactionBlock) -> Self {
+        match block {
+            TransactionBlock::BlockPrologue(block_metadata) => BlockPlanStep::BlockPrologue {
+                block_id: block_metadata.id(),
+            },
+            TransactionBlock::WaypointWriteSet(_) => BlockPlanStep::WaypointWriteSet,
+            TransactionBlock::WriteSet(txn) => BlockPlanStep::WriteSet {
+                sender: txn.sender(),
+            },
+            TransactionBlock::UserTransaction(txns) => {
+                let mut senders = BTreeMap::new();
+                for txn in txns {
+                    *senders.entry(txn.sender()).or_insert(0) += 1;
+                }
+                BlockPlanStep::UserTransactions {
+                    transaction_count: txns.len(),
+                    senders,
+                }
+            }
+        }
+    }
+}
+
+/// The steps the executor goes through for a block, in order.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BlockPlan {
+    pub steps: Vec<BlockPlanStep>,
+}
+
+impl BlockPlan {
+    pub fn new(blocks: &[TransactionBlock]) -> Self {
+        Self {
+            steps: blocks.iter().map(BlockPlanStep::from).collect(),
+        }
+    }
+
+    /// Total number of transactions in the block.
+    pub fn transaction_count(&self) -> usize {
+        self.steps
+            .iter()
+            .map(BlockPlanStep::transaction_count)
+            .sum()
+    }
+}
+
+/// Checks that no two of `txns` have the same sender, for consensus rules allowing at most one
+/// transaction per sender in a block. Returns the sender of the first transaction whose sender
+/// already sent one of the transactions before it.
+pub fn assert_distinct_senders(txns: &[SignedTransaction]) -> Result<(), AccountAddress> {
+    let mut senders = HashSet::new();
+    match txns.iter().find(|txn| !senders.insert(txn.sender())) {
+        Some(txn) => Err(txn.sender()),
+        None => Ok(()),
+    }
+}
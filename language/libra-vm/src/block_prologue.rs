@@ -0,0 +1,53 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Construction of the arguments the block prologue is called with.
+//!
+//! The executor calls `LibraBlock::block_prologue` for every `BlockMetadata` of a block. Chains
+//! shipping a framework whose prologue takes other arguments can describe how to build them
+//! here instead of patching the VM.
+
+use libra_types::account_address::AccountAddress;
+use move_vm_types::values::Value;
+
+/// What the block prologue is told about a block.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BlockPrologueInputs {
+    /// The address of the VM, the signer of the prologue.
+    pub vm_address: AccountAddress,
+    pub round: u64,
+    pub timestamp_usecs: u64,
+    pub previous_block_votes: Vec<AccountAddress>,
+    pub proposer: AccountAddress,
+}
+
+/// Builds the arguments of the block prologue from the inputs of a block. Set with
+/// `ExecutorConfig::block_prologue_args`.
+#[derive(Clone, Copy, Debug)]
+pub struct BlockPrologueArgs {
+    pub build: fn(BlockPrologueInputs) -> Vec<Value>,
+}
+
+impl BlockPrologueArgs {
+    /// The arguments of the block prologue of the Libra framework: the VM signer, the round,
+    /// the timestamp, the votes on the previous block and the proposer.
+    pub fn standard() -> Self {
+        Self {
+            build: |inputs| {
+                vec![
+                    Value::transaction_argument_signer_reference(inputs.vm_address),
+                    Value::u64(inputs.round),
+                    Value::u64(inputs.timestamp_usecs),
+                    Value::vector_address(inputs.previous_block_votes),
+                    Value::address(inputs.proposer),
+                ]
+            },
+        }
+    }
+}
+
+impl Default for BlockPrologueArgs {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
@@ -0,0 +1,70 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Aggregated statistics about the outputs of a block, for monitoring.
+
+use libra_types::{
+    transaction::{TransactionOutput, TransactionStatus},
+    vm_status::StatusCode,
+};
+use std::collections::HashMap;
+
+/// Statistics about the outputs of the last block executed by a `LibraVM`, built while the block
+/// runs.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BlockSummary {
+    /// Number of outputs in the block.
+    pub transaction_count: usize,
+    /// Number of kept outputs.
+    pub kept_count: usize,
+    /// Number of discarded outputs.
+    pub discarded_count: usize,
+    /// Total gas used by the kept outputs.
+    pub gas_used: u64,
+    /// Index in the block and gas used of the kept output that used the most gas. On a tie, the
+    /// first one is reported.
+    pub max_gas_transaction: Option<(usize, u64)>,
+    /// Number of events emitted by the kept outputs.
+    pub total_events: usize,
+    /// Indices in the block of the transactions discarded because their gas currency code could
+    /// not be resolved to a currency, in block order. They are counted in `discarded_count` too.
+    /// A block where every user transaction ends up here usually means the gas currency resolver
+    /// is misconfigured.
+    pub unresolved_gas_currency: Vec<usize>,
+    /// Number of kept and discarded outputs with each major status, e.g. to spot a burst of
+    /// `SEQUENCE_NUMBER_TOO_OLD` or `OUT_OF_GAS`. Successful transactions are counted under
+    /// `EXECUTED`.
+    pub status_counts: HashMap<StatusCode, usize>,
+}
+
+impl BlockSummary {
+    /// Accounts for `output`, the next output of the block.
+    pub fn record(&mut self, output: &TransactionOutput) {
+        let index = self.transaction_count;
+        self.transaction_count += 1;
+        if let TransactionStatus::Keep(status) | TransactionStatus::Discard(status) =
+            output.status()
+        {
+            *self.status_counts.entry(status.major_status).or_insert(0) += 1;
+        }
+        match output.status() {
+            TransactionStatus::Keep(_) => {
+                self.kept_count += 1;
+                self.total_events += output.events().len();
+                let gas_used = output.gas_used();
+                self.gas_used = self.gas_used.saturating_add(gas_used);
+                match self.max_gas_transaction {
+                    Some((_, max_gas_used)) if max_gas_used >= gas_used => (),
+                    _ => self.max_gas_transaction = Some((index, gas_used)),
+                }
+            }
+            TransactionStatus::Discard(status) => {
+                self.discarded_count += 1;
+                if status.major_status == StatusCode::INVALID_GAS_SPECIFIER {
+                    self.unresolved_gas_currency.push(index);
+                }
+            }
+            TransactionStatus::Retry => (),
+        }
+    }
+}
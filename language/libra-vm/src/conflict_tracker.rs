@@ -0,0 +1,153 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Read/write set tracking for the speculative parallel execution of user transactions.
+//!
+//! The transactions of a chunk are all executed in parallel against a `SnapshotView` of the state
+//! at the start of the chunk, each one recording the paths it read with a `ReadTrackingView`. The
+//! outputs are then committed in block order. The `ConflictTracker` records the write sets
+//! committed since the snapshot was taken: a speculative output whose read set intersects them
+//! may be stale, and the transaction is executed again on top of the outputs committed before it,
+//! so the later speculative outputs are checked against its final writes.
+//!
+//! A `ConflictResolver` may choose another order for a run of consecutive conflicting
+//! transactions. The order is only followed when no transaction of the run reads or writes a path
+//! written by another one, so it gives the same outputs as block order.
+
+use anyhow::Result;
+use libra_state_view::StateView;
+use libra_types::{access_path::AccessPath, write_set::WriteSet};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet},
+};
+
+/// Chooses the order in which a run of consecutive transactions of a chunk whose speculative
+/// outputs conflict with the outputs committed before them are executed again.
+///
+/// The order must only depend on the indices passed in: every validator has to execute the
+/// transactions in the same order to get the same outputs.
+pub trait ConflictResolver {
+    /// Returns the order in which to execute the transactions at `conflicting`, which are the
+    /// indices in the chunk of the conflicting transactions, in ascending order. An order that is
+    /// not a permutation of `conflicting` is ignored.
+    fn resolution_order(&self, conflicting: &[usize]) -> Vec<usize>;
+}
+
+/// Executes the conflicting transactions in block order.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AscendingOrder;
+
+impl ConflictResolver for AscendingOrder {
+    fn resolution_order(&self, conflicting: &[usize]) -> Vec<usize> {
+        conflicting.to_vec()
+    }
+}
+
+/// The state of a block at some point: the writes cached by a `StateViewCache` on top of the
+/// block's `StateView`. Unlike the cache, the snapshot can be shared between threads.
+pub(crate) struct SnapshotView<'a> {
+    base: &'a (dyn StateView + Sync),
+    overlay: &'a BTreeMap<AccessPath, Option<Vec<u8>>>,
+}
+
+impl<'a> SnapshotView<'a> {
+    pub(crate) fn new(
+        base: &'a (dyn StateView + Sync),
+        overlay: &'a BTreeMap<AccessPath, Option<Vec<u8>>>,
+    ) -> Self {
+        Self { base, overlay }
+    }
+}
+
+impl<'a> StateView for SnapshotView<'a> {
+    fn get(&self, access_path: &AccessPath) -> Result<Option<Vec<u8>>> {
+        match self.overlay.get(access_path) {
+            Some(value) => Ok(value.clone()),
+            None => self.base.get(access_path),
+        }
+    }
+
+    fn multi_get(&self, access_paths: &[AccessPath]) -> Result<Vec<Option<Vec<u8>>>> {
+        access_paths.iter().map(|ap| self.get(ap)).collect()
+    }
+
+    fn is_genesis(&self) -> bool {
+        self.base.is_genesis()
+    }
+}
+
+/// Records the paths read through the wrapped view.
+pub(crate) struct ReadTrackingView<'a> {
+    view: &'a dyn StateView,
+    reads: RefCell<BTreeSet<AccessPath>>,
+}
+
+impl<'a> ReadTrackingView<'a> {
+    pub(crate) fn new(view: &'a dyn StateView) -> Self {
+        Self {
+            view,
+            reads: RefCell::new(BTreeSet::new()),
+        }
+    }
+
+    pub(crate) fn into_reads(self) -> BTreeSet<AccessPath> {
+        self.reads.into_inner()
+    }
+}
+
+impl<'a> StateView for ReadTrackingView<'a> {
+    fn get(&self, access_path: &AccessPath) -> Result<Option<Vec<u8>>> {
+        self.reads.borrow_mut().insert(access_path.clone());
+        self.view.get(access_path)
+    }
+
+    fn multi_get(&self, access_paths: &[AccessPath]) -> Result<Vec<Option<Vec<u8>>>> {
+        self.reads.borrow_mut().extend(access_paths.iter().cloned());
+        self.view.multi_get(access_paths)
+    }
+
+    fn is_genesis(&self) -> bool {
+        self.view.is_genesis()
+    }
+}
+
+/// The paths written by the outputs committed since a snapshot was taken.
+#[derive(Debug, Default)]
+pub(crate) struct ConflictTracker {
+    written: BTreeSet<AccessPath>,
+}
+
+impl ConflictTracker {
+    /// Records a write set pushed to the `StateViewCache` the snapshot was taken from.
+    pub(crate) fn record_write_set(&mut self, write_set: &WriteSet) {
+        self.written
+            .extend(write_set.iter().map(|(access_path, _)| access_path.clone()));
+    }
+
+    /// Returns whether a transaction that read `reads` from the snapshot may have seen values
+    /// that have changed since.
+    pub(crate) fn conflicts_with(&self, reads: &BTreeSet<AccessPath>) -> bool {
+        !self.written.is_disjoint(reads)
+    }
+}
+
+/// Returns whether none of the transactions that read `reads[i]` and wrote `writes[i]` on the same
+/// snapshot reads or writes a path written by another one. Each transaction then reads the same
+/// values in any order, so the order they are committed in doesn't change their outputs.
+pub(crate) fn are_independent(
+    reads: &[&BTreeSet<AccessPath>],
+    writes: &[BTreeSet<AccessPath>],
+) -> bool {
+    writes.iter().enumerate().all(|(i, written)| {
+        (0..writes.len())
+            .filter(|j| *j != i)
+            .all(|j| written.is_disjoint(reads[j]) && written.is_disjoint(&writes[j]))
+    })
+}
+
+pub(crate) fn is_permutation_of(order: &[usize], indices: &[usize]) -> bool {
+    let mut order = order.to_vec();
+    order.sort_unstable();
+    order == indices
+}
@@ -2,9 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use libra_metrics::{
-    register_histogram, register_int_counter_vec, register_int_gauge, Histogram, IntCounterVec,
-    IntGauge,
+    register_histogram, register_histogram_vec, register_int_counter_vec, register_int_gauge,
+    Histogram, HistogramVec, IntCounterVec, IntGauge,
 };
+use libra_types::transaction::TransactionPayload;
 use once_cell::sync::Lazy;
 
 /// Count the number of transactions verified, with a "status" label to
@@ -45,6 +46,26 @@ pub static TXN_TOTAL_SECONDS: Lazy<Histogram> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Total time per user transaction, discarded ones included, with a "payload" label for the
+/// payload type of the transaction, see `payload_label`.
+pub static TXN_TOTAL_SECONDS_BY_PAYLOAD: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "libra_vm_txn_total_seconds_by_payload",
+        "Histogram of total time per transaction, by payload type",
+        &["payload"]
+    )
+    .unwrap()
+});
+
+/// The label of `payload` in `TXN_TOTAL_SECONDS_BY_PAYLOAD`.
+pub fn payload_label(payload: &TransactionPayload) -> &'static str {
+    match payload {
+        TransactionPayload::Script(_) => "script",
+        TransactionPayload::Module(_) => "module",
+        TransactionPayload::WriteSet(_) => "write_set",
+    }
+}
+
 pub static TXN_VERIFICATION_SECONDS: Lazy<Histogram> = Lazy::new(|| {
     register_histogram!(
         "libra_vm_txn_verification_seconds",
@@ -85,6 +106,16 @@ pub static TXN_EPILOGUE_SECONDS: Lazy<Histogram> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Number of Move instructions executed by each script.
+#[cfg(feature = "instruction-count")]
+pub static TXN_INSTRUCTIONS_EXECUTED: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        "libra_vm_txn_instructions_executed",
+        "Histogram of instructions executed per script"
+    )
+    .unwrap()
+});
+
 pub static TXN_EXECUTION_GAS_USAGE: Lazy<Histogram> = Lazy::new(|| {
     register_histogram!(
         "libra_vm_txn_execution_gas_usage",
@@ -93,6 +124,26 @@ pub static TXN_EXECUTION_GAS_USAGE: Lazy<Histogram> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Total gas charged to kept user transactions, with a "currency" label for the currency the gas
+/// is paid in.
+pub static TXN_GAS_USED_BY_CURRENCY: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "libra_vm_txn_gas_used_by_currency",
+        "Gas charged to kept transactions, by gas currency",
+        &["currency"]
+    )
+    .unwrap()
+});
+
+/// Gas used by the block prologue, only observed with `ExecutorConfig::meter_block_prologue`.
+pub static BLOCK_PROLOGUE_GAS_USAGE: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        "libra_vm_block_prologue_gas_usage",
+        "Histogram for the gas used by the block prologue when it is metered"
+    )
+    .unwrap()
+});
+
 pub static TXN_TOTAL_GAS_USAGE: Lazy<Histogram> = Lazy::new(|| {
     register_histogram!(
         "libra_vm_txn_total_gas_usage",
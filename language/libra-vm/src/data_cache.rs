@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 //! Scratchpad for on chain values during the execution.
 
-use crate::create_access_path;
+use crate::{create_access_path, storage_gas::StorageGasModel};
 use libra_logger::prelude::*;
 use libra_state_view::StateView;
 use libra_types::{
@@ -16,7 +16,11 @@ use move_core_types::{
     language_storage::{ModuleId, TypeTag},
 };
 use move_vm_runtime::data_cache::RemoteCache;
-use std::collections::btree_map::BTreeMap;
+use std::{
+    cell::{Cell, RefCell},
+    collections::btree_map::BTreeMap,
+    sync::Arc,
+};
 use vm::errors::*;
 
 /// A local cache for a given a `StateView`. The cache is private to the Libra layer
@@ -35,8 +39,21 @@ use vm::errors::*;
 pub struct StateViewCache<'a> {
     data_view: &'a dyn StateView,
     data_map: BTreeMap<AccessPath, Option<Vec<u8>>>,
+    storage_gas_model: Option<Arc<dyn StorageGasModel>>,
+    // Gas owed for the resource reads since the last `take_storage_gas`.
+    storage_gas: Cell<u64>,
+    // The values read since the last `take_witness`, if recorded.
+    witness: Option<RefCell<BTreeMap<AccessPath, Vec<u8>>>>,
+    // The entries of `data_map` overwritten since the first checkpoint, oldest first, with
+    // `None` for the paths that had no entry. Only kept once a checkpoint was taken.
+    undo_log: Option<Vec<(AccessPath, Option<Option<Vec<u8>>>)>>,
 }
 
+/// A point `StateViewCache::rollback` can restore the cache to, see
+/// `StateViewCache::checkpoint`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CacheToken(usize);
+
 impl<'a> StateViewCache<'a> {
     /// Create a `StateViewCache` give a `StateView`. Hold updates to the data store and
     /// forward data request to the `StateView` if not in the local cache.
@@ -44,41 +61,134 @@ impl<'a> StateViewCache<'a> {
         StateViewCache {
             data_view,
             data_map: BTreeMap::new(),
+            storage_gas_model: None,
+            storage_gas: Cell::new(0),
+            witness: None,
+            undo_log: None,
+        }
+    }
+
+    /// Returns a token to undo the changes made to the cache from now on with `rollback`, e.g.
+    /// to execute a transaction speculatively and discard its write set. Checkpoints can be
+    /// nested. Once a checkpoint was taken, the cache keeps the previous value of every entry
+    /// it changes.
+    pub fn checkpoint(&mut self) -> CacheToken {
+        CacheToken(self.undo_log.get_or_insert_with(Vec::new).len())
+    }
+
+    /// Restores the values of the cache to what they were when `token` was returned by
+    /// `checkpoint`, including the entries overwritten since. The checkpoints taken after
+    /// `token` are rolled back too and must not be used anymore. The storage gas and witness
+    /// are not affected.
+    pub fn rollback(&mut self, token: CacheToken) {
+        let undo_log = match &mut self.undo_log {
+            Some(undo_log) => undo_log,
+            None => return,
+        };
+        assert!(
+            token.0 <= undo_log.len(),
+            "rollback to a checkpoint that was already rolled back"
+        );
+        for (ap, previous) in undo_log.drain(token.0..).rev() {
+            match previous {
+                Some(value) => self.data_map.insert(ap, value),
+                None => self.data_map.remove(&ap),
+            };
         }
     }
 
-    // Publishes a `WriteSet` computed at the end of a transaction.
-    // The effect is to build a layer in front of the `StateView` which keeps
-    // track of the data as if the changes were applied immediately.
-    pub(crate) fn push_write_set(&mut self, write_set: &WriteSet) {
+    // Sets the entry of `ap`, recording its previous entry if a checkpoint was taken.
+    fn set_entry(&mut self, ap: AccessPath, value: Option<Vec<u8>>) {
+        let previous = self.data_map.insert(ap.clone(), value);
+        if let Some(undo_log) = &mut self.undo_log {
+            undo_log.push((ap, previous));
+        }
+    }
+
+    /// Records the bytes of the values read through this cache, see `take_witness`. Off by
+    /// default, as it keeps a copy of everything read.
+    pub fn record_witness(&mut self) {
+        self.witness = Some(RefCell::new(BTreeMap::new()));
+    }
+
+    /// Returns the access paths and bytes of the values read since the last call, by access
+    /// path, and resets them. Reads of missing values are not included. Always empty unless
+    /// `record_witness` was called.
+    pub fn take_witness(&self) -> Vec<(AccessPath, Vec<u8>)> {
+        self.witness
+            .as_ref()
+            .map(|witness| witness.replace(BTreeMap::new()).into_iter().collect())
+            .unwrap_or_default()
+    }
+
+    /// Prices the resource reads going through this cache with `storage_gas_model`.
+    pub fn set_storage_gas_model(&mut self, storage_gas_model: Arc<dyn StorageGasModel>) {
+        self.storage_gas_model = Some(storage_gas_model);
+    }
+
+    /// Returns the gas owed for the resource reads since the last call, and resets it. Always 0
+    /// if no `StorageGasModel` is set.
+    pub(crate) fn take_storage_gas(&self) -> u64 {
+        self.storage_gas.replace(0)
+    }
+
+    /// Publishes a `WriteSet` computed at the end of a transaction.
+    /// The effect is to build a layer in front of the `StateView` which keeps
+    /// track of the data as if the changes were applied immediately.
+    pub fn push_write_set(&mut self, write_set: &WriteSet) {
         for (ref ap, ref write_op) in write_set.iter() {
             match write_op {
-                WriteOp::Value(blob) => {
-                    self.data_map.insert(ap.clone(), Some(blob.clone()));
-                }
-                WriteOp::Deletion => {
-                    self.data_map.remove(ap);
-                    self.data_map.insert(ap.clone(), None);
-                }
+                WriteOp::Value(blob) => self.set_entry(ap.clone(), Some(blob.clone())),
+                WriteOp::Deletion => self.set_entry(ap.clone(), None),
             }
         }
     }
+
+    /// The values written or loaded so far, which take precedence over the `StateView`.
+    pub(crate) fn overlay(&self) -> &BTreeMap<AccessPath, Option<Vec<u8>>> {
+        &self.data_map
+    }
+
+    /// Loads the values of `access_paths` from the `StateView` in a single `multi_get` and keeps
+    /// them in the local cache, so that later reads do not go to the `StateView`. Paths that are
+    /// already cached are skipped, as the cached value may be the result of a transaction.
+    pub fn prefetch(&mut self, access_paths: &[AccessPath]) -> anyhow::Result<()> {
+        let missing: Vec<AccessPath> = access_paths
+            .iter()
+            .filter(|ap| !self.data_map.contains_key(ap))
+            .cloned()
+            .collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        let values = self.data_view.multi_get(&missing)?;
+        for (ap, value) in missing.into_iter().zip(values) {
+            self.set_entry(ap, value);
+        }
+        Ok(())
+    }
 }
 
 impl<'block> StateView for StateViewCache<'block> {
     // Get some data either through the cache or the `StateView` on a cache miss.
     fn get(&self, access_path: &AccessPath) -> anyhow::Result<Option<Vec<u8>>> {
-        match self.data_map.get(access_path) {
-            Some(opt_data) => Ok(opt_data.clone()),
+        let value = match self.data_map.get(access_path) {
+            Some(opt_data) => opt_data.clone(),
             None => match self.data_view.get(&access_path) {
-                Ok(remote_data) => Ok(remote_data),
+                Ok(remote_data) => remote_data,
                 // TODO: should we forward some error info?
                 Err(e) => {
                     crit!("[VM] Error getting data from storage for {:?}", access_path);
-                    Err(e)
+                    return Err(e);
                 }
             },
+        };
+        if let (Some(witness), Some(bytes)) = (&self.witness, &value) {
+            witness
+                .borrow_mut()
+                .insert(access_path.clone(), bytes.clone());
         }
+        Ok(value)
     }
 
     fn multi_get(&self, _access_paths: &[AccessPath]) -> anyhow::Result<Vec<Option<Vec<u8>>>> {
@@ -100,7 +210,13 @@ impl<'block> RemoteCache for StateViewCache<'block> {
         address: &AccountAddress,
         tag: &TypeTag,
     ) -> PartialVMResult<Option<Vec<u8>>> {
-        RemoteStorage::new(self).get_resource(address, tag)
+        let value = RemoteStorage::new(self).get_resource(address, tag)?;
+        if let Some(model) = &self.storage_gas_model {
+            let cost = model.resource_read_cost(value.as_ref().map_or(0, Vec::len));
+            self.storage_gas
+                .set(self.storage_gas.get().saturating_add(cost));
+        }
+        Ok(value)
     }
 }
 
@@ -0,0 +1,218 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Per-transaction information gathered by the executor that is not part of the
+//! `TransactionOutput`.
+
+use libra_types::{
+    access_path::AccessPath,
+    contract_event::ContractEvent,
+    transaction::{TransactionOutput, TransactionStatus},
+    vm_status::{StatusCode, StatusType},
+};
+use move_core_types::language_storage::ModuleId;
+use move_vm_runtime::call_trace::{TraceEvent, TraceEventKind};
+use std::time::Duration;
+
+/// Side information about the execution of a single transaction. Only collected when
+/// `ExecutorConfig::collect_diagnostics` is set.
+///
+/// Outputs that are not produced by a user transaction (e.g. the block prologue) get a default
+/// value, so that the diagnostics of a block line up with its outputs.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TransactionDiagnostics {
+    /// Gas units left for the script body, i.e. after the prologue ran and the intrinsic gas was
+    /// charged. `None` if the transaction did not get that far or is not a script.
+    pub gas_available_for_script: Option<u64>,
+
+    /// Gas charged for the size of a script transaction. `None` if the transaction is not a script
+    /// or did not get past the prologue.
+    pub intrinsic_gas: Option<u64>,
+
+    /// How the gas used by a script transaction splits between its phases. `None` if the
+    /// transaction is not a script or did not get past the prologue.
+    pub gas_attribution: Option<GasAttribution>,
+
+    /// Gas units left when the script or module transaction failed, e.g. when a script aborted,
+    /// before the failure epilogue ran. `None` if the transaction did not fail after the
+    /// prologue.
+    pub gas_left_at_failure: Option<u64>,
+
+    /// Events emitted by a script before it aborted or ran out of gas. Only captured when
+    /// `ExecutorConfig::keep_failed_transaction_events` is set.
+    pub pre_failure_events: Vec<ContractEvent>,
+
+    /// The modules published by the transaction. Empty unless it is a module transaction that was
+    /// kept and executed successfully.
+    pub published_modules: Vec<ModuleId>,
+
+    /// The calls made by the script, up to the error if it failed. Only recorded when set to
+    /// `Some` before the transaction runs, as done by `LibraVM::execute_script_traced`.
+    pub call_trace: Option<Vec<TraceEvent>>,
+
+    /// The access paths and bytes of the values read by the transaction, by access path. Only
+    /// recorded when `ExecutorConfig::record_state_witness` is set. Modules are only read from
+    /// storage the first time the VM loads them, so they only show up in the witness of the
+    /// first transaction using them.
+    pub state_witness: Vec<(AccessPath, Vec<u8>)>,
+
+    /// Time spent by a script or module transaction in each of its phases.
+    pub phase_times: PhaseTimes,
+
+    /// Number of Move instructions executed by the script, up to the error if it failed, calls
+    /// included. Only recorded with the `instruction-count` feature, for profiling: gas only
+    /// approximates the work of a script.
+    pub instructions_executed: Option<u64>,
+}
+
+/// The time spent in each phase of a script or module transaction. Phases the transaction did
+/// not complete are zero, e.g. the prologue of a transaction discarded by the prologue.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PhaseTimes {
+    /// Time spent in the prologue.
+    pub prologue: Duration,
+    /// Time spent running the script or publishing the module, and checking the transaction.
+    pub execution: Duration,
+    /// Time spent in the epilogue, successful or not, including making the write set.
+    pub epilogue: Duration,
+}
+
+/// The gas used by a script transaction, by phase. The phases add up to the `gas_used` of the
+/// output, unless the failure epilogue fails and the transaction is discarded.
+///
+/// The prologue and epilogue are not metered, so their share only contains what is charged
+/// around them: the intrinsic gas of the transaction is charged once the prologue passed and is
+/// counted as part of it.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct GasAttribution {
+    /// Gas used to admit the transaction.
+    pub prologue: u64,
+    /// Gas used by the script body, including the storage gas it was charged.
+    pub execution: u64,
+    /// Gas used by the epilogue.
+    pub epilogue: u64,
+}
+
+impl GasAttribution {
+    /// The gas used by the whole transaction.
+    pub fn total(&self) -> u64 {
+        self.prologue + self.execution + self.epilogue
+    }
+}
+
+/// The gas used by a script transaction, split between the charge for its size and the rest.
+/// Returned by `LibraVM::execute_script_with_breakdown`; the parts add up to the `gas_used` of the
+/// output.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct GasBreakdown {
+    /// Gas charged for the size of the transaction.
+    pub intrinsic: u64,
+    /// Gas used by the script body, including the storage gas it was charged.
+    pub execution: u64,
+    /// Gas used by the epilogue.
+    pub epilogue: u64,
+}
+
+/// A function on the call stack of a script.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StackFrame {
+    /// The module of the function, `None` for the main function of the script.
+    pub module: Option<ModuleId>,
+    pub function: String,
+}
+
+/// Rebuilds the call stack of a failed script from its call trace, as returned by
+/// `LibraVM::execute_script_traced`: the chain of calls from the main function, first, to the
+/// function the script failed in, e.g. aborted, last. Returns `None` if the trace does not end
+/// with a failure.
+pub fn failure_call_stack(call_trace: &[TraceEvent]) -> Option<Vec<StackFrame>> {
+    let mut stack = vec![];
+    for event in call_trace {
+        match event.kind {
+            TraceEventKind::Enter => stack.push(StackFrame {
+                module: event.module.clone(),
+                function: event.function.clone(),
+            }),
+            TraceEventKind::Exit => {
+                stack.pop();
+            }
+            TraceEventKind::Error(_) => {
+                stack.truncate(event.depth + 1);
+                return Some(stack);
+            }
+        }
+    }
+    None
+}
+
+/// Why a kept transaction failed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FailureReason {
+    /// The transaction ran out of gas.
+    OutOfGas,
+    /// The program aborted with the given code.
+    Aborted(u64),
+    /// Any other failure, e.g. an arithmetic error or a verification failure of a module.
+    ExecutionError,
+}
+
+/// Classifies the failure of a kept transaction, based on its status. Returns `None` if the
+/// transaction executed successfully or was not kept.
+pub fn failure_reason(output: &TransactionOutput) -> Option<FailureReason> {
+    let status = match output.status() {
+        TransactionStatus::Keep(status) => status,
+        TransactionStatus::Discard(_) | TransactionStatus::Retry => return None,
+    };
+    match status.major_status {
+        StatusCode::EXECUTED => None,
+        StatusCode::OUT_OF_GAS => Some(FailureReason::OutOfGas),
+        StatusCode::ABORTED => Some(match status.sub_status {
+            Some(code) => FailureReason::Aborted(code),
+            None => FailureReason::ExecutionError,
+        }),
+        _ => Some(FailureReason::ExecutionError),
+    }
+}
+
+/// Why a module transaction did not publish its module.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PublishingFailure {
+    /// The on-chain publishing option does not let the sender publish modules.
+    PublishingDisabled,
+    /// A module with the same name is already published under the sender.
+    DuplicateModule,
+    /// The module depends on a module that is not published.
+    MissingDependency,
+    /// The module does not deserialize, with the given status.
+    Malformed(StatusCode),
+    /// The module failed verification with the given status, e.g. because its address is not
+    /// the one of the sender or its bytecode does not verify.
+    VerificationFailed(StatusCode),
+    /// Any other failure, e.g. the module transaction ran out of gas.
+    Other(StatusCode),
+}
+
+/// Classifies why the module transaction that produced `output` did not publish its module,
+/// based on its status, so that tooling can tell the failures that all end up in the status
+/// apart. Returns `None` if the module was published, or if the transaction was retried.
+pub fn publishing_failure(output: &TransactionOutput) -> Option<PublishingFailure> {
+    let status = match output.status() {
+        TransactionStatus::Keep(status) | TransactionStatus::Discard(status) => status,
+        TransactionStatus::Retry => return None,
+    };
+    let code = status.major_status;
+    Some(match code {
+        StatusCode::EXECUTED => return None,
+        StatusCode::INVALID_MODULE_PUBLISHER => PublishingFailure::PublishingDisabled,
+        StatusCode::DUPLICATE_MODULE_NAME => PublishingFailure::DuplicateModule,
+        StatusCode::LINKER_ERROR | StatusCode::MISSING_DEPENDENCY => {
+            PublishingFailure::MissingDependency
+        }
+        StatusCode::CODE_DESERIALIZATION_ERROR => PublishingFailure::Malformed(code),
+        _ => match code.status_type() {
+            StatusType::Deserialization => PublishingFailure::Malformed(code),
+            StatusType::Verification => PublishingFailure::VerificationFailed(code),
+            _ => PublishingFailure::Other(code),
+        },
+    })
+}
@@ -0,0 +1,60 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Block-level check that the events of a block form consistent streams, i.e. that the events
+//! emitted on each handle have consecutive sequence numbers across all the outputs.
+
+use libra_types::{event::EventKey, transaction::TransactionOutput};
+use std::collections::HashMap;
+
+/// What to do with the result of the event consistency check.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EventConsistencyCheck {
+    /// Do not run the check.
+    Off,
+    /// Record the conflicts, see `LibraVM::take_event_conflicts`.
+    Report,
+    /// Fail the whole block with `EVENT_KEY_MISMATCH` if there is any conflict.
+    Fail,
+}
+
+impl Default for EventConsistencyCheck {
+    fn default() -> Self {
+        EventConsistencyCheck::Off
+    }
+}
+
+/// An event whose sequence number does not follow the previous event emitted on the same key
+/// within the block.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EventConflict {
+    pub key: EventKey,
+    /// Index of the output containing the event.
+    pub output_index: usize,
+    pub expected_sequence_number: u64,
+    pub sequence_number: u64,
+}
+
+/// Returns the conflicts between the event streams of `outputs`, in output order. The first event
+/// seen for a key sets the start of its stream.
+pub fn find_event_conflicts(outputs: &[TransactionOutput]) -> Vec<EventConflict> {
+    let mut next_sequence_numbers: HashMap<EventKey, u64> = HashMap::new();
+    let mut conflicts = vec![];
+    for (output_index, output) in outputs.iter().enumerate() {
+        for event in output.events() {
+            let sequence_number = event.sequence_number();
+            if let Some(expected) = next_sequence_numbers.get(event.key()) {
+                if *expected != sequence_number {
+                    conflicts.push(EventConflict {
+                        key: *event.key(),
+                        output_index,
+                        expected_sequence_number: *expected,
+                        sequence_number,
+                    });
+                }
+            }
+            next_sequence_numbers.insert(*event.key(), sequence_number + 1);
+        }
+    }
+    conflicts
+}
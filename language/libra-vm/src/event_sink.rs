@@ -0,0 +1,16 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Delivery of the events of a block to the caller instead of the transaction outputs.
+
+use libra_types::contract_event::ContractEvent;
+
+/// Receives the events of the outputs of a block as they are committed, for callers that can't
+/// hold all the events of a large block in memory. Set with `LibraVM::set_event_sink`.
+///
+/// The events are delivered in emission order: output by output in block order, and in the order
+/// each transaction emitted them. Speculatively executed outputs are only delivered once they are
+/// committed, so no event is delivered twice.
+pub trait EventSink: Send + Sync {
+    fn accept(&mut self, event: ContractEvent);
+}
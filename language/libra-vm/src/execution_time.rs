@@ -0,0 +1,95 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tracking of observed execution times, used to estimate how long a block will take.
+
+use libra_types::transaction::{Transaction, TransactionPayload};
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+/// Weight given to a new sample in the moving average, as `1 / SAMPLE_WEIGHT`.
+const SAMPLE_WEIGHT: u32 = 8;
+
+/// The different kinds of work the executor performs for a transaction.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TransactionKind {
+    BlockPrologue,
+    WaypointWriteSet,
+    Script,
+    Module,
+    WriteSet,
+}
+
+impl TransactionKind {
+    pub fn of_payload(payload: &TransactionPayload) -> Self {
+        match payload {
+            TransactionPayload::Script(_) => TransactionKind::Script,
+            TransactionPayload::Module(_) => TransactionKind::Module,
+            TransactionPayload::WriteSet(_) => TransactionKind::WriteSet,
+        }
+    }
+
+    pub fn of_transaction(txn: &Transaction) -> Self {
+        match txn {
+            Transaction::BlockMetadata(_) => TransactionKind::BlockPrologue,
+            Transaction::WaypointWriteSet(_) => TransactionKind::WaypointWriteSet,
+            Transaction::UserTransaction(txn) => Self::of_payload(txn.payload()),
+        }
+    }
+}
+
+/// An exponential moving average of the execution time of each `TransactionKind`.
+#[derive(Clone, Debug, Default)]
+pub struct ExecutionTimeAverages {
+    averages: HashMap<TransactionKind, Duration>,
+}
+
+impl ExecutionTimeAverages {
+    pub fn record(&mut self, kind: TransactionKind, elapsed: Duration) {
+        let average = self.averages.entry(kind).or_insert(elapsed);
+        *average = *average * (SAMPLE_WEIGHT - 1) / SAMPLE_WEIGHT + elapsed / SAMPLE_WEIGHT;
+    }
+
+    pub fn average(&self, kind: TransactionKind) -> Option<Duration> {
+        self.averages.get(&kind).copied()
+    }
+
+    /// Best-effort estimate of the time needed to execute `transactions`. Kinds that have not
+    /// been observed yet do not contribute to the estimate.
+    pub fn estimate(&self, transactions: &[Transaction]) -> Duration {
+        transactions
+            .iter()
+            .filter_map(|txn| self.average(TransactionKind::of_transaction(txn)))
+            .sum()
+    }
+}
+
+/// A soft deadline for executing a block. Execution is cooperative: a transaction is only started
+/// if it is expected to complete before the deadline, and is never interrupted once started.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct BlockDeadline {
+    deadline: Instant,
+    expired: bool,
+}
+
+impl BlockDeadline {
+    pub(crate) fn new(deadline: Instant) -> Self {
+        Self {
+            deadline,
+            expired: false,
+        }
+    }
+
+    /// Returns whether a transaction expected to take `expected` can be started now. Once a
+    /// transaction is refused, all the following ones are too, as they may depend on it.
+    pub(crate) fn admit(&mut self, expected: Duration) -> bool {
+        if !self.expired {
+            self.expired = Instant::now()
+                .checked_add(expected)
+                .map_or(true, |end| end > self.deadline);
+        }
+        !self.expired
+    }
+}
@@ -0,0 +1,175 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Local knobs for a `LibraVM` instance.
+//!
+//! Most of these options only affect what the executor reports back to its caller, or are meant
+//! for testing and tooling, and each node can pick its own.
+//!
+//! The others change the outputs of the transactions they apply to. The validators of a chain
+//! must all run with the same value for them, or they won't agree on the state:
+//! `allow_user_reconfiguration`, `account_layout`, `block_prologue_args`, `reserved_vm_address`,
+//! `max_module_dependency_depth`, `max_transaction_size_in_bytes`, `canonical_event_order`,
+//! `meter_block_prologue`, `max_resource_writes` and `first_transaction_gas_grace`. The options
+//! that change the outputs but are not meant for validators at all say so.
+
+use crate::{
+    account_layout::AccountLayout, block_prologue::BlockPrologueArgs,
+    event_consistency::EventConsistencyCheck, storage_gas::StorageGasModel,
+};
+use libra_types::{account_address::AccountAddress, account_config};
+use std::sync::Arc;
+#[cfg(feature = "chaos")]
+use std::time::Duration;
+
+/// Default for `ExecutorConfig::max_module_dependency_depth`, well above what the framework uses.
+pub const DEFAULT_MAX_MODULE_DEPENDENCY_DEPTH: usize = 256;
+
+/// Options controlling the behavior of a `LibraVM` instance. The default value matches the
+/// behavior of `VMExecutor::execute_block`.
+#[derive(Clone, Debug)]
+pub struct ExecutorConfig {
+    /// Record a `TransactionDiagnostics` for every output produced by the VM. The diagnostics
+    /// can be retrieved with `LibraVM::take_diagnostics`.
+    pub collect_diagnostics: bool,
+
+    /// Keep the events a script emitted before it failed, for debugging. They are not part of
+    /// the output, which only has the failure epilogue events, and are returned by
+    /// `LibraVM::take_failed_transaction_events`. Also recorded in
+    /// `TransactionDiagnostics::pre_failure_events` along with the other diagnostics.
+    pub keep_failed_transaction_events: bool,
+
+    /// Check, once the whole block ran, that the events emitted on each key have consecutive
+    /// sequence numbers across the outputs. Only applies to `LibraVM::execute_transaction_block`.
+    pub event_consistency_check: EventConsistencyCheck,
+
+    /// Fetch the data predicted by `prefetch::derive_prefetch_hints` in bulk before executing
+    /// the user transactions of a block.
+    pub prefetch: bool,
+
+    /// Let user transactions trigger a reconfiguration. When unset, a script or module
+    /// transaction that emits a new epoch event is discarded with `UNEXPECTED_RECONFIGURATION`;
+    /// write set transactions are not affected.
+    pub allow_user_reconfiguration: bool,
+
+    /// Number of outputs kept by the `OutputCache` used by `LibraVM::execute_transaction_cached`.
+    /// No cache is kept if `None`.
+    pub output_cache_capacity: Option<usize>,
+
+    /// Charge scripts for the resources they read according to this model, on top of the gas
+    /// schedule. Only meant for experimenting with storage pricing, not for validators.
+    pub storage_gas_model: Option<Arc<dyn StorageGasModel>>,
+
+    /// Where the executor reads the data of accounts from. Must match the account module of the
+    /// framework the chain runs.
+    pub account_layout: AccountLayout,
+
+    /// How the arguments of the block prologue are built. Must match the block module of the
+    /// framework the chain runs.
+    pub block_prologue_args: BlockPrologueArgs,
+
+    /// The address the block prologue runs as, which is the address of its signer. Chains that
+    /// fork the framework with another reserved VM address set it to theirs.
+    pub reserved_vm_address: AccountAddress,
+
+    /// Record the user transactions that execute against on-chain configs changed by an earlier
+    /// transaction of their block, see `LibraVM::take_stale_config_transactions`. Meant to catch
+    /// configs not being reloaded; on by default in debug builds.
+    pub stale_config_check: bool,
+
+    /// Reject module transactions whose module has a longer chain of dependencies, counted in
+    /// modules, with `MAX_DEPENDENCY_DEPTH_REACHED`. Bounds the work done by the loader to load
+    /// the module.
+    pub max_module_dependency_depth: usize,
+
+    /// Reject script and module transactions whose raw bytes are longer than this with
+    /// `EXCEEDED_MAX_TRANSACTION_SIZE`, before any gas is charged, on top of the bound set by the
+    /// gas schedule.
+    pub max_transaction_size_in_bytes: Option<u64>,
+
+    /// Record the bytes of the values read by each user transaction in
+    /// `TransactionDiagnostics::state_witness`, e.g. to build a witness of the execution. Needs
+    /// `collect_diagnostics`. Keeps a copy of everything read, so it is off by default.
+    pub record_state_witness: bool,
+
+    /// Put the events of each output in canonical order rather than in the order they were
+    /// emitted: sorted by event key, and by sequence number for each key. Emission order is
+    /// deterministic too, this makes the order independent of the order in which a script
+    /// happens to use its event handles.
+    pub canonical_event_order: bool,
+
+    /// Run the block prologue under the on-chain gas schedule rather than for free, and report
+    /// the gas it used in its output and in the `libra_vm_block_prologue_gas_usage` histogram.
+    /// The prologue still can't run out of gas. Meant to measure the cost of the prologue. A
+    /// prologue running without an on-chain gas schedule runs for free and is not observed.
+    pub meter_block_prologue: bool,
+
+    /// Fail scripts that move a single resource to or from global storage more times than this
+    /// with `MAX_RESOURCE_WRITES_REACHED`, charging the gas used. The write set only keeps the
+    /// last value, so the limit bounds the work a script can do for one write. Every mutable
+    /// borrow of a resource counts as a write too, whether or not the script changes it.
+    pub max_resource_writes: Option<u64>,
+
+    /// Waive the fee of the first transaction of each account, the one with sequence number 0:
+    /// its prologue and epilogue run with a gas unit price of zero, so the sender needs no
+    /// balance to send it and is charged nothing. The gas used is still metered and reported.
+    /// Applies to `LibraVM` execution, simulation and validation, not to `LibraVMValidator`, and
+    /// is not reflected by `fees::transaction_fee`.
+    pub first_transaction_gas_grace: bool,
+
+    /// Record which writes of each kept write set transaction come from its payload and which
+    /// from the framework, e.g. the write set epilogue, see `LibraVM::take_write_set_origins`.
+    /// The outputs still carry the merged write set. Keeps a copy of every write set, so it is
+    /// off by default.
+    pub record_write_set_origins: bool,
+
+    /// Run the block prologues with this timestamp, in microseconds, instead of the one of their
+    /// block, so that the scripts reading the time and the expiration checks give the same
+    /// outputs whenever a block is executed. Only available with the `timestamp-override`
+    /// feature, for tests and simulation, not for validators.
+    #[cfg(feature = "timestamp-override")]
+    pub timestamp_override: Option<u64>,
+
+    /// Artificial slowdowns, only available with the `chaos` feature.
+    #[cfg(feature = "chaos")]
+    pub chaos: ChaosConfig,
+}
+
+impl Default for ExecutorConfig {
+    fn default() -> Self {
+        Self {
+            collect_diagnostics: false,
+            keep_failed_transaction_events: false,
+            event_consistency_check: EventConsistencyCheck::Off,
+            prefetch: false,
+            allow_user_reconfiguration: true,
+            output_cache_capacity: None,
+            storage_gas_model: None,
+            account_layout: AccountLayout::standard(),
+            block_prologue_args: BlockPrologueArgs::standard(),
+            reserved_vm_address: account_config::reserved_vm_address(),
+            stale_config_check: cfg!(debug_assertions),
+            max_module_dependency_depth: DEFAULT_MAX_MODULE_DEPENDENCY_DEPTH,
+            max_transaction_size_in_bytes: None,
+            record_state_witness: false,
+            canonical_event_order: false,
+            meter_block_prologue: false,
+            max_resource_writes: None,
+            first_transaction_gas_grace: false,
+            record_write_set_origins: false,
+            #[cfg(feature = "timestamp-override")]
+            timestamp_override: None,
+            #[cfg(feature = "chaos")]
+            chaos: ChaosConfig::default(),
+        }
+    }
+}
+
+/// Synthetic latency injected by the executor to simulate slow execution when testing the
+/// resilience of downstream systems. Nothing is injected by default.
+#[cfg(feature = "chaos")]
+#[derive(Clone, Debug, Default)]
+pub struct ChaosConfig {
+    /// Time to sleep before executing each user transaction.
+    pub transaction_latency: Duration,
+}
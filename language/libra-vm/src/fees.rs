@@ -0,0 +1,28 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The fee charged to a user transaction and the part of its gas budget it got back, computed
+//! from its output.
+//!
+//! A transaction reserves `max_gas_amount * gas_unit_price` when it is admitted; the epilogue
+//! only charges for the gas used, the difference being refunded.
+
+use libra_types::transaction::{SignedTransaction, TransactionOutput, TransactionStatus};
+
+/// The fee charged for `output`, the output of `txn`: the gas used, at the gas unit price of the
+/// transaction. Discarded and retried outputs are not charged.
+pub fn transaction_fee(output: &TransactionOutput, txn: &SignedTransaction) -> u64 {
+    match output.status() {
+        TransactionStatus::Keep(_) => output.gas_used().saturating_mul(txn.gas_unit_price()),
+        TransactionStatus::Discard(_) | TransactionStatus::Retry => 0,
+    }
+}
+
+/// The part of the gas budget of `txn` that is not charged for `output`:
+/// `(max_gas_amount - gas_used) * gas_unit_price`. A transaction that failed after using its
+/// whole budget, e.g. by running out of gas, gets no refund. The whole budget is refunded for
+/// discarded and retried outputs, which are not charged.
+pub fn gas_refund(output: &TransactionOutput, txn: &SignedTransaction) -> u64 {
+    let budget = txn.max_gas_amount().saturating_mul(txn.gas_unit_price());
+    budget.saturating_sub(transaction_fee(output, txn))
+}
@@ -0,0 +1,137 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Regression fixtures: a block, the state it read and the outputs it produced, recorded once
+//! and replayed by tests to catch changes in the behavior of the executor.
+
+use crate::{read_log::ReadLog, LibraVM};
+use anyhow::Result;
+use libra_state_view::StateView;
+use libra_types::{
+    contract_event::ContractEvent,
+    transaction::{Transaction, TransactionOutput, TransactionStatus},
+    vm_status::VMStatus,
+    write_set::WriteSet,
+};
+
+/// A recorded block execution. Executing `transactions` against `reads` must give `outputs`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BlockFixture {
+    pub transactions: Vec<Transaction>,
+    pub reads: ReadLog,
+    pub outputs: Vec<TransactionOutput>,
+}
+
+impl BlockFixture {
+    /// Serializes the fixture with LCS, to store it as a file next to the test replaying it.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let outputs: Vec<_> = self
+            .outputs
+            .iter()
+            .map(|output| {
+                (
+                    output.write_set(),
+                    output.events(),
+                    output.gas_used(),
+                    output.status(),
+                )
+            })
+            .collect();
+        lcs::to_bytes(&(&self.transactions, self.reads.to_bytes()?, outputs)).map_err(Into::into)
+    }
+
+    /// Deserializes a fixture serialized with `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let (transactions, reads, outputs): (
+            Vec<Transaction>,
+            Vec<u8>,
+            Vec<(WriteSet, Vec<ContractEvent>, u64, TransactionStatus)>,
+        ) = lcs::from_bytes(bytes)?;
+        Ok(Self {
+            transactions,
+            reads: ReadLog::from_bytes(&reads)?,
+            outputs: outputs
+                .into_iter()
+                .map(|(write_set, events, gas_used, status)| {
+                    TransactionOutput::new(write_set, events, gas_used, status)
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Records `BlockFixture`s of the blocks executed by the wrapped VM, see
+/// `LibraVM::execute_block_recorded`. `run_fixture` replays them with the default config, so the
+/// wrapped VM should use it too.
+pub struct FixtureRecorder {
+    vm: LibraVM,
+}
+
+impl FixtureRecorder {
+    pub fn new(vm: LibraVM) -> Self {
+        Self { vm }
+    }
+
+    /// Executes `transactions` on top of `state_view` and returns the fixture of the execution.
+    pub fn record(
+        &mut self,
+        transactions: Vec<Transaction>,
+        state_view: &dyn StateView,
+    ) -> Result<BlockFixture, VMStatus> {
+        let (outputs, reads) = self
+            .vm
+            .execute_block_recorded(transactions.clone(), state_view)?;
+        Ok(BlockFixture {
+            transactions,
+            reads,
+            outputs,
+        })
+    }
+
+    pub fn into_inner(self) -> LibraVM {
+        self.vm
+    }
+}
+
+/// How the replay of a fixture differs from its recording.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FixtureMismatch {
+    /// The block failed to execute, e.g. because it read a path the recording did not.
+    ExecutionFailed(VMStatus),
+    /// The block did not produce as many outputs as recorded.
+    OutputCount { expected: usize, actual: usize },
+    /// The first output that differs from the recorded one.
+    Output {
+        index: usize,
+        expected: Box<TransactionOutput>,
+        actual: Box<TransactionOutput>,
+    },
+}
+
+/// Executes the block of `fixture` with a new `LibraVM` against the recorded reads, and checks
+/// that it gives the recorded outputs.
+pub fn run_fixture(fixture: &BlockFixture) -> Result<(), FixtureMismatch> {
+    let outputs = LibraVM::new()
+        .execute_transaction_block(fixture.transactions.clone(), &fixture.reads)
+        .map_err(FixtureMismatch::ExecutionFailed)?;
+    if outputs.len() != fixture.outputs.len() {
+        return Err(FixtureMismatch::OutputCount {
+            expected: fixture.outputs.len(),
+            actual: outputs.len(),
+        });
+    }
+    match fixture
+        .outputs
+        .iter()
+        .zip(outputs)
+        .enumerate()
+        .find(|(_, (expected, actual))| *expected != actual)
+    {
+        Some((index, (expected, actual))) => Err(FixtureMismatch::Output {
+            index,
+            expected: Box::new(expected.clone()),
+            actual: Box::new(actual),
+        }),
+        None => Ok(()),
+    }
+}
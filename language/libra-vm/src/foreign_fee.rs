@@ -0,0 +1,33 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Simulation of gas fees paid in another currency than the gas currency of a transaction, to
+//! model cross-currency fee markets. See `LibraVM::simulate_transaction_with_foreign_fee`.
+
+use libra_types::transaction::TransactionOutput;
+use std::convert::TryFrom;
+
+/// The outcome of a transaction simulated with its gas paid in a foreign currency.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ForeignFeeSimulation {
+    /// The output of the transaction, with the fee deducted from the foreign currency balance of
+    /// the sender.
+    pub output: TransactionOutput,
+    /// The fee in the gas currency of the transaction.
+    pub fee: u64,
+    /// The fee charged in the foreign currency.
+    pub foreign_fee: u64,
+}
+
+/// Converts a gas unit price to a currency worth `exchange_rate` units per unit of the original
+/// one. The rate is a `FixedPoint32` with 32 fractional bits, the representation of the on-chain
+/// exchange rates. The result is rounded up relative to the exact conversion. Returns `None` if
+/// the rate is zero or the converted price doesn't fit in a `u64`.
+pub fn convert_gas_unit_price(gas_unit_price: u64, exchange_rate: u64) -> Option<u64> {
+    if exchange_rate == 0 {
+        return None;
+    }
+    let scaled = u128::from(gas_unit_price) * u128::from(exchange_rate);
+    let converted = (scaled >> 32) + u128::from(scaled & u128::from(u32::max_value()) != 0);
+    u64::try_from(converted).ok()
+}
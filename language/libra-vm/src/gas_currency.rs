@@ -0,0 +1,15 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Resolution of the gas currency code of a transaction to the currency its gas is paid in.
+
+use move_core_types::identifier::Identifier;
+
+/// Maps the gas currency codes of transactions to currencies, for deployments that register
+/// currencies whose codes are not valid Move identifiers. Set with
+/// `LibraVM::set_gas_currency_resolver`.
+pub trait GasCurrencyResolver: Send + Sync {
+    /// Returns the currency identifier for `gas_currency_code`, or `None` to fall back to
+    /// `account_config::from_currency_code_string`.
+    fn resolve(&self, gas_currency_code: &str) -> Option<Identifier>;
+}
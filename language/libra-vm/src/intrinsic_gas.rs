@@ -0,0 +1,45 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The gas schedule constants that determine the intrinsic gas of a transaction, i.e. the gas
+//! charged for its size before any code runs.
+
+use move_core_types::gas_schedule::{GasAlgebra, GasConstants, WORD_SIZE};
+
+/// The parameters used by `CostStrategy::charge_intrinsic_gas`, in gas units and bytes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct IntrinsicGasParams {
+    /// Gas charged for any transaction up to `large_transaction_cutoff` bytes.
+    pub min_transaction_gas_units: u64,
+    /// Size in bytes above which every additional word is charged.
+    pub large_transaction_cutoff: u64,
+    /// Gas charged per word above `large_transaction_cutoff`.
+    pub intrinsic_gas_per_byte: u64,
+    /// Size of a word, in bytes.
+    pub word_size: u64,
+    /// Size of the largest transaction accepted by the VM.
+    pub max_transaction_size_in_bytes: u64,
+}
+
+impl IntrinsicGasParams {
+    pub fn new(gas_constants: &GasConstants) -> Self {
+        Self {
+            min_transaction_gas_units: gas_constants.min_transaction_gas_units.get(),
+            large_transaction_cutoff: gas_constants.large_transaction_cutoff.get(),
+            intrinsic_gas_per_byte: gas_constants.instrinsic_gas_per_byte.get(),
+            word_size: WORD_SIZE.get(),
+            max_transaction_size_in_bytes: gas_constants.max_transaction_size_in_bytes,
+        }
+    }
+
+    /// Intrinsic gas of a transaction whose raw bytes are `transaction_size` long.
+    pub fn intrinsic_gas(&self, transaction_size: u64) -> u64 {
+        if transaction_size > self.large_transaction_cutoff {
+            let excess = transaction_size - self.large_transaction_cutoff;
+            let words = (excess + self.word_size - 1) / self.word_size;
+            self.min_transaction_gas_units + self.intrinsic_gas_per_byte * words
+        } else {
+            self.min_transaction_gas_units
+        }
+    }
+}
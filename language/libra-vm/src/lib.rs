@@ -102,15 +102,43 @@
 
 #[macro_use]
 extern crate mirai_annotations;
+pub mod abort_message;
 mod access_path_cache;
+pub mod account_layout;
+pub mod argument_preprocessor;
+mod block_options;
+pub mod block_plan;
+pub mod block_prologue;
+pub mod block_summary;
+pub mod conflict_tracker;
 #[macro_use]
 mod counters;
 pub mod data_cache;
+pub mod diagnostics;
+pub mod event_consistency;
+pub mod event_sink;
+pub mod execution_time;
+pub mod executor_config;
+pub mod fees;
+pub mod fixture;
+pub mod foreign_fee;
+pub mod gas_currency;
+pub mod intrinsic_gas;
 
 #[cfg(feature = "mirai-contracts")]
 pub mod foreign_contracts;
 
 mod libra_vm;
+pub mod metrics_snapshot;
+pub mod output_cache;
+pub mod output_invariants;
+pub mod prefetch;
+pub mod profile;
+pub mod read_cache;
+pub mod read_log;
+pub mod schedule_search;
+pub mod state_store;
+pub mod storage_gas;
 pub mod transaction_metadata;
 
 #[cfg(test)]
@@ -119,6 +147,8 @@ mod unit_tests;
 pub mod libra_transaction_executor;
 pub mod libra_transaction_validator;
 pub mod system_module_names;
+pub mod write_set_categories;
+pub mod write_set_validator;
 
 pub use crate::{
     libra_transaction_executor::LibraVM, libra_transaction_validator::LibraVMValidator,
@@ -160,6 +190,14 @@ pub trait VMExecutor: Send {
         transactions: Vec<Transaction>,
         state_view: &dyn StateView,
     ) -> Result<Vec<TransactionOutput>, VMStatus>;
+
+    /// Executes a block of transactions like `execute_block`, retrying the user transactions that
+    /// come after the total gas used by the block reached `block_gas_limit`.
+    fn execute_block_with_gas_limit(
+        transactions: Vec<Transaction>,
+        state_view: &dyn StateView,
+        block_gas_limit: u64,
+    ) -> Result<Vec<TransactionOutput>, VMStatus>;
 }
 
 /// Get the AccessPath to a resource stored under `address` with type name `tag`
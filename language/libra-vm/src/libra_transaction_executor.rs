@@ -2,35 +2,69 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    abort_message::AbortMessageResolver,
+    argument_preprocessor::ArgumentPreprocessor,
+    block_options::BlockOptions,
+    block_plan::BlockPlan,
+    block_prologue::BlockPrologueInputs,
+    block_summary::BlockSummary,
+    conflict_tracker::{
+        are_independent, is_permutation_of, ConflictResolver, ConflictTracker, ReadTrackingView,
+        SnapshotView,
+    },
     counters::*,
+    create_access_path,
     data_cache::StateViewCache,
+    diagnostics::{GasAttribution, GasBreakdown, TransactionDiagnostics},
+    event_consistency::{find_event_conflicts, EventConflict, EventConsistencyCheck},
+    event_sink::EventSink,
+    execution_time::{ExecutionTimeAverages, TransactionKind},
+    executor_config::ExecutorConfig,
+    foreign_fee::{convert_gas_unit_price, ForeignFeeSimulation},
+    gas_currency::GasCurrencyResolver,
+    intrinsic_gas::IntrinsicGasParams,
     libra_vm::{
         get_transaction_output, txn_effects_to_writeset_and_events_cached, LibraVMImpl,
         LibraVMInternals,
     },
+    metrics_snapshot::VmMetricsSnapshot,
+    output_cache::OutputCache,
+    prefetch::derive_prefetch_hints,
+    profile::FoldedProfile,
+    read_log::{ReadLog, RecordingStateView},
+    schedule_search::{scale_cost_table, SCALE_DENOMINATOR},
+    state_store::{apply_block_outputs, InMemoryStateView},
     system_module_names::*,
     transaction_metadata::TransactionMetadata,
+    write_set_categories::WriteSetOrigins,
+    write_set_validator::WriteSetValidator,
     VMExecutor,
 };
 use debug_interface::prelude::*;
-use libra_crypto::HashValue;
+use libra_crypto::{hash::CryptoHash, HashValue};
 use libra_logger::prelude::*;
 use libra_state_view::StateView;
 use libra_types::{
+    access_path::AccessPath,
+    account_address::AccountAddress,
     account_config,
     block_metadata::BlockMetadata,
+    contract_event::ContractEvent,
+    event::EventKey,
+    on_chain_config::new_epoch_event_key,
     transaction::{
-        ChangeSet, Module, Script, SignatureCheckedTransaction, SignedTransaction, Transaction,
-        TransactionArgument, TransactionOutput, TransactionPayload, TransactionStatus,
+        authenticator::InvalidSignatures, ChangeSet, Module, Script, SignatureCheckedTransaction,
+        SignedTransaction, Transaction, TransactionArgument, TransactionOutput, TransactionPayload,
+        TransactionStatus, Version,
     },
     vm_status::{StatusCode, VMStatus},
-    write_set::{WriteSet, WriteSetMut},
+    write_set::{WriteOp, WriteSet, WriteSetMut},
 };
 use move_core_types::{
-    gas_schedule::{CostTable, GasAlgebra, GasCarrier, GasUnits},
-    identifier::IdentStr,
+    gas_schedule::{CostTable, GasAlgebra, GasCarrier, GasPrice, GasUnits},
+    identifier::{IdentStr, Identifier},
 };
-use move_vm_runtime::{data_cache::RemoteCache, session::Session};
+use move_vm_runtime::{call_trace::TraceEvent, data_cache::RemoteCache, session::Session};
 
 use move_vm_types::{
     gas_schedule::{zero_cost_schedule, CostStrategy},
@@ -38,24 +72,1053 @@ use move_vm_types::{
 };
 use rayon::prelude::*;
 use std::{
-    collections::HashSet,
+    collections::{BTreeSet, HashMap, HashSet},
     convert::{AsMut, AsRef, TryFrom},
+    time::{Duration, Instant},
+};
+use vm::{
+    errors::{Location, VMError},
+    CompiledModule,
 };
 
-pub struct LibraVM(LibraVMImpl);
+/// Margin added by `LibraVM::estimate_gas` to the gas used by a transaction, in percent.
+pub const GAS_ESTIMATE_MARGIN_PERCENT: u64 = 10;
+
+/// Callback passed the write set and events of every output applied during a block.
+pub type WriteSetObserver = Box<dyn FnMut(&WriteSet, &[ContractEvent]) + Send + Sync>;
+
+pub struct LibraVM {
+    inner: LibraVMImpl,
+    config: ExecutorConfig,
+    block_summary: BlockSummary,
+    diagnostics: Vec<TransactionDiagnostics>,
+    event_conflicts: Vec<EventConflict>,
+    execution_times: ExecutionTimeAverages,
+    output_cache: Option<OutputCache>,
+    write_set_observer: Option<WriteSetObserver>,
+    event_sink: Option<Box<dyn EventSink>>,
+    gas_currency_resolver: Option<Box<dyn GasCurrencyResolver>>,
+    write_set_validator: Option<Box<dyn WriteSetValidator>>,
+    abort_message_resolver: Option<Box<dyn AbortMessageResolver>>,
+    argument_preprocessor: Option<Box<dyn ArgumentPreprocessor>>,
+    /// Where the phases of the block are timed, only while `execute_block_with_profile` runs.
+    profile: Option<FoldedProfile>,
+    /// Number of reconfigurations committed by user transactions, and its value when the
+    /// on-chain configs were last loaded. Only maintained with `ExecutorConfig::stale_config_check`.
+    config_epoch: u64,
+    loaded_config_epoch: u64,
+    stale_config_transactions: Vec<(AccountAddress, u64)>,
+    write_set_origins: Vec<WriteSetOrigins>,
+    failed_transaction_events: Vec<Vec<ContractEvent>>,
+    #[cfg(feature = "chaos")]
+    injected_latency: Duration,
+    #[cfg(feature = "determinism-audit")]
+    audited_transactions: usize,
+}
 
 impl LibraVM {
     #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
-        Self(LibraVMImpl::new())
+        Self::new_with_config(ExecutorConfig::default())
+    }
+
+    pub fn new_with_config(config: ExecutorConfig) -> Self {
+        let output_cache = config.output_cache_capacity.map(OutputCache::new);
+        Self {
+            inner: LibraVMImpl::new(),
+            config,
+            block_summary: BlockSummary::default(),
+            diagnostics: vec![],
+            event_conflicts: vec![],
+            execution_times: ExecutionTimeAverages::default(),
+            output_cache,
+            write_set_observer: None,
+            event_sink: None,
+            gas_currency_resolver: None,
+            write_set_validator: None,
+            abort_message_resolver: None,
+            argument_preprocessor: None,
+            profile: None,
+            config_epoch: 0,
+            loaded_config_epoch: 0,
+            stale_config_transactions: vec![],
+            write_set_origins: vec![],
+            failed_transaction_events: vec![],
+            #[cfg(feature = "chaos")]
+            injected_latency: Duration::from_secs(0),
+            #[cfg(feature = "determinism-audit")]
+            audited_transactions: 0,
+        }
+    }
+
+    pub fn config(&self) -> &ExecutorConfig {
+        &self.config
+    }
+
+    /// Executes a block of `transactions` using the configuration of this instance. Behaves like
+    /// `VMExecutor::execute_block` otherwise.
+    pub fn execute_transaction_block(
+        &mut self,
+        transactions: Vec<Transaction>,
+        state_view: &dyn StateView,
+    ) -> Result<Vec<TransactionOutput>, VMStatus> {
+        self.execute_block_with_hooks(transactions, state_view, |_| (), |_, _| ())
+    }
+
+    /// Executes a block of `transactions` with `execute_transaction_block` on top of `state`, and
+    /// applies the write sets of the outputs that are kept to it. Discarded and retried outputs
+    /// leave `state` as it was. If the block fails, `state` is not changed either.
+    pub fn execute_block_and_apply(
+        &mut self,
+        transactions: Vec<Transaction>,
+        state: &mut InMemoryStateView,
+    ) -> Result<Vec<TransactionOutput>, VMStatus> {
+        let outputs = self.execute_transaction_block(transactions, &*state)?;
+        apply_block_outputs(state, &outputs)?;
+        Ok(outputs)
+    }
+
+    /// Like `execute_transaction_block`, and also returns every value read from `state_view`.
+    /// Executing `transactions` again against the returned log, e.g. on another machine, gives the
+    /// same outputs. Executions that do not go through this method do not record anything.
+    ///
+    /// The modules this instance already loaded are dropped first, so that the log contains all
+    /// the code the block runs.
+    pub fn execute_block_recorded(
+        &mut self,
+        transactions: Vec<Transaction>,
+        state_view: &dyn StateView,
+    ) -> Result<(Vec<TransactionOutput>, ReadLog), VMStatus> {
+        self.inner.reset_code_cache();
+        let recording_view = RecordingStateView::new(state_view);
+        let outputs = self.execute_transaction_block(transactions, &recording_view)?;
+        Ok((outputs, recording_view.into_log()))
+    }
+
+    /// Like `execute_transaction_block`, and also returns the time spent in each phase of the
+    /// block, e.g. to render it as a flamegraph. Executions that do not go through this method do
+    /// not aggregate anything.
+    pub fn execute_block_with_profile(
+        &mut self,
+        transactions: Vec<Transaction>,
+        state_view: &dyn StateView,
+    ) -> Result<(Vec<TransactionOutput>, FoldedProfile), VMStatus> {
+        self.profile = Some(FoldedProfile::default());
+        let result = self.execute_block_impl(transactions, state_view);
+        let profile = self.profile.take().unwrap_or_default();
+        result.map(|outputs| (outputs, profile))
+    }
+
+    /// Like `execute_transaction_block`, but calls `pre_block` before the first transaction is
+    /// executed and `post_block` with all the outputs once the last one was, both with the cache
+    /// holding the state of the block at that point.
+    pub fn execute_block_with_hooks<Pre, Post>(
+        &mut self,
+        transactions: Vec<Transaction>,
+        state_view: &dyn StateView,
+        pre_block: Pre,
+        post_block: Post,
+    ) -> Result<Vec<TransactionOutput>, VMStatus>
+    where
+        Pre: FnOnce(&StateViewCache<'_>),
+        Post: FnOnce(&StateViewCache<'_>, &[TransactionOutput]),
+    {
+        let outputs = self.execute_block_impl_with_hooks(
+            transactions,
+            state_view,
+            BlockOptions::default(),
+            pre_block,
+            post_block,
+        )?;
+        self.check_event_consistency(&outputs)?;
+        Ok(outputs)
+    }
+
+    /// Like `execute_transaction_block`, with `options` changing how the transactions are
+    /// executed.
+    pub(crate) fn execute_block_with_options(
+        &mut self,
+        transactions: Vec<Transaction>,
+        state_view: &dyn StateView,
+        options: BlockOptions<'_>,
+    ) -> Result<Vec<TransactionOutput>, VMStatus> {
+        let outputs = self.execute_block_impl_with_hooks(
+            transactions,
+            state_view,
+            options,
+            |_| (),
+            |_, _| (),
+        )?;
+        self.check_event_consistency(&outputs)?;
+        Ok(outputs)
+    }
+
+    fn check_event_consistency(&mut self, outputs: &[TransactionOutput]) -> Result<(), VMStatus> {
+        match self.config.event_consistency_check {
+            EventConsistencyCheck::Off => (),
+            EventConsistencyCheck::Report => {
+                self.event_conflicts.extend(find_event_conflicts(outputs))
+            }
+            EventConsistencyCheck::Fail => {
+                if let Some(conflict) = find_event_conflicts(outputs).first() {
+                    return Err(VMStatus::new(
+                        StatusCode::EVENT_KEY_MISMATCH,
+                        None,
+                        Some(format!(
+                            "Event {:?} in output {} has sequence number {}, expected {}",
+                            conflict.key,
+                            conflict.output_index,
+                            conflict.sequence_number,
+                            conflict.expected_sequence_number
+                        )),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Executes `transaction` as a singleton block on top of `state_view`, which must be the
+    /// state at `state_version`. The output is taken from the `OutputCache` if this transaction
+    /// was already executed on that version, and cached otherwise. Behaves like
+    /// `execute_transaction_block` if the cache is disabled.
+    pub fn execute_transaction_cached(
+        &mut self,
+        transaction: Transaction,
+        state_view: &dyn StateView,
+        state_version: Version,
+    ) -> Result<TransactionOutput, VMStatus> {
+        let txn_hash = transaction.hash();
+        if let Some(output) = self
+            .output_cache
+            .as_mut()
+            .and_then(|cache| cache.get(txn_hash, state_version))
+        {
+            self.record_diagnostics(TransactionDiagnostics::default());
+            return Ok(output);
+        }
+        let output = self
+            .execute_transaction_block(vec![transaction], state_view)?
+            .pop()
+            .ok_or_else(|| VMStatus::new(StatusCode::UNREACHABLE, None, None))?;
+        if let Some(cache) = self.output_cache.as_mut() {
+            cache.insert(txn_hash, state_version, output.clone());
+        }
+        Ok(output)
+    }
+
+    /// Like `execute_transaction_block`, but returns the outputs keyed by the hash of their
+    /// transaction, the one stored in the ledger. Block prologues and write sets are keyed the
+    /// same way as user transactions. If the block contains the same transaction more than once,
+    /// the output of the first occurrence is returned.
+    pub fn execute_block_by_hash(
+        &mut self,
+        transactions: Vec<Transaction>,
+        state_view: &dyn StateView,
+    ) -> Result<HashMap<HashValue, TransactionOutput>, VMStatus> {
+        let hashes: Vec<_> = transactions.iter().map(CryptoHash::hash).collect();
+        let outputs = self.execute_transaction_block(transactions, state_view)?;
+        let mut outputs_by_hash = HashMap::with_capacity(outputs.len());
+        for (hash, output) in hashes.into_iter().zip(outputs) {
+            outputs_by_hash.entry(hash).or_insert(output);
+        }
+        Ok(outputs_by_hash)
+    }
+
+    /// Statistics about the outputs of the last block executed by this instance.
+    pub fn block_summary(&self) -> &BlockSummary {
+        &self.block_summary
+    }
+
+    /// Simulates `txn` on top of `state_view` as if it had been signed with `sequence_number`.
+    /// The signature is still checked against the transaction as signed. This lets a client
+    /// simulate a transaction meant to follow others in a batch: passing the current sequence
+    /// number of the sender makes the prologue accept it. The output is not meant to be committed.
+    pub fn simulate_transaction_at_sequence_number(
+        &mut self,
+        txn: SignedTransaction,
+        state_view: &dyn StateView,
+        sequence_number: u64,
+    ) -> TransactionOutput {
+        let overrides = MetadataOverrides {
+            sequence_number: Some(sequence_number),
+            ..MetadataOverrides::default()
+        };
+        self.execute_standalone_transaction(
+            txn,
+            state_view,
+            overrides,
+            &mut TransactionDiagnostics::default(),
+        )
+    }
+
+    /// Executes the user transaction `txn` on top of `state_view`, outside of a block. The
+    /// prologue and epilogue run as in a block, and a transaction with an invalid signature is
+    /// discarded. Write set transactions are processed as in a block too, with the write set
+    /// prologue and the checks of the write set against the epilogue writes.
+    pub fn execute_single_transaction(
+        &mut self,
+        txn: SignedTransaction,
+        state_view: &dyn StateView,
+    ) -> Result<TransactionOutput, VMStatus> {
+        if let TransactionPayload::WriteSet(_) = txn.payload() {
+            let mut data_cache = StateViewCache::new(state_view);
+            self.inner.load_configs_impl(&data_cache);
+            return self.process_writeset_transaction(&mut data_cache, txn);
+        }
+        Ok(self.execute_standalone_transaction(
+            txn,
+            state_view,
+            MetadataOverrides::default(),
+            &mut TransactionDiagnostics::default(),
+        ))
+    }
+
+    /// Simulates `txn` on top of `state_view` with its gas paid in `currency_code` rather than in
+    /// its gas currency, at `exchange_rate` units of `currency_code` per unit of the gas currency.
+    /// The rate is a `FixedPoint32`, like the on-chain exchange rates. The gas unit price is
+    /// converted with `convert_gas_unit_price`, and the prologue and epilogue run against a
+    /// balance of the sender in `currency_code`. The output is not meant to be committed.
+    ///
+    /// Fails with `INVALID_GAS_SPECIFIER` if the rate is zero or the converted gas unit price
+    /// doesn't fit in a `u64`.
+    pub fn simulate_transaction_with_foreign_fee(
+        &mut self,
+        txn: SignedTransaction,
+        state_view: &dyn StateView,
+        currency_code: &IdentStr,
+        exchange_rate: u64,
+    ) -> Result<ForeignFeeSimulation, VMStatus> {
+        let gas_unit_price = txn.gas_unit_price();
+        let waived = self.waives_fee(txn.sequence_number());
+        let foreign_gas_unit_price = convert_gas_unit_price(gas_unit_price, exchange_rate)
+            .ok_or_else(|| {
+                VMStatus::new(
+                    StatusCode::INVALID_GAS_SPECIFIER,
+                    None,
+                    Some(format!(
+                        "cannot convert gas unit price {} at exchange rate {}",
+                        gas_unit_price, exchange_rate
+                    )),
+                )
+            })?;
+        let overrides = MetadataOverrides {
+            gas_unit_price: Some(foreign_gas_unit_price),
+            gas_currency_code: Some(currency_code.as_str()),
+            ..MetadataOverrides::default()
+        };
+        let output = self.execute_standalone_transaction(
+            txn,
+            state_view,
+            overrides,
+            &mut TransactionDiagnostics::default(),
+        );
+        let fee = |gas_unit_price: u64| {
+            if waived {
+                0
+            } else {
+                output.gas_used().saturating_mul(gas_unit_price)
+            }
+        };
+        Ok(ForeignFeeSimulation {
+            fee: fee(gas_unit_price),
+            foreign_fee: fee(foreign_gas_unit_price),
+            output,
+        })
+    }
+
+    /// Executes the script transaction `txn` on top of `state_view` like
+    /// `execute_single_transaction`, and reports how its gas splits between the intrinsic
+    /// charge, the script body and the epilogue. The breakdown is `None` if `txn` is not a script
+    /// or did not get past the prologue.
+    pub fn execute_script_with_breakdown(
+        &mut self,
+        txn: SignedTransaction,
+        state_view: &dyn StateView,
+    ) -> (TransactionOutput, Option<GasBreakdown>) {
+        let mut diagnostics = TransactionDiagnostics::default();
+        let output = self.execute_standalone_transaction(
+            txn,
+            state_view,
+            MetadataOverrides::default(),
+            &mut diagnostics,
+        );
+        let breakdown = match (diagnostics.intrinsic_gas, diagnostics.gas_attribution) {
+            (Some(intrinsic), Some(attribution)) => Some(GasBreakdown {
+                intrinsic,
+                execution: attribution.total() - intrinsic - attribution.epilogue,
+                epilogue: attribution.epilogue,
+            }),
+            _ => None,
+        };
+        (output, breakdown)
+    }
+
+    /// Executes the script transaction `txn` on top of `state_view` like
+    /// `execute_single_transaction`, and returns the calls made by the script: an event when a
+    /// function is entered and when it returns, with the gas left at that point. If the script
+    /// fails, the trace ends with an error event in the function that failed, and
+    /// `diagnostics::failure_call_stack` gives the calls that led to it. The trace is empty if
+    /// `txn` is not a script or did not get past the prologue.
+    pub fn execute_script_traced(
+        &mut self,
+        txn: SignedTransaction,
+        state_view: &dyn StateView,
+    ) -> (TransactionOutput, Vec<TraceEvent>) {
+        let mut diagnostics = TransactionDiagnostics {
+            call_trace: Some(vec![]),
+            ..TransactionDiagnostics::default()
+        };
+        let output = self.execute_standalone_transaction(
+            txn,
+            state_view,
+            MetadataOverrides::default(),
+            &mut diagnostics,
+        );
+        (output, diagnostics.call_trace.unwrap_or_default())
+    }
+
+    /// Checks whether `txn` would pass the prologue on top of `state_view`, i.e. its size and gas
+    /// parameters, whether its payload is allowed, and the sequence number, balance and expiration
+    /// time checked by `LibraAccount::prologue`, without executing it. Returns the status of the
+    /// first failing check, or `None`. The signature is not checked.
+    ///
+    /// The on-chain configs are read from `state_view`; the ones this instance loaded for the
+    /// blocks it executes are left untouched. Nothing is written: the session the prologue runs
+    /// in is dropped.
+    pub fn validate_transaction(
+        &self,
+        txn: &SignedTransaction,
+        state_view: &dyn StateView,
+    ) -> Option<VMStatus> {
+        let data_cache = StateViewCache::new(state_view);
+        let mut inner = self.inner.clone();
+        inner.load_configs_impl(&data_cache);
+        self.validate_transaction_impl(&inner, &data_cache, txn)
+            .err()
+    }
+
+    /// Validates each of `txns` like `validate_transaction`, in parallel, after checking its
+    /// signature: a transaction with an invalid signature is rejected without running the
+    /// prologue. Returns the transactions in order, each with the status it was rejected with,
+    /// or `None` if it is admitted.
+    ///
+    /// All the transactions are validated against `state_view` alone, they don't see each other:
+    /// e.g. two transactions of a sender with the same sequence number can both be admitted.
+    /// Pass the same `read_cache::SharedReadCache` to successive calls against the same state to
+    /// avoid reading its values again.
+    pub fn validate_batch(
+        &self,
+        txns: Vec<SignedTransaction>,
+        state_view: &(dyn StateView + Sync),
+    ) -> Vec<(SignedTransaction, Option<VMStatus>)> {
+        let mut inner = self.inner.clone();
+        inner.load_configs_impl(&StateViewCache::new(state_view));
+        txns.into_par_iter()
+            .map(|txn| {
+                let status = match txn.clone().check_signature() {
+                    Ok(_) => self
+                        .validate_transaction_impl(&inner, &StateViewCache::new(state_view), &txn)
+                        .err(),
+                    Err(err) => Some(invalid_signature_status(&err)),
+                };
+                (txn, status)
+            })
+            .collect()
+    }
+
+    fn validate_transaction_impl(
+        &self,
+        inner: &LibraVMImpl,
+        data_cache: &StateViewCache<'_>,
+        txn: &SignedTransaction,
+    ) -> Result<(), VMStatus> {
+        let account_currency_symbol = self.resolve_gas_currency(txn.gas_currency_code())?;
+        let txn_data = self.user_transaction_metadata(txn);
+        let mut cost_strategy =
+            CostStrategy::system(inner.get_gas_schedule()?, txn_data.max_gas_amount());
+        let mut session = inner.new_session(data_cache);
+        cost_strategy.disable_metering();
+        self.check_transaction_size(&txn_data)?;
+        inner.check_gas(&txn_data)?;
+        match txn.payload() {
+            TransactionPayload::Script(script) => inner.is_allowed_script(script)?,
+            TransactionPayload::Module(_) => inner.is_allowed_module(&txn_data, data_cache)?,
+            TransactionPayload::WriteSet(_) => {
+                return inner.run_writeset_prologue(&mut session, &txn_data)
+            }
+        }
+        inner.run_prologue(
+            &mut session,
+            &mut cost_strategy,
+            &txn_data,
+            account_currency_symbol.as_ident_str(),
+        )
+    }
+
+    /// Dry-runs the script or module of `txn` on top of `state_view` to estimate its gas usage.
+    /// The prologue and epilogue are not run, so the signature, sequence number, balance and
+    /// expiration time of the transaction are not checked. As both run unmetered, the reported
+    /// gas usage is the one a valid transaction would get. The write set lacks the updates of
+    /// the epilogue and must not be committed.
+    pub fn simulate_user_transaction(
+        &mut self,
+        txn: &SignedTransaction,
+        state_view: &dyn StateView,
+    ) -> TransactionOutput {
+        let mut data_cache = StateViewCache::new(state_view);
+        if let Some(storage_gas_model) = &self.config.storage_gas_model {
+            data_cache.set_storage_gas_model(storage_gas_model.clone());
+        }
+        self.inner.load_configs_impl(&data_cache);
+        let txn_data = self.user_transaction_metadata(txn);
+        self.simulate_user_transaction_impl(&data_cache, txn, txn_data)
+            .unwrap_or_else(discard_error_output)
+    }
+
+    /// Suggests a `max_gas_amount` for `txn`: the gas its script or module uses when simulated
+    /// with `simulate_user_transaction` under the largest `max_gas_amount` the gas schedule
+    /// allows, plus `GAS_ESTIMATE_MARGIN_PERCENT`. The gas used includes the intrinsic gas of
+    /// the transaction, and the epilogue is not metered, so a transaction setting the exact gas
+    /// used as its `max_gas_amount` would run against the same state. The margin covers changes
+    /// of the state before it runs. The `max_gas_amount` of `txn` is ignored.
+    ///
+    /// Fails with the status of the simulation if the transaction fails even with all that gas,
+    /// e.g. `ABORTED` or `OUT_OF_GAS`, or is discarded.
+    pub fn estimate_gas(
+        &mut self,
+        txn: &SignedTransaction,
+        state_view: &dyn StateView,
+    ) -> Result<u64, VMStatus> {
+        let mut data_cache = StateViewCache::new(state_view);
+        if let Some(storage_gas_model) = &self.config.storage_gas_model {
+            data_cache.set_storage_gas_model(storage_gas_model.clone());
+        }
+        self.inner.load_configs_impl(&data_cache);
+        let max_gas_amount = self.max_gas_per_transaction()?;
+        let mut txn_data = self.user_transaction_metadata(txn);
+        txn_data.max_gas_amount = GasUnits::new(max_gas_amount);
+        let output = self.simulate_user_transaction_impl(&data_cache, txn, txn_data)?;
+        if output.status() != &TransactionStatus::Keep(VMStatus::executed()) {
+            return Err(output.status().vm_status());
+        }
+        let margin = output
+            .gas_used()
+            .saturating_mul(GAS_ESTIMATE_MARGIN_PERCENT)
+            / 100;
+        Ok(output.gas_used().saturating_add(margin).min(max_gas_amount))
+    }
+
+    /// Executes `txn` on top of `state_view` in a throwaway cache and returns the paths it read,
+    /// e.g. to schedule similar transactions in an optimistic concurrency scheduler. The output
+    /// is dropped, whether the transaction is kept or discarded. The on-chain configs are not
+    /// read: the ones loaded by this instance are used, and the modules it already loaded are
+    /// not read again, so they are missing from the read set.
+    ///
+    /// Fails with `INVALID_SIGNATURE` if the signature of `txn` is invalid, and with
+    /// `VM_STARTUP_FAILURE` if no on-chain configs were loaded.
+    pub fn predict_read_set(
+        &self,
+        state_view: &dyn StateView,
+        txn: &SignedTransaction,
+    ) -> Result<HashSet<AccessPath>, VMStatus> {
+        self.inner.on_chain_config()?;
+        let txn = txn
+            .clone()
+            .check_signature()
+            .map_err(|err| invalid_signature_status(&err))?;
+        let read_tracker = ReadTrackingView::new(state_view);
+        {
+            let mut cache = StateViewCache::new(&read_tracker);
+            if let Some(storage_gas_model) = &self.config.storage_gas_model {
+                cache.set_storage_gas_model(storage_gas_model.clone());
+            }
+            self.execute_user_transaction_with_metadata(
+                &cache,
+                &txn,
+                self.user_transaction_metadata(&txn),
+                txn.gas_currency_code(),
+                &mut TransactionDiagnostics::default(),
+            );
+        }
+        Ok(read_tracker.into_reads().into_iter().collect())
+    }
+
+    fn simulate_user_transaction_impl(
+        &self,
+        data_cache: &StateViewCache<'_>,
+        txn: &SignedTransaction,
+        txn_data: TransactionMetadata,
+    ) -> Result<TransactionOutput, VMStatus> {
+        let gas_schedule = self.inner.get_gas_schedule()?;
+        self.inner.check_gas(&txn_data)?;
+        let mut cost_strategy = CostStrategy::transaction(gas_schedule, txn_data.max_gas_amount());
+        let mut session = self.inner.new_session(data_cache);
+        let result = cost_strategy
+            .charge_intrinsic_gas(txn_data.transaction_size())
+            .map_err(|e| e.into_vm_status())
+            .and_then(|()| match txn.payload() {
+                TransactionPayload::Script(script) => {
+                    self.inner.is_allowed_script(script)?;
+                    data_cache.take_storage_gas();
+                    session
+                        .execute_script(
+                            script.code().to_vec(),
+                            script.ty_args().to_vec(),
+                            self.script_args(script),
+                            txn_data.sender(),
+                            &mut cost_strategy,
+                        )
+                        .map_err(|e| e.into_vm_status())?;
+                    cost_strategy
+                        .deduct_gas(GasUnits::new(data_cache.take_storage_gas()))
+                        .map_err(|e| e.finish(Location::Undefined).into_vm_status())
+                }
+                TransactionPayload::Module(module) => {
+                    self.inner.is_allowed_module(&txn_data, data_cache)?;
+                    let module_address =
+                        if self.inner.on_chain_config()?.publishing_option.is_open() {
+                            txn_data.sender()
+                        } else {
+                            account_config::CORE_CODE_ADDRESS
+                        };
+                    session
+                        .publish_module(module.code().to_vec(), module_address, &mut cost_strategy)
+                        .map_err(|e| e.into_vm_status())
+                }
+                TransactionPayload::WriteSet(_) => {
+                    Err(VMStatus::new(StatusCode::UNREACHABLE, None, None))
+                }
+            });
+        match result {
+            Ok(()) => get_transaction_output(
+                &mut (),
+                session,
+                &cost_strategy,
+                &txn_data,
+                VMStatus::executed(),
+                self.config.canonical_event_order,
+            ),
+            Err(err) => {
+                if TransactionStatus::from(err.clone()).is_discarded() {
+                    return Err(err);
+                }
+                let gas_used = txn_data
+                    .max_gas_amount()
+                    .sub(cost_strategy.remaining_gas())
+                    .get();
+                Ok(TransactionOutput::new(
+                    WriteSet::default(),
+                    vec![],
+                    gas_used,
+                    TransactionStatus::Keep(err),
+                ))
+            }
+        }
+    }
+
+    fn execute_standalone_transaction(
+        &mut self,
+        txn: SignedTransaction,
+        state_view: &dyn StateView,
+        overrides: MetadataOverrides<'_>,
+        diagnostics: &mut TransactionDiagnostics,
+    ) -> TransactionOutput {
+        let txn = match txn.check_signature() {
+            Ok(txn) => txn,
+            Err(err) => return discard_error_output(invalid_signature_status(&err)),
+        };
+        let mut data_cache = StateViewCache::new(state_view);
+        if let Some(storage_gas_model) = &self.config.storage_gas_model {
+            data_cache.set_storage_gas_model(storage_gas_model.clone());
+        }
+        self.inner.load_configs_impl(&data_cache);
+        let mut txn_data = TransactionMetadata::new(&txn);
+        if let Some(sequence_number) = overrides.sequence_number {
+            txn_data.sequence_number = sequence_number;
+        }
+        if let Some(gas_unit_price) = overrides.gas_unit_price {
+            txn_data.gas_unit_price = GasPrice::new(gas_unit_price);
+        }
+        // The grace follows the sequence number the transaction runs with, and waives even an
+        // overridden gas unit price.
+        if self.waives_fee(txn_data.sequence_number()) {
+            txn_data.gas_unit_price = GasPrice::new(0);
+        }
+        let gas_currency_code = overrides
+            .gas_currency_code
+            .unwrap_or_else(|| txn.gas_currency_code());
+        self.execute_user_transaction_with_metadata(
+            &data_cache,
+            &txn,
+            txn_data,
+            gas_currency_code,
+            diagnostics,
+        )
+    }
+
+    /// Returns the output cache, if enabled with `ExecutorConfig::output_cache_capacity`.
+    pub fn output_cache(&self) -> Option<&OutputCache> {
+        self.output_cache.as_ref()
+    }
+
+    /// Returns the event conflicts found so far and resets the collection. Always empty unless
+    /// `ExecutorConfig::event_consistency_check` is `EventConsistencyCheck::Report`.
+    pub fn take_event_conflicts(&mut self) -> Vec<EventConflict> {
+        std::mem::take(&mut self.event_conflicts)
+    }
+
+    /// Returns the sender and sequence number of the transactions found to have executed against
+    /// on-chain configs changed by an earlier transaction of their block, and resets the
+    /// collection. Always empty unless `ExecutorConfig::stale_config_check` is set.
+    pub fn take_stale_config_transactions(&mut self) -> Vec<(AccountAddress, u64)> {
+        std::mem::take(&mut self.stale_config_transactions)
+    }
+
+    /// Returns the origins of the writes of the write set transactions kept so far, one per
+    /// output, and resets the collection. Always empty unless
+    /// `ExecutorConfig::record_write_set_origins` is set.
+    pub fn take_write_set_origins(&mut self) -> Vec<WriteSetOrigins> {
+        std::mem::take(&mut self.write_set_origins)
+    }
+
+    /// Returns the events each transaction emitted before it failed, one list per output, and
+    /// resets the collection. The list is empty for the transactions that didn't fail after the
+    /// prologue, and the whole collection is empty unless
+    /// `ExecutorConfig::keep_failed_transaction_events` is set.
+    ///
+    /// These events are not in the outputs: the failure rolled them back with the rest of the
+    /// effects of the script. Their sequence numbers were never committed, and the next events
+    /// emitted on the same keys use them again.
+    pub fn take_failed_transaction_events(&mut self) -> Vec<Vec<ContractEvent>> {
+        std::mem::take(&mut self.failed_transaction_events)
+    }
+
+    /// Returns the diagnostics collected so far, one per output, and resets the collection.
+    /// Always empty unless `ExecutorConfig::collect_diagnostics` is set.
+    pub fn take_diagnostics(&mut self) -> Vec<TransactionDiagnostics> {
+        std::mem::take(&mut self.diagnostics)
+    }
+
+    /// Returns whether `output` starts a new epoch: it is kept and emits an event on the new
+    /// epoch event key, as done when the on-chain configs change, be it by a write set or by a
+    /// script.
+    pub fn is_reconfiguration(output: &TransactionOutput) -> bool {
+        is_reconfiguration(output)
+    }
+
+    /// Returns the events of `output` grouped by event key, in the order they were emitted for
+    /// each key.
+    pub fn group_events_by_key(
+        output: &TransactionOutput,
+    ) -> HashMap<EventKey, Vec<&ContractEvent>> {
+        events_by_key(output.events())
+    }
+
+    /// Returns the current values of the VM counters, as reported to Prometheus.
+    pub fn metrics_snapshot() -> VmMetricsSnapshot {
+        VmMetricsSnapshot::capture()
+    }
+
+    /// Returns the total gas charged so far to the kept user transactions paying for gas in
+    /// `currency_code`, as reported to Prometheus. Shared by all the VM instances of the process.
+    pub fn gas_used_in_currency(currency_code: &IdentStr) -> u64 {
+        TXN_GAS_USED_BY_CURRENCY
+            .with_label_values(&[currency_code.as_str()])
+            .get() as u64
+    }
+
+    /// Describes how `transactions` would be split and run by the executor, without executing
+    /// them.
+    pub fn plan_block(transactions: Vec<Transaction>) -> BlockPlan {
+        BlockPlan::new(&chunk_block_transactions(transactions))
+    }
+
+    /// Heuristic estimate of the time it would take this instance to execute `transactions`,
+    /// based on a moving average of the execution times it observed so far for each kind of
+    /// transaction. Returns zero until some blocks have been executed.
+    pub fn estimate_block_duration(&self, transactions: &[Transaction]) -> Duration {
+        self.execution_times.estimate(transactions)
+    }
+
+    /// Total latency injected so far because of `ExecutorConfig::chaos`.
+    #[cfg(feature = "chaos")]
+    pub fn injected_latency(&self) -> Duration {
+        self.injected_latency
+    }
+
+    /// Number of user transactions executed twice so far by the `determinism-audit` feature.
+    #[cfg(feature = "determinism-audit")]
+    pub fn audited_transactions(&self) -> usize {
+        self.audited_transactions
+    }
+
+    /// Returns the authentication key stored in the account resource of `addr`, or `None` if
+    /// there is no account at that address.
+    pub fn account_auth_key(
+        &self,
+        state_view: &dyn StateView,
+        addr: AccountAddress,
+    ) -> Result<Option<Vec<u8>>, VMStatus> {
+        let layout = &self.config.account_layout;
+        read_account_field(
+            state_view,
+            create_access_path(addr, layout.account_resource.clone()),
+            layout.authentication_key,
+        )
+    }
+
+    /// Returns the sequence number stored in the account resource of `addr`, or `None` if there
+    /// is no account at that address.
+    pub fn account_sequence_number(
+        &self,
+        state_view: &dyn StateView,
+        addr: AccountAddress,
+    ) -> Result<Option<u64>, VMStatus> {
+        let layout = &self.config.account_layout;
+        read_account_field(
+            state_view,
+            create_access_path(addr, layout.account_resource.clone()),
+            layout.sequence_number,
+        )
+    }
+
+    /// Returns the balance of `addr` in `currency_code`, or `None` if the account holds no
+    /// balance in that currency.
+    pub fn account_balance(
+        &self,
+        state_view: &dyn StateView,
+        addr: AccountAddress,
+        currency_code: &IdentStr,
+    ) -> Result<Option<u64>, VMStatus> {
+        let layout = &self.config.account_layout;
+        let currency = account_config::type_tag_for_currency_code(currency_code.to_owned());
+        read_account_field(
+            state_view,
+            create_access_path(addr, (layout.balance_resource)(currency)),
+            layout.balance,
+        )
+    }
+
+    /// Returns by how much `output` changes the balance of `addr` in `currency_code`, comparing
+    /// the balance in `pre_state`, the state the transaction was executed on, to the one written
+    /// by `output`. Accounts whose balance is not written by the output, e.g. because it is
+    /// discarded, have a change of 0. A deleted balance counts as a balance of 0.
+    pub fn net_balance_change(
+        &self,
+        pre_state: &dyn StateView,
+        output: &TransactionOutput,
+        addr: AccountAddress,
+        currency_code: &IdentStr,
+    ) -> Result<i128, VMStatus> {
+        let layout = &self.config.account_layout;
+        let currency = account_config::type_tag_for_currency_code(currency_code.to_owned());
+        let access_path = create_access_path(addr, (layout.balance_resource)(currency));
+        let post_balance = match output
+            .write_set()
+            .iter()
+            .find(|(path, _)| *path == access_path)
+        {
+            Some((_, WriteOp::Value(blob))) => (layout.balance)(blob).ok_or_else(|| {
+                VMStatus::new(StatusCode::VALUE_DESERIALIZATION_ERROR, None, None)
+            })?,
+            Some((_, WriteOp::Deletion)) => 0,
+            None => return Ok(0),
+        };
+        let pre_balance = read_account_field(pre_state, access_path, layout.balance)?.unwrap_or(0);
+        Ok(i128::from(post_balance) - i128::from(pre_balance))
+    }
+
+    /// Returns the gas schedule constants used to charge the intrinsic gas of a transaction.
+    /// Requires the on-chain configs to be loaded.
+    pub fn intrinsic_gas_params(&self) -> Result<IntrinsicGasParams, VMStatus> {
+        let gas_schedule = self.inner.get_gas_schedule()?;
+        Ok(IntrinsicGasParams::new(&gas_schedule.gas_constants))
+    }
+
+    /// Returns the largest `max_gas_amount` a transaction can set, the bound checked before the
+    /// prologue runs. Requires the on-chain configs to be loaded.
+    pub fn max_gas_per_transaction(&self) -> Result<u64, VMStatus> {
+        let gas_schedule = self.inner.get_gas_schedule()?;
+        Ok(gas_schedule.gas_constants.maximum_number_of_gas_units.get())
+    }
+
+    /// Returns whether the publishing option of the on-chain configs lets transactions run
+    /// `script`, looking the hash of its code up in the allowlist if there is one. Unlike
+    /// `validate_transaction` this neither runs the prologue nor reads account state. Requires
+    /// the on-chain configs to be loaded.
+    pub fn is_script_allowed(&self, script: &Script) -> Result<bool, VMStatus> {
+        Ok(self
+            .inner
+            .on_chain_config()?
+            .publishing_option
+            .is_allowed_script(script.code()))
+    }
+
+    /// Calls `observer` with the write set and events of every output of a block that is not
+    /// discarded, in the order they are applied to the block state, e.g. to stream the state
+    /// changes to an indexer. Replaces any previous observer.
+    pub fn set_write_set_observer<F>(&mut self, observer: F)
+    where
+        F: FnMut(&WriteSet, &[ContractEvent]) + Send + Sync + 'static,
+    {
+        self.write_set_observer = Some(Box::new(observer));
+    }
+
+    /// Removes the observer set with `set_write_set_observer`.
+    pub fn clear_write_set_observer(&mut self) {
+        self.write_set_observer = None;
+    }
+
+    /// Delivers the events of the outputs of a block to `sink` as they are committed rather than
+    /// returning them in the outputs, which are then returned without events. The write set
+    /// observer still sees the events. Replaces any previous sink.
+    ///
+    /// The session only hands the events of a transaction over when it finishes, so the events
+    /// of one transaction are still buffered: the sink bounds the memory used by the events of
+    /// a block, not of a transaction. The outputs returned with a sink don't hash to the outputs
+    /// of the block and must not be committed to storage, and the event consistency check of
+    /// `ExecutorConfig::event_consistency_check` sees no events.
+    pub fn set_event_sink<S>(&mut self, sink: S)
+    where
+        S: EventSink + 'static,
+    {
+        self.event_sink = Some(Box::new(sink));
+    }
+
+    /// Removes the sink set with `set_event_sink`, the events are returned in the outputs again.
+    pub fn clear_event_sink(&mut self) {
+        self.event_sink = None;
+    }
+
+    /// Resolves the gas currency codes of user transactions with `resolver` before falling back
+    /// to `account_config::from_currency_code_string`. Replaces any previous resolver.
+    pub fn set_gas_currency_resolver<R>(&mut self, resolver: R)
+    where
+        R: GasCurrencyResolver + 'static,
+    {
+        self.gas_currency_resolver = Some(Box::new(resolver));
+    }
+
+    /// Removes the resolver set with `set_gas_currency_resolver`.
+    pub fn clear_gas_currency_resolver(&mut self) {
+        self.gas_currency_resolver = None;
+    }
+
+    /// Checks the write set of every kept user transaction output of a block with `validator`
+    /// before it is committed. Replaces any previous validator.
+    pub fn set_write_set_validator<V>(&mut self, validator: V)
+    where
+        V: WriteSetValidator + 'static,
+    {
+        self.write_set_validator = Some(Box::new(validator));
+    }
+
+    /// Removes the validator set with `set_write_set_validator`.
+    pub fn clear_write_set_validator(&mut self) {
+        self.write_set_validator = None;
+    }
+
+    /// Appends the message `resolver` gives to the abort code of a script aborting in a module to
+    /// the status of the transaction. The failure epilogue keeps that status, so the message
+    /// shows up in the output. Replaces any previous resolver.
+    pub fn set_abort_message_resolver<R>(&mut self, resolver: R)
+    where
+        R: AbortMessageResolver + 'static,
+    {
+        self.abort_message_resolver = Some(Box::new(resolver));
+    }
+
+    /// Removes the resolver set with `set_abort_message_resolver`.
+    pub fn clear_abort_message_resolver(&mut self) {
+        self.abort_message_resolver = None;
+    }
+
+    /// Runs scripts with the arguments `preprocessor` turns their signed arguments into.
+    /// Replaces any previous preprocessor.
+    pub fn set_argument_preprocessor<P>(&mut self, preprocessor: P)
+    where
+        P: ArgumentPreprocessor + 'static,
+    {
+        self.argument_preprocessor = Some(Box::new(preprocessor));
+    }
+
+    /// Removes the preprocessor set with `set_argument_preprocessor`.
+    pub fn clear_argument_preprocessor(&mut self) {
+        self.argument_preprocessor = None;
+    }
+
+    /// Executes with `gas_schedule` instead of the on-chain gas schedule, or with the on-chain one
+    /// again if `None`. Meant for tooling, validators must not use it.
+    pub fn set_gas_schedule_override(&mut self, gas_schedule: Option<CostTable>) {
+        self.inner.set_gas_schedule_override(gas_schedule)
+    }
+
+    /// Heuristic search for a gas schedule under which every transaction of `transactions` is
+    /// kept and executed successfully, for gas schedule design.
+    ///
+    /// The instruction and native costs of the on-chain schedule are scaled down uniformly, and
+    /// the schedule with the largest scale under which the whole block succeeds is returned, with
+    /// a precision of `1 / SCALE_DENOMINATOR`. This assumes that cheaper instructions never make a
+    /// transaction fail. Returns the status of a failing transaction if the block does not succeed
+    /// even with free instructions.
+    pub fn min_viable_schedule(
+        &self,
+        state_view: &dyn StateView,
+        transactions: Vec<Transaction>,
+    ) -> Result<CostTable, VMStatus> {
+        let mut simulator = LibraVM::new();
+        simulator.inner = self.inner.clone();
+        simulator.load_configs(state_view);
+        simulator.set_gas_schedule_override(None);
+        let on_chain_schedule = simulator.inner.get_gas_schedule()?.clone();
+
+        let mut first_failure = |numerator| -> Result<Option<VMStatus>, VMStatus> {
+            simulator
+                .set_gas_schedule_override(Some(scale_cost_table(&on_chain_schedule, numerator)));
+            let outputs = simulator.execute_block_impl(transactions.clone(), state_view)?;
+            Ok(outputs
+                .into_iter()
+                .find_map(|output| match output.status() {
+                    TransactionStatus::Keep(status)
+                        if status.major_status == StatusCode::EXECUTED =>
+                    {
+                        None
+                    }
+                    TransactionStatus::Keep(status) | TransactionStatus::Discard(status) => {
+                        Some(status.clone())
+                    }
+                    TransactionStatus::Retry => {
+                        Some(VMStatus::new(StatusCode::UNKNOWN_STATUS, None, None))
+                    }
+                }))
+        };
+
+        if first_failure(SCALE_DENOMINATOR)?.is_none() {
+            return Ok(on_chain_schedule);
+        }
+        if let Some(status) = first_failure(0)? {
+            return Err(status);
+        }
+        // The block succeeds with `low` and fails with `high`.
+        let (mut low, mut high) = (0, SCALE_DENOMINATOR);
+        while high - low > 1 {
+            let mid = low + (high - low) / 2;
+            if first_failure(mid)?.is_none() {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        Ok(scale_cost_table(&on_chain_schedule, low))
     }
 
     pub fn load_configs<S: StateView>(&mut self, state: &S) {
-        self.0.load_configs(state)
+        self.inner.load_configs(state)
     }
 
     pub fn internals(&self) -> LibraVMInternals {
-        LibraVMInternals::new(&self.0)
+        LibraVMInternals::new(&self.inner)
     }
 
     /// Generates a transaction output for a transaction that encountered errors during the
@@ -68,50 +1131,96 @@ impl LibraVM {
         txn_data: &TransactionMetadata,
         remote_cache: &StateViewCache<'_>,
         account_currency_symbol: &IdentStr,
+    ) -> TransactionOutput {
+        self.failed_transaction_cleanup_with_status(
+            TransactionStatus::from(error_code),
+            gas_schedule,
+            gas_left,
+            txn_data,
+            remote_cache,
+            account_currency_symbol,
+        )
+    }
+
+    pub(crate) fn failed_transaction_cleanup_with_status(
+        &self,
+        status: TransactionStatus,
+        gas_schedule: &CostTable,
+        gas_left: GasUnits<GasCarrier>,
+        txn_data: &TransactionMetadata,
+        remote_cache: &StateViewCache<'_>,
+        account_currency_symbol: &IdentStr,
     ) -> TransactionOutput {
         let mut cost_strategy = CostStrategy::system(gas_schedule, gas_left);
-        let mut session = self.0.new_session(remote_cache);
-        match TransactionStatus::from(error_code) {
+        let mut session = self.inner.new_session(remote_cache);
+        match status {
             TransactionStatus::Keep(status) => {
-                if let Err(e) = self.0.run_failure_epilogue(
+                if let Err(e) = self.inner.run_failure_epilogue(
                     &mut session,
                     &mut cost_strategy,
                     txn_data,
                     account_currency_symbol,
                 ) {
-                    return discard_error_output(e);
+                    // Keep the status that sent the transaction down the failure path, the
+                    // epilogue failure alone does not say why the transaction failed.
+                    return discard_error_output(
+                        e.with_context(format!("failure epilogue run after {}", status)),
+                    );
+                }
+                match get_transaction_output(
+                    &mut (),
+                    session,
+                    &cost_strategy,
+                    txn_data,
+                    status,
+                    self.config.canonical_event_order,
+                ) {
+                    Ok(output) => {
+                        record_gas_used(account_currency_symbol, &output);
+                        output
+                    }
+                    Err(e) => discard_error_output(e),
                 }
-                get_transaction_output(&mut (), session, &cost_strategy, txn_data, status)
-                    .unwrap_or_else(discard_error_output)
             }
             TransactionStatus::Discard(status) => discard_error_output(status),
-            TransactionStatus::Retry => unreachable!(),
+            // No `VMStatus` maps to `Retry`. Don't bring the executor down if one ever does.
+            TransactionStatus::Retry => discard_error_output(VMStatus::new(
+                StatusCode::UNREACHABLE,
+                None,
+                Some("Retry status in failed transaction cleanup".to_owned()),
+            )),
         }
     }
 
+    /// Charges the storage gas of the writes of `session` to `cost_strategy`, then runs the
+    /// success epilogue with the gas left and returns the output of the transaction.
     fn success_transaction_cleanup<R: RemoteCache>(
         &self,
         mut session: Session<R>,
         gas_schedule: &CostTable,
-        gas_left: GasUnits<GasCarrier>,
+        cost_strategy: &mut CostStrategy,
         txn_data: &TransactionMetadata,
         account_currency_symbol: &IdentStr,
     ) -> Result<TransactionOutput, VMStatus> {
-        let mut cost_strategy = CostStrategy::system(gas_schedule, gas_left);
-        self.0.run_success_epilogue(
+        self.charge_write_set_storage_gas(&session, gas_schedule, cost_strategy)?;
+        let mut cost_strategy = CostStrategy::system(gas_schedule, cost_strategy.remaining_gas());
+        self.inner.run_success_epilogue(
             &mut session,
             &mut cost_strategy,
             txn_data,
             account_currency_symbol,
         )?;
 
-        Ok(get_transaction_output(
+        let output = get_transaction_output(
             &mut (),
             session,
             &cost_strategy,
             txn_data,
             VMStatus::executed(),
-        )?)
+            self.config.canonical_event_order,
+        )?;
+        record_gas_used(account_currency_symbol, &output);
+        Ok(output)
     }
 
     fn execute_script(
@@ -121,41 +1230,107 @@ impl LibraVM {
         txn_data: &TransactionMetadata,
         script: &Script,
         account_currency_symbol: &IdentStr,
+        diagnostics: &mut TransactionDiagnostics,
     ) -> Result<TransactionOutput, VMStatus> {
-        let gas_schedule = self.0.get_gas_schedule()?;
-        let mut session = self.0.new_session(remote_cache);
+        let gas_schedule = self.inner.get_gas_schedule()?;
+        let mut session = self.inner.new_session(remote_cache);
         // TODO: The logic for handling falied transaction fee is pretty ugly right now. Fix it later.
 
         // Run the validation logic
         {
             cost_strategy.disable_metering();
             let _timer = TXN_VERIFICATION_SECONDS.start_timer();
-            self.0.check_gas(txn_data)?;
-            self.0.is_allowed_script(script)?;
-            self.0.run_prologue(
+            self.check_transaction_size(txn_data)?;
+            self.inner.check_gas(txn_data)?;
+            self.inner.is_allowed_script(script)?;
+            let start = Instant::now();
+            self.inner.run_prologue(
                 &mut session,
                 cost_strategy,
                 &txn_data,
                 account_currency_symbol,
             )?;
+            diagnostics.phase_times.prologue = start.elapsed();
         }
 
         // Run the execution logic
         {
             let _timer = TXN_EXECUTION_SECONDS.start_timer();
             cost_strategy.enable_metering();
+            let gas_before_intrinsic = cost_strategy.remaining_gas().get();
             cost_strategy
                 .charge_intrinsic_gas(txn_data.transaction_size())
                 .map_err(|e| e.into_vm_status())?;
-            session
-                .execute_script(
+            let gas_after_prologue = cost_strategy.remaining_gas().get();
+            diagnostics.intrinsic_gas = Some(gas_before_intrinsic - gas_after_prologue);
+            diagnostics.gas_available_for_script = Some(gas_after_prologue);
+            let prologue_gas = txn_data.max_gas_amount().get() - gas_after_prologue;
+            // Reads done by the prologue are not charged.
+            remote_cache.take_storage_gas();
+            #[cfg(feature = "instruction-count")]
+            let instructions_before = cost_strategy.instructions_executed();
+            let args = self.script_args(script);
+            let result = match diagnostics.call_trace.as_mut() {
+                Some(call_trace) => {
+                    let (result, trace) = session.execute_script_traced(
+                        script.code().to_vec(),
+                        script.ty_args().to_vec(),
+                        args,
+                        txn_data.sender(),
+                        cost_strategy,
+                    );
+                    call_trace.extend(trace);
+                    result
+                }
+                None => session.execute_script(
                     script.code().to_vec(),
                     script.ty_args().to_vec(),
-                    convert_txn_args(script.args()),
+                    args,
                     txn_data.sender(),
                     cost_strategy,
-                )
-                .map_err(|e| e.into_vm_status())?;
+                ),
+            };
+            #[cfg(feature = "instruction-count")]
+            {
+                let instructions = cost_strategy.instructions_executed() - instructions_before;
+                TXN_INSTRUCTIONS_EXECUTED.observe(instructions as f64);
+                diagnostics.instructions_executed = Some(instructions);
+            }
+            if let Err(err) = result
+                .map_err(|e| self.script_error_status(e, remote_cache))
+                .and_then(|()| self.check_resource_writes(&session))
+                .and_then(|()| self.check_event_count(&session))
+            {
+                if self.config.keep_failed_transaction_events {
+                    // The session is not rolled back on failure, so its effects still hold
+                    // whatever the script emitted before it stopped.
+                    diagnostics.pre_failure_events = session
+                        .finish()
+                        .map_err(|e| e.into_vm_status())
+                        .and_then(|effects| {
+                            txn_effects_to_writeset_and_events_cached(&mut (), effects)
+                        })
+                        .map(|(_, events)| events)
+                        .unwrap_or_default();
+                }
+                diagnostics.gas_attribution = Some(GasAttribution {
+                    prologue: prologue_gas,
+                    execution: gas_after_prologue - cost_strategy.remaining_gas().get(),
+                    epilogue: 0,
+                });
+                return Err(err);
+            }
+            if let Err(err) = cost_strategy
+                .deduct_gas(GasUnits::new(remote_cache.take_storage_gas()))
+                .map_err(|e| e.finish(Location::Undefined).into_vm_status())
+            {
+                diagnostics.gas_attribution = Some(GasAttribution {
+                    prologue: prologue_gas,
+                    execution: gas_after_prologue - cost_strategy.remaining_gas().get(),
+                    epilogue: 0,
+                });
+                return Err(err);
+            }
 
             let gas_usage = txn_data
                 .max_gas_amount()
@@ -164,13 +1339,21 @@ impl LibraVM {
             TXN_EXECUTION_GAS_USAGE.observe(gas_usage as f64);
 
             cost_strategy.disable_metering();
-            self.success_transaction_cleanup(
+            let start = Instant::now();
+            let output = self.success_transaction_cleanup(
                 session,
                 gas_schedule,
-                cost_strategy.remaining_gas(),
+                cost_strategy,
                 txn_data,
                 account_currency_symbol,
-            )
+            )?;
+            diagnostics.phase_times.epilogue = start.elapsed();
+            diagnostics.gas_attribution = Some(GasAttribution {
+                prologue: prologue_gas,
+                execution: gas_usage - prologue_gas,
+                epilogue: output.gas_used() - gas_usage,
+            });
+            Ok(output)
         }
     }
 
@@ -181,23 +1364,27 @@ impl LibraVM {
         txn_data: &TransactionMetadata,
         module: &Module,
         account_currency_symbol: &IdentStr,
+        diagnostics: &mut TransactionDiagnostics,
     ) -> Result<TransactionOutput, VMStatus> {
-        let gas_schedule = self.0.get_gas_schedule()?;
-        let mut session = self.0.new_session(remote_cache);
+        let gas_schedule = self.inner.get_gas_schedule()?;
+        let mut session = self.inner.new_session(remote_cache);
 
         // Run validation logic
         cost_strategy.disable_metering();
-        self.0.check_gas(txn_data)?;
-        self.0.is_allowed_module(txn_data, remote_cache)?;
-        self.0.run_prologue(
+        self.check_transaction_size(txn_data)?;
+        self.inner.check_gas(txn_data)?;
+        self.inner.is_allowed_module(txn_data, remote_cache)?;
+        let start = Instant::now();
+        self.inner.run_prologue(
             &mut session,
             cost_strategy,
             txn_data,
             account_currency_symbol,
         )?;
+        diagnostics.phase_times.prologue = start.elapsed();
 
         // Publish the module
-        let module_address = if self.0.on_chain_config()?.publishing_option.is_open() {
+        let module_address = if self.inner.on_chain_config()?.publishing_option.is_open() {
             txn_data.sender()
         } else {
             account_config::CORE_CODE_ADDRESS
@@ -207,24 +1394,245 @@ impl LibraVM {
         cost_strategy
             .charge_intrinsic_gas(txn_data.transaction_size())
             .map_err(|e| e.into_vm_status())?;
+        let dependency_depth = session
+            .module_dependency_depth(module.code())
+            .map_err(|e| e.into_vm_status())?;
+        if dependency_depth > self.config.max_module_dependency_depth {
+            return Err(VMStatus::new(
+                StatusCode::MAX_DEPENDENCY_DEPTH_REACHED,
+                None,
+                None,
+            ));
+        }
         session
             .publish_module(module.code().to_vec(), module_address, cost_strategy)
             .map_err(|e| e.into_vm_status())?;
+        // The module deserialized when it was published, and its address was checked to be
+        // `module_address`.
+        let module_id = CompiledModule::deserialize(module.code())
+            .map_err(|e| e.finish(Location::Undefined).into_vm_status())?
+            .self_id();
+
+        let start = Instant::now();
+        let output = self.success_transaction_cleanup(
+            session,
+            gas_schedule,
+            cost_strategy,
+            txn_data,
+            account_currency_symbol,
+        )?;
+        diagnostics.phase_times.epilogue = start.elapsed();
+        diagnostics.published_modules.push(module_id);
+        Ok(output)
+    }
+
+    fn execute_user_transaction(
+        &mut self,
+        _state_view: &dyn StateView,
+        remote_cache: &StateViewCache<'_>,
+        txn: &SignatureCheckedTransaction,
+        diagnostics: &mut TransactionDiagnostics,
+    ) -> TransactionOutput {
+        #[cfg(feature = "chaos")]
+        self.inject_latency();
+
+        // Drop what was read before the transaction, e.g. the on-chain configs.
+        remote_cache.take_witness();
+        let output = self.execute_user_transaction_with_metadata(
+            remote_cache,
+            txn,
+            self.user_transaction_metadata(txn),
+            txn.gas_currency_code(),
+            diagnostics,
+        );
+        diagnostics.state_witness = remote_cache.take_witness();
+        #[cfg(feature = "determinism-audit")]
+        self.audit_determinism(remote_cache, txn, &output);
+        output
+    }
+
+    /// Executes `txn` again against the same cache and panics if the output differs from
+    /// `output`. The cache is not modified by executing a transaction, so any difference comes
+    /// from state kept elsewhere, e.g. in the loader, or from non-deterministic code such as an
+    /// iteration over a `HashMap` feeding the write set.
+    #[cfg(feature = "determinism-audit")]
+    fn audit_determinism(
+        &mut self,
+        remote_cache: &StateViewCache<'_>,
+        txn: &SignatureCheckedTransaction,
+        output: &TransactionOutput,
+    ) {
+        let replayed = self.execute_user_transaction_with_metadata(
+            remote_cache,
+            txn,
+            self.user_transaction_metadata(txn),
+            txn.gas_currency_code(),
+            &mut TransactionDiagnostics::default(),
+        );
+        assert_eq!(
+            output,
+            &replayed,
+            "non-deterministic execution of the transaction of {} with sequence number {}",
+            txn.sender(),
+            txn.sequence_number(),
+        );
+        self.audited_transactions += 1;
+    }
+
+    /// The metadata of a user transaction, with its gas unit price zeroed if its fee is waived
+    /// by `ExecutorConfig::first_transaction_gas_grace`.
+    fn user_transaction_metadata(&self, txn: &SignedTransaction) -> TransactionMetadata {
+        let mut txn_data = TransactionMetadata::new(txn);
+        if self.waives_fee(txn_data.sequence_number()) {
+            txn_data.gas_unit_price = GasPrice::new(0);
+        }
+        txn_data
+    }
+
+    /// Whether `ExecutorConfig::first_transaction_gas_grace` waives the fee of a transaction run
+    /// with `sequence_number`.
+    fn waives_fee(&self, sequence_number: u64) -> bool {
+        self.config.first_transaction_gas_grace && sequence_number == 0
+    }
+
+    /// Checks the size of a transaction against `ExecutorConfig::max_transaction_size_in_bytes`.
+    fn check_transaction_size(&self, txn_data: &TransactionMetadata) -> Result<(), VMStatus> {
+        let transaction_size = txn_data.transaction_size().get();
+        match self.config.max_transaction_size_in_bytes {
+            Some(max_size) if transaction_size > max_size => Err(VMStatus::new(
+                StatusCode::EXCEEDED_MAX_TRANSACTION_SIZE,
+                None,
+                Some(format!(
+                    "max size: {}, txn size: {}",
+                    max_size, transaction_size
+                )),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// The arguments `script` runs with, rewritten by the argument preprocessor if there is one.
+    fn script_args(&self, script: &Script) -> Vec<Value> {
+        match &self.argument_preprocessor {
+            Some(preprocessor) => convert_txn_args(&preprocessor.preprocess(script.args())),
+            None => convert_txn_args(script.args()),
+        }
+    }
+
+    /// Converts the error of a script to its status, with the message of the abort message
+    /// resolver appended if the script aborted in a module.
+    fn script_error_status(&self, err: VMError, state: &dyn StateView) -> VMStatus {
+        let message = match (
+            &self.abort_message_resolver,
+            err.location(),
+            err.sub_status(),
+        ) {
+            (Some(resolver), Location::Module(module), Some(abort_code))
+                if err.major_status() == StatusCode::ABORTED =>
+            {
+                resolver.resolve(module, abort_code, state)
+            }
+            _ => None,
+        };
+        let status = err.into_vm_status();
+        match message {
+            Some(message) => status.with_context(message),
+            None => status,
+        }
+    }
+
+    /// Charges `global_memory_per_byte_write_cost` of the on-chain gas schedule for every byte
+    /// written by `session` so far, if the on-chain `StorageGasConfig` asks for it. This runs
+    /// before the epilogue, whose own writes are not charged. Fails with `OUT_OF_GAS` if that is
+    /// more than the gas left, and the transaction is then charged all its gas.
+    fn charge_write_set_storage_gas<R: RemoteCache>(
+        &self,
+        session: &Session<R>,
+        gas_schedule: &CostTable,
+        cost_strategy: &mut CostStrategy,
+    ) -> Result<(), VMStatus> {
+        if !self.inner.charges_written_bytes() {
+            return Ok(());
+        }
+        let written_bytes = session.written_bytes().map_err(|e| e.into_vm_status())?;
+        let storage_gas = gas_schedule
+            .gas_constants
+            .global_memory_per_byte_write_cost
+            .get()
+            .saturating_mul(written_bytes);
+        cost_strategy.enable_metering();
+        let result = cost_strategy
+            .deduct_gas(GasUnits::new(storage_gas))
+            .map_err(|e| e.finish(Location::Undefined).into_vm_status());
+        cost_strategy.disable_metering();
+        result
+    }
+
+    /// Checks the resources written by a session against `ExecutorConfig::max_resource_writes`.
+    fn check_resource_writes<R: RemoteCache>(&self, session: &Session<R>) -> Result<(), VMStatus> {
+        let max_writes = match self.config.max_resource_writes {
+            Some(max_writes) => max_writes,
+            None => return Ok(()),
+        };
+        match session
+            .most_written_resource()
+            .map_err(|e| e.into_vm_status())?
+        {
+            Some((address, type_tag, writes)) if writes > max_writes => Err(VMStatus::new(
+                StatusCode::MAX_RESOURCE_WRITES_REACHED,
+                None,
+                Some(format!(
+                    "max writes: {}, {} writes to {} under {}",
+                    max_writes, writes, type_tag, address
+                )),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks the events emitted by a session against the `max_events_per_transaction` of the
+    /// on-chain `TransactionLimits`, if the chain published them. The events of the epilogue are
+    /// not counted.
+    fn check_event_count<R: RemoteCache>(&self, session: &Session<R>) -> Result<(), VMStatus> {
+        match self
+            .inner
+            .transaction_limits()
+            .map(|limits| limits.max_events_per_transaction)
+        {
+            Some(max_events) if session.num_events() as u64 > max_events => Err(VMStatus::new(
+                StatusCode::MAX_EVENTS_REACHED,
+                None,
+                Some(format!(
+                    "max events: {}, {} events emitted",
+                    max_events,
+                    session.num_events()
+                )),
+            )),
+            _ => Ok(()),
+        }
+    }
 
-        self.success_transaction_cleanup(
-            session,
-            gas_schedule,
-            cost_strategy.remaining_gas(),
-            txn_data,
-            account_currency_symbol,
-        )
+    fn resolve_gas_currency(&self, gas_currency_code: &str) -> Result<Identifier, VMStatus> {
+        if let Some(currency) = self
+            .gas_currency_resolver
+            .as_ref()
+            .and_then(|resolver| resolver.resolve(gas_currency_code))
+        {
+            return Ok(currency);
+        }
+        account_config::from_currency_code_string(gas_currency_code)
+            .map_err(|_| VMStatus::new(StatusCode::INVALID_GAS_SPECIFIER, None, None))
     }
 
-    fn execute_user_transaction(
-        &mut self,
-        _state_view: &dyn StateView,
+    /// Executes `txn` with `txn_data` passed to the prologue and epilogue instead of its own
+    /// metadata, paying for gas in `gas_currency_code`.
+    fn execute_user_transaction_with_metadata(
+        &self,
         remote_cache: &StateViewCache<'_>,
         txn: &SignatureCheckedTransaction,
+        txn_data: TransactionMetadata,
+        gas_currency_code: &str,
+        diagnostics: &mut TransactionDiagnostics,
     ) -> TransactionOutput {
         macro_rules! unwrap_or_discard {
             ($res: expr) => {
@@ -235,13 +1643,14 @@ impl LibraVM {
             };
         }
 
-        let gas_schedule = unwrap_or_discard!(self.0.get_gas_schedule());
-        let txn_data = TransactionMetadata::new(txn);
+        let gas_schedule = unwrap_or_discard!(self.inner.get_gas_schedule());
         let mut cost_strategy = CostStrategy::system(gas_schedule, txn_data.max_gas_amount());
-        let account_currency_symbol = unwrap_or_discard!(
-            account_config::from_currency_code_string(txn.gas_currency_code())
-                .map_err(|_| VMStatus::new(StatusCode::INVALID_GAS_SPECIFIER, None, None))
-        );
+        let account_currency_symbol =
+            unwrap_or_discard!(self.resolve_gas_currency(gas_currency_code));
+        let start = Instant::now();
+        // Deliberately exhaustive, without a catch-all: a new payload type must be given explicit
+        // handling here before it compiles. A node running an older VM never gets to see a newer
+        // payload type, as a transaction using it fails to deserialize before reaching the VM.
         let result = match txn.payload() {
             TransactionPayload::Script(s) => self.execute_script(
                 remote_cache,
@@ -249,6 +1658,7 @@ impl LibraVM {
                 &txn_data,
                 s,
                 account_currency_symbol.as_ident_str(),
+                diagnostics,
             ),
             TransactionPayload::Module(m) => self.execute_module(
                 remote_cache,
@@ -256,11 +1666,17 @@ impl LibraVM {
                 &txn_data,
                 m,
                 account_currency_symbol.as_ident_str(),
+                diagnostics,
             ),
             TransactionPayload::WriteSet(_) => {
                 return discard_error_output(VMStatus::new(StatusCode::UNREACHABLE, None, None))
             }
         };
+        let phase_times = &mut diagnostics.phase_times;
+        phase_times.execution = start
+            .elapsed()
+            .checked_sub(phase_times.prologue + phase_times.epilogue)
+            .unwrap_or_default();
 
         match result {
             Ok(output) => output,
@@ -269,14 +1685,21 @@ impl LibraVM {
                 if txn_status.is_discarded() {
                     discard_error_output(err)
                 } else {
-                    self.failed_transaction_cleanup(
+                    diagnostics.gas_left_at_failure = Some(cost_strategy.remaining_gas().get());
+                    let start = Instant::now();
+                    let output = self.failed_transaction_cleanup(
                         err,
                         gas_schedule,
                         cost_strategy.remaining_gas(),
                         &txn_data,
                         remote_cache,
                         account_currency_symbol.as_ident_str(),
-                    )
+                    );
+                    diagnostics.phase_times.epilogue = start.elapsed();
+                    if output.status().is_discarded() {
+                        diagnostics.pre_failure_events.clear();
+                    }
+                    output
                 }
             }
         }
@@ -305,7 +1728,7 @@ impl LibraVM {
         let (write_set, events) = change_set.into_inner();
         self.read_writeset(remote_cache, &write_set)?;
         remote_cache.push_write_set(&write_set);
-        self.0.load_configs_impl(remote_cache);
+        self.inner.load_configs_impl(remote_cache);
         Ok(TransactionOutput::new(
             write_set,
             events,
@@ -326,24 +1749,45 @@ impl LibraVM {
         //    might be useful here.
         // 3. We set the max gas to a big number just to get rid of the potential out of gas error.
         let mut txn_data = TransactionMetadata::default();
-        txn_data.sender = account_config::reserved_vm_address();
+        txn_data.sender = self.config.reserved_vm_address;
         txn_data.max_gas_amount = GasUnits::new(std::u64::MAX);
 
-        let gas_schedule = zero_cost_schedule();
-        let mut cost_strategy = CostStrategy::transaction(&gas_schedule, txn_data.max_gas_amount());
+        let zero_cost = zero_cost_schedule();
+        let mut metered = false;
+        let gas_schedule = if self.config.meter_block_prologue {
+            self.inner.load_configs_impl(remote_cache);
+            match self.inner.get_gas_schedule() {
+                Ok(gas_schedule) => {
+                    metered = true;
+                    gas_schedule
+                }
+                Err(err) => {
+                    warn!(
+                        "[VM] Failed to load the gas schedule to meter the block prologue: {:?}",
+                        err
+                    );
+                    &zero_cost
+                }
+            }
+        } else {
+            &zero_cost
+        };
+        let mut cost_strategy = CostStrategy::transaction(gas_schedule, txn_data.max_gas_amount());
         cost_strategy
             .charge_intrinsic_gas(txn_data.transaction_size())
             .map_err(|e| e.into_vm_status())?;
-        let mut session = self.0.new_session(remote_cache);
+        let mut session = self.inner.new_session(remote_cache);
 
         if let Ok((round, timestamp, previous_vote, proposer)) = block_metadata.into_inner() {
-            let args = vec![
-                Value::transaction_argument_signer_reference(txn_data.sender),
-                Value::u64(round),
-                Value::u64(timestamp),
-                Value::vector_address(previous_vote),
-                Value::address(proposer),
-            ];
+            #[cfg(feature = "timestamp-override")]
+            let timestamp = self.config.timestamp_override.unwrap_or(timestamp);
+            let args = (self.config.block_prologue_args.build)(BlockPrologueInputs {
+                vm_address: txn_data.sender,
+                round,
+                timestamp_usecs: timestamp,
+                previous_block_votes: previous_vote,
+                proposer,
+            });
             session
                 .execute_function(
                     &LIBRA_BLOCK_MODULE,
@@ -364,8 +1808,12 @@ impl LibraVM {
             &cost_strategy,
             &txn_data,
             VMStatus::executed(),
+            self.config.canonical_event_order,
         )
         .map(|output| {
+            if metered {
+                BLOCK_PROLOGUE_GAS_USAGE.observe(output.gas_used() as f64);
+            }
             remote_cache.push_write_set(output.write_set());
             output
         })
@@ -378,13 +1826,7 @@ impl LibraVM {
     ) -> Result<TransactionOutput, VMStatus> {
         let txn = match txn.check_signature() {
             Ok(t) => t,
-            _ => {
-                return Ok(discard_error_output(VMStatus::new(
-                    StatusCode::INVALID_SIGNATURE,
-                    None,
-                    None,
-                )))
-            }
+            Err(err) => return Ok(discard_error_output(invalid_signature_status(&err))),
         };
 
         let change_set = if let TransactionPayload::WriteSet(change_set) = txn.payload() {
@@ -400,9 +1842,9 @@ impl LibraVM {
 
         let txn_data = TransactionMetadata::new(&txn);
 
-        let mut session = self.0.new_session(remote_cache);
+        let mut session = self.inner.new_session(remote_cache);
 
-        if let Err(e) = self.0.run_writeset_prologue(&mut session, &txn_data) {
+        if let Err(e) = self.inner.run_writeset_prologue(&mut session, &txn_data) {
             return Ok(discard_error_output(e));
         };
 
@@ -423,12 +1865,57 @@ impl LibraVM {
             )
             .map_err(|e| e.into_vm_status())?;
 
+        let (output, epilogue_writeset) =
+            self.write_set_output(remote_cache, session, change_set, &txn_data)?;
+        self.record_write_set_origins(&txn_data, change_set, epilogue_writeset);
+        Ok(output)
+    }
+
+    /// Applies `change_set` on top of `remote_cache` like a write set transaction sent by the
+    /// Libra root account, but without a signature to check nor a sequence number to bump, e.g.
+    /// for a genesis change set produced internally on top of a framework. The write set
+    /// epilogue still runs, and the write set still has to be read and be disjoint from the
+    /// epilogue's. The output is pushed to `remote_cache` and the on-chain configs are reloaded
+    /// from it. The first genesis, on top of an empty state, has no write set manager to run the
+    /// epilogue: it is applied as a `Transaction::WaypointWriteSet`.
+    pub fn process_genesis_write_set(
+        &mut self,
+        remote_cache: &mut StateViewCache<'_>,
+        change_set: ChangeSet,
+    ) -> Result<TransactionOutput, VMStatus> {
+        let txn_data = TransactionMetadata {
+            sender: account_config::libra_root_address(),
+            ..TransactionMetadata::default()
+        };
+        let (output, epilogue_writeset) = {
+            let session = self.inner.new_session(remote_cache);
+            self.write_set_output(remote_cache, session, &change_set, &txn_data)?
+        };
+        self.record_write_set_origins(&txn_data, &change_set, epilogue_writeset);
+        if !output.status().is_discarded() {
+            remote_cache.push_write_set(output.write_set());
+            self.inner.load_configs_impl(remote_cache);
+        }
+        Ok(output)
+    }
+
+    /// Runs the write set epilogue for `change_set` in `session` and returns the output of the
+    /// write set transaction: the epilogue's writes and events merged with the ones of
+    /// `change_set`, which must not overlap. The epilogue's write set is returned too, unless the
+    /// output is discarded.
+    fn write_set_output<R: RemoteCache>(
+        &self,
+        remote_cache: &StateViewCache<'_>,
+        mut session: Session<R>,
+        change_set: &ChangeSet,
+        txn_data: &TransactionMetadata,
+    ) -> Result<(TransactionOutput, Option<WriteSet>), VMStatus> {
         // Emit the reconfiguration event
-        self.0
-            .run_writeset_epilogue(&mut session, change_set, &txn_data)?;
+        self.inner
+            .run_writeset_epilogue(&mut session, change_set, txn_data)?;
 
         if let Err(e) = self.read_writeset(remote_cache, &change_set.write_set()) {
-            return Ok(discard_error_output(e));
+            return Ok((discard_error_output(e), None));
         };
 
         let effects = session.finish().map_err(|e| e.into_vm_status())?;
@@ -448,29 +1935,21 @@ impl LibraVM {
                     .collect::<HashSet<_>>(),
             )
         {
-            return Ok(discard_error_output(VMStatus::new(
-                StatusCode::INVALID_WRITE_SET,
-                None,
+            return Ok((
+                discard_error_output(VMStatus::new(StatusCode::INVALID_WRITE_SET, None, None)),
                 None,
-            )));
+            ));
         }
-        if !epilogue_events
+        let epilogue_events_by_key = events_by_key(&epilogue_events);
+        if change_set
+            .events()
             .iter()
-            .map(|event| event.key())
-            .collect::<HashSet<_>>()
-            .is_disjoint(
-                &change_set
-                    .events()
-                    .iter()
-                    .map(|event| event.key())
-                    .collect::<HashSet<_>>(),
-            )
+            .any(|event| epilogue_events_by_key.contains_key(event.key()))
         {
-            return Ok(discard_error_output(VMStatus::new(
-                StatusCode::INVALID_WRITE_SET,
-                None,
+            return Ok((
+                discard_error_output(VMStatus::new(StatusCode::INVALID_WRITE_SET, None, None)),
                 None,
-            )));
+            ));
         }
 
         let write_set = WriteSetMut::new(
@@ -489,26 +1968,106 @@ impl LibraVM {
             .cloned()
             .collect();
 
-        Ok(TransactionOutput::new(
-            write_set,
-            events,
-            0,
-            TransactionStatus::Keep(VMStatus::executed()),
+        Ok((
+            TransactionOutput::new(
+                write_set,
+                events,
+                0,
+                TransactionStatus::Keep(VMStatus::executed()),
+            ),
+            Some(epilogue_writeset),
         ))
     }
 
+    /// Records where the writes of a kept write set transaction come from, with
+    /// `ExecutorConfig::record_write_set_origins`.
+    fn record_write_set_origins(
+        &mut self,
+        txn_data: &TransactionMetadata,
+        change_set: &ChangeSet,
+        epilogue_writeset: Option<WriteSet>,
+    ) {
+        if !self.config.record_write_set_origins {
+            return;
+        }
+        if let Some(epilogue) = epilogue_writeset {
+            self.write_set_origins.push(WriteSetOrigins {
+                sender: txn_data.sender(),
+                sequence_number: txn_data.sequence_number(),
+                payload: change_set.write_set().clone(),
+                epilogue,
+            });
+        }
+    }
+
     fn execute_block_impl(
         &mut self,
         transactions: Vec<Transaction>,
         state_view: &dyn StateView,
     ) -> Result<Vec<TransactionOutput>, VMStatus> {
+        self.execute_block_impl_with_hooks(
+            transactions,
+            state_view,
+            BlockOptions::default(),
+            |_| (),
+            |_, _| (),
+        )
+    }
+
+    fn execute_block_impl_with_hooks<Pre, Post>(
+        &mut self,
+        transactions: Vec<Transaction>,
+        state_view: &dyn StateView,
+        mut options: BlockOptions<'_>,
+        pre_block: Pre,
+        post_block: Post,
+    ) -> Result<Vec<TransactionOutput>, VMStatus>
+    where
+        Pre: FnOnce(&StateViewCache<'_>),
+        Post: FnOnce(&StateViewCache<'_>, &[TransactionOutput]),
+    {
         let count = transactions.len();
         let mut result = vec![];
-        let blocks = chunk_block_transactions(transactions);
+        let mut summary = BlockSummary::default();
+        let blocks = chunk_block_transactions_iter(transactions.into_iter());
         let mut data_cache = StateViewCache::new(state_view);
+        if let Some(storage_gas_model) = &self.config.storage_gas_model {
+            data_cache.set_storage_gas_model(storage_gas_model.clone());
+        }
+        if self.config.record_state_witness {
+            data_cache.record_witness();
+        }
+        pre_block(&data_cache);
         let mut execute_block_trace_guard = vec![];
         let mut current_block_id = HashValue::zero();
         for block in blocks {
+            if let Some(deadline) = options.deadline.as_mut() {
+                let expected = match &block {
+                    TransactionBlock::UserTransaction(_) => Duration::from_secs(0),
+                    TransactionBlock::BlockPrologue(_) => {
+                        self.expected_duration(TransactionKind::BlockPrologue)
+                    }
+                    TransactionBlock::WaypointWriteSet(_) => {
+                        self.expected_duration(TransactionKind::WaypointWriteSet)
+                    }
+                    TransactionBlock::WriteSet(_) => {
+                        self.expected_duration(TransactionKind::WriteSet)
+                    }
+                };
+                if !deadline.admit(expected) {
+                    let retried = match &block {
+                        TransactionBlock::UserTransaction(txns) => txns.len(),
+                        _ => 1,
+                    };
+                    for _ in 0..retried {
+                        let output = retry_output();
+                        self.record_diagnostics(TransactionDiagnostics::default());
+                        summary.record(&output);
+                        result.push(output);
+                    }
+                    continue;
+                }
+            }
             match block {
                 TransactionBlock::UserTransaction(txns) => {
                     let mut outs = self.execute_user_transactions(
@@ -516,6 +2075,8 @@ impl LibraVM {
                         txns,
                         &mut data_cache,
                         state_view,
+                        &mut summary,
+                        &mut options,
                     )?;
                     result.append(&mut outs);
                 }
@@ -523,14 +2084,49 @@ impl LibraVM {
                     execute_block_trace_guard.clear();
                     current_block_id = block_metadata.id();
                     trace_code_block!("libra_vm::execute_block_impl", {"block", current_block_id}, execute_block_trace_guard);
-                    result.push(self.process_block_prologue(&mut data_cache, block_metadata)?)
+                    let start = Instant::now();
+                    let output = match self.process_block_prologue(&mut data_cache, block_metadata)
+                    {
+                        Ok(output) => output,
+                        Err(status) if options.lenient_block_prologue => {
+                            discard_error_output(status)
+                        }
+                        Err(status) => return Err(status),
+                    };
+                    self.observe_write_set(&output);
+                    let output = self.route_events(output);
+                    summary.record(&output);
+                    result.push(output);
+                    self.record_block_time(TransactionKind::BlockPrologue, start.elapsed());
+                    self.record_diagnostics(TransactionDiagnostics::default());
+                }
+                TransactionBlock::WaypointWriteSet(change_set) => {
+                    let start = Instant::now();
+                    let output = self
+                        .process_waypoint_change_set(&mut data_cache, change_set)
+                        .unwrap_or_else(discard_error_output);
+                    options.check_discard(&output)?;
+                    self.observe_write_set(&output);
+                    let output = self.route_events(output);
+                    summary.record(&output);
+                    result.push(output);
+                    self.record_block_time(TransactionKind::WaypointWriteSet, start.elapsed());
+                    self.record_diagnostics(TransactionDiagnostics::default());
                 }
-                TransactionBlock::WaypointWriteSet(change_set) => result.push(
-                    self.process_waypoint_change_set(&mut data_cache, change_set)
-                        .unwrap_or_else(discard_error_output),
-                ),
                 TransactionBlock::WriteSet(txn) => {
-                    result.push(self.process_writeset_transaction(&mut data_cache, *txn)?)
+                    let start = Instant::now();
+                    let label = payload_label(txn.payload());
+                    let output = self.process_writeset_transaction(&mut data_cache, *txn)?;
+                    TXN_TOTAL_SECONDS_BY_PAYLOAD
+                        .with_label_values(&[label])
+                        .observe(start.elapsed().as_secs_f64());
+                    options.check_discard(&output)?;
+                    self.observe_write_set(&output);
+                    let output = self.route_events(output);
+                    summary.record(&output);
+                    result.push(output);
+                    self.record_block_time(TransactionKind::WriteSet, start.elapsed());
+                    self.record_diagnostics(TransactionDiagnostics::default());
                 }
             }
         }
@@ -541,6 +2137,8 @@ impl LibraVM {
             Err(_) => BLOCK_TRANSACTION_COUNT.set(std::i64::MAX),
         }
 
+        self.block_summary = summary;
+        post_block(&data_cache, &result);
         Ok(result)
     }
 
@@ -550,8 +2148,25 @@ impl LibraVM {
         txn_block: Vec<SignedTransaction>,
         data_cache: &mut StateViewCache<'_>,
         state_view: &dyn StateView,
+        summary: &mut BlockSummary,
+        options: &mut BlockOptions<'_>,
     ) -> Result<Vec<TransactionOutput>, VMStatus> {
-        self.0.load_configs_impl(data_cache);
+        let start = Instant::now();
+        self.inner.load_configs_impl(data_cache);
+        if let Some(profile) = self.profile.as_mut() {
+            profile.record(&["config_load"], start.elapsed());
+        }
+        self.loaded_config_epoch = self.config_epoch;
+        if self.config.prefetch {
+            trace_code_block!("libra_vm::prefetch", {"block", block_id});
+            // Prefetching is an optimization only, the transactions read the data on a failure.
+            if let Err(err) = data_cache.prefetch(&derive_prefetch_hints(
+                &txn_block,
+                &self.config.account_layout,
+            )) {
+                warn!("[VM] Failed to prefetch data for block: {:?}", err);
+            }
+        }
         let signature_verified_block: Vec<Result<SignatureCheckedTransaction, VMStatus>>;
         {
             trace_code_block!("libra_vm::verify_signatures", {"block", block_id});
@@ -559,42 +2174,479 @@ impl LibraVM {
                 .into_par_iter()
                 .map(|txn| {
                     txn.check_signature()
-                        .map_err(|_| VMStatus::new(StatusCode::INVALID_SIGNATURE, None, None))
+                        .map_err(|err| invalid_signature_status(&err))
                 })
                 .collect();
         }
-        let mut result = vec![];
+        // Only needed to profile the transactions.
+        let kinds: Vec<_> = if self.profile.is_some() {
+            signature_verified_block
+                .iter()
+                .map(|txn| {
+                    txn.as_ref()
+                        .ok()
+                        .map(|txn| TransactionKind::of_payload(txn.payload()))
+                })
+                .collect()
+        } else {
+            vec![]
+        };
+        let (speculations, mut conflict_tracker) = match options.parallel_view {
+            Some(view) => {
+                trace_code_block!("libra_vm::speculate_transactions", {"block", block_id});
+                (
+                    self.speculate(view, data_cache, &signature_verified_block),
+                    Some(ConflictTracker::default()),
+                )
+            }
+            None => (
+                signature_verified_block.iter().map(|_| None).collect(),
+                None,
+            ),
+        };
+        // The output and diagnostics of each transaction, in block order. A transaction whose
+        // speculative output conflicts with the outputs committed before it is executed again
+        // right away, so every later speculation is checked against the final writes of all the
+        // transactions before it.
+        let mut slots = vec![];
         trace_code_block!("libra_vm::execute_transactions", {"block", block_id});
-        for transaction in signature_verified_block {
-            let output = match transaction {
-                Ok(txn) => {
-                    let _timer = TXN_TOTAL_SECONDS.start_timer();
-                    self.execute_user_transaction(state_view, data_cache, &txn)
+        let mut transactions = signature_verified_block
+            .into_iter()
+            .zip(speculations)
+            .enumerate()
+            .peekable();
+        while let Some((index, (transaction, speculation))) = transactions.next() {
+            let (speculation, stale) = match speculation {
+                Some(speculation) if is_stale(conflict_tracker.as_ref(), &speculation) => {
+                    (None, true)
                 }
-                Err(e) => discard_error_output(e),
+                speculation => (speculation, false),
             };
-
-            if !output.status().is_discarded() {
-                data_cache.push_write_set(output.write_set());
-            }
-
-            // Increment the counter for transactions executed.
-            let counter_label = match output.status() {
-                TransactionStatus::Keep(_) => Some("success"),
-                TransactionStatus::Discard(_) => Some("discarded"),
-                TransactionStatus::Retry => None,
+            let transaction = match (transaction, options.conflict_resolver) {
+                (Ok(txn), Some(resolver)) if stale => {
+                    let mut conflicts = vec![(index, txn)];
+                    while let Some((_, (Ok(_), Some(speculation)))) = transactions.peek() {
+                        if !is_stale(conflict_tracker.as_ref(), speculation) {
+                            break;
+                        }
+                        if let Some((index, (Ok(txn), _))) = transactions.next() {
+                            conflicts.push((index, txn));
+                        }
+                    }
+                    slots.extend(self.execute_conflicts(
+                        state_view,
+                        data_cache,
+                        conflicts,
+                        resolver,
+                        options,
+                        conflict_tracker.as_mut(),
+                    )?);
+                    continue;
+                }
+                (transaction, _) => transaction,
             };
-            if let Some(label) = counter_label {
-                TRANSACTIONS_EXECUTED.with_label_values(&[label]).inc();
+            if !self.admit_transaction(options, transaction.as_ref().ok()) {
+                slots.push((retry_output(), TransactionDiagnostics::default()));
+                continue;
             }
+            let slot = match transaction {
+                Ok(txn) => match speculation {
+                    Some(speculation) => self.commit_speculation(
+                        &txn,
+                        speculation,
+                        data_cache,
+                        conflict_tracker.as_mut(),
+                    ),
+                    None => self.execute_and_commit_user_transaction(
+                        state_view,
+                        data_cache,
+                        &txn,
+                        conflict_tracker.as_mut(),
+                    ),
+                },
+                Err(e) => (
+                    self.commit_user_output(discard_error_output(e), data_cache, None),
+                    TransactionDiagnostics::default(),
+                ),
+            };
+            options.check_discard(&slot.0)?;
+            options.block_gas_used = options.block_gas_used.saturating_add(slot.0.gas_used());
+            slots.push(slot);
+        }
 
+        let mut result = vec![];
+        for (index, (output, diagnostics)) in slots.into_iter().enumerate() {
+            if let (Some(profile), Some(Some(kind))) = (self.profile.as_mut(), kinds.get(index)) {
+                profile.record_transaction(*kind, &diagnostics.phase_times);
+            }
+            self.record_diagnostics(diagnostics);
             // `result` is initially empty, a single element is pushed per loop iteration and
             // the number of iterations is bound to the max size of `signature_verified_block`
             assume!(result.len() < usize::max_value());
+            summary.record(&output);
             result.push(output);
         }
         Ok(result)
     }
+
+    /// Returns whether `transaction` is executed, rather than retried in a later block because
+    /// the block ran out of time or gas.
+    fn admit_transaction(
+        &self,
+        options: &mut BlockOptions<'_>,
+        txn: Option<&SignatureCheckedTransaction>,
+    ) -> bool {
+        if let Some(deadline) = options.deadline.as_mut() {
+            let expected = match txn {
+                Some(txn) => self.expected_duration(TransactionKind::of_payload(txn.payload())),
+                None => Duration::from_secs(0),
+            };
+            if !deadline.admit(expected) {
+                return false;
+            }
+        }
+        !options.gas_limit_reached()
+    }
+
+    /// Executes again `conflicts`, a run of consecutive transactions whose speculative outputs
+    /// conflict with the outputs committed before them, and returns their outputs in block order.
+    ///
+    /// The transactions are first executed in parallel on the current state. If none of them
+    /// reads or writes a path written by another one, these outputs are the ones of a sequential
+    /// execution in any order, and they are committed in the order chosen by `resolver`.
+    /// Otherwise, or if `resolver` doesn't return a permutation of the run, they are executed
+    /// again one after the other, in block order.
+    fn execute_conflicts(
+        &mut self,
+        state_view: &dyn StateView,
+        data_cache: &mut StateViewCache<'_>,
+        conflicts: Vec<(usize, SignatureCheckedTransaction)>,
+        resolver: &dyn ConflictResolver,
+        options: &mut BlockOptions<'_>,
+        mut conflict_tracker: Option<&mut ConflictTracker>,
+    ) -> Result<Vec<(TransactionOutput, TransactionDiagnostics)>, VMStatus> {
+        let indices: Vec<_> = conflicts.iter().map(|(index, _)| *index).collect();
+        let mut speculations: Vec<Option<Speculation>> = match options.parallel_view {
+            Some(view) if conflicts.len() > 1 => {
+                let snapshot = SnapshotView::new(view, data_cache.overlay());
+                let this = &*self;
+                let speculations: Vec<_> = conflicts
+                    .par_iter()
+                    .map(|(_, txn)| this.speculate_transaction(&snapshot, txn))
+                    .collect();
+                let reads: Vec<_> = speculations.iter().map(|s| &s.reads).collect();
+                let writes: Vec<BTreeSet<_>> = speculations
+                    .iter()
+                    .map(|s| {
+                        s.output
+                            .write_set()
+                            .iter()
+                            .map(|(access_path, _)| access_path.clone())
+                            .collect()
+                    })
+                    .collect();
+                if are_independent(&reads, &writes) {
+                    speculations.into_iter().map(Some).collect()
+                } else {
+                    vec![]
+                }
+            }
+            _ => vec![],
+        };
+        let order = match resolver.resolution_order(&indices) {
+            order if !speculations.is_empty() && is_permutation_of(&order, &indices) => order,
+            _ => indices.clone(),
+        };
+
+        let first = indices[0];
+        let mut slots: Vec<_> = conflicts.iter().map(|_| None).collect();
+        for index in order {
+            let position = index - first;
+            let txn = &conflicts[position].1;
+            let slot = if !self.admit_transaction(options, Some(txn)) {
+                (retry_output(), TransactionDiagnostics::default())
+            } else {
+                match speculations.get_mut(position).and_then(Option::take) {
+                    Some(speculation) => self.commit_speculation(
+                        txn,
+                        speculation,
+                        data_cache,
+                        conflict_tracker.as_deref_mut(),
+                    ),
+                    None => self.execute_and_commit_user_transaction(
+                        state_view,
+                        data_cache,
+                        txn,
+                        conflict_tracker.as_deref_mut(),
+                    ),
+                }
+            };
+            options.check_discard(&slot.0)?;
+            options.block_gas_used = options.block_gas_used.saturating_add(slot.0.gas_used());
+            slots[position] = Some(slot);
+        }
+        Ok(slots.into_iter().flatten().collect())
+    }
+
+    /// Commits the speculative output of `txn`, which doesn't conflict with the outputs
+    /// committed since it was executed.
+    fn commit_speculation(
+        &mut self,
+        txn: &SignatureCheckedTransaction,
+        speculation: Speculation,
+        data_cache: &mut StateViewCache<'_>,
+        conflict_tracker: Option<&mut ConflictTracker>,
+    ) -> (TransactionOutput, TransactionDiagnostics) {
+        TXN_TOTAL_SECONDS.observe(speculation.elapsed.as_secs_f64());
+        TXN_TOTAL_SECONDS_BY_PAYLOAD
+            .with_label_values(&[payload_label(txn.payload())])
+            .observe(speculation.elapsed.as_secs_f64());
+        self.execution_times.record(
+            TransactionKind::of_payload(txn.payload()),
+            speculation.elapsed,
+        );
+        self.check_config_epoch(txn);
+        let output = self.validate_write_set(txn, speculation.output);
+        (
+            self.commit_user_output(output, data_cache, conflict_tracker),
+            speculation.diagnostics,
+        )
+    }
+
+    /// Executes `txn` on top of `data_cache` and commits its output.
+    fn execute_and_commit_user_transaction(
+        &mut self,
+        state_view: &dyn StateView,
+        data_cache: &mut StateViewCache<'_>,
+        txn: &SignatureCheckedTransaction,
+        conflict_tracker: Option<&mut ConflictTracker>,
+    ) -> (TransactionOutput, TransactionDiagnostics) {
+        self.check_config_epoch(txn);
+        let mut diagnostics = TransactionDiagnostics::default();
+        let output = {
+            let _timer = TXN_TOTAL_SECONDS.start_timer();
+            let start = Instant::now();
+            let output =
+                self.execute_user_transaction(state_view, data_cache, txn, &mut diagnostics);
+            let elapsed = start.elapsed();
+            TXN_TOTAL_SECONDS_BY_PAYLOAD
+                .with_label_values(&[payload_label(txn.payload())])
+                .observe(elapsed.as_secs_f64());
+            self.execution_times
+                .record(TransactionKind::of_payload(txn.payload()), elapsed);
+            output
+        };
+        let output = self.validate_write_set(txn, output);
+        (
+            self.commit_user_output(output, data_cache, conflict_tracker),
+            diagnostics,
+        )
+    }
+
+    /// Applies the output of a user transaction to `data_cache`, unless it is discarded, and
+    /// returns it. A transaction that is not allowed to reconfigure is discarded here.
+    fn commit_user_output(
+        &mut self,
+        output: TransactionOutput,
+        data_cache: &mut StateViewCache<'_>,
+        conflict_tracker: Option<&mut ConflictTracker>,
+    ) -> TransactionOutput {
+        let output = if !self.config.allow_user_reconfiguration && is_reconfiguration(&output) {
+            discard_error_output(VMStatus::new(
+                StatusCode::UNEXPECTED_RECONFIGURATION,
+                None,
+                None,
+            ))
+        } else {
+            output
+        };
+        if !output.status().is_discarded() {
+            data_cache.push_write_set(output.write_set());
+            if let Some(tracker) = conflict_tracker {
+                tracker.record_write_set(output.write_set());
+            }
+        }
+        if self.config.stale_config_check
+            && !output.status().is_discarded()
+            && is_reconfiguration(&output)
+        {
+            self.config_epoch += 1;
+        }
+        self.observe_write_set(&output);
+        let output = self.route_events(output);
+
+        // Increment the counter for transactions executed.
+        let counter_label = match output.status() {
+            TransactionStatus::Keep(_) => Some("success"),
+            TransactionStatus::Discard(_) => Some("discarded"),
+            TransactionStatus::Retry => None,
+        };
+        if let Some(label) = counter_label {
+            TRANSACTIONS_EXECUTED.with_label_values(&[label]).inc();
+        }
+        output
+    }
+
+    /// Warns if `txn` executes against on-chain configs loaded before a reconfiguration, i.e. if
+    /// they were not reloaded when they should have been.
+    fn check_config_epoch(&mut self, txn: &SignatureCheckedTransaction) {
+        if self.config.stale_config_check && self.loaded_config_epoch != self.config_epoch {
+            warn!(
+                "[VM] Transaction {}:{} executed against stale on-chain configs",
+                txn.sender(),
+                txn.sequence_number()
+            );
+            self.stale_config_transactions
+                .push((txn.sender(), txn.sequence_number()));
+        }
+    }
+
+    /// Discards `output` if the write set validator, if any, rejects its write set.
+    fn validate_write_set(
+        &self,
+        txn: &SignatureCheckedTransaction,
+        output: TransactionOutput,
+    ) -> TransactionOutput {
+        let validator = match self.write_set_validator.as_ref() {
+            Some(validator) if !output.status().is_discarded() => validator,
+            _ => return output,
+        };
+        match validator.validate(txn.sender(), output.write_set()) {
+            Ok(()) => output,
+            Err(status) => discard_error_output(status),
+        }
+    }
+
+    fn observe_write_set(&mut self, output: &TransactionOutput) {
+        if let Some(observer) = self.write_set_observer.as_mut() {
+            if !output.status().is_discarded() {
+                observer(output.write_set(), output.events());
+            }
+        }
+    }
+
+    /// Hands the events of a committed `output` over to the event sink, if any, and returns the
+    /// output without them.
+    fn route_events(&mut self, output: TransactionOutput) -> TransactionOutput {
+        let sink = match self.event_sink.as_mut() {
+            Some(sink) if !output.events().is_empty() => sink,
+            _ => return output,
+        };
+        for event in output.events() {
+            sink.accept(event.clone());
+        }
+        TransactionOutput::new(
+            output.write_set().clone(),
+            vec![],
+            output.gas_used(),
+            output.status().clone(),
+        )
+    }
+
+    /// Executes each of `txns` on top of `data_cache`, in parallel and without committing
+    /// anything, recording what the transaction read.
+    fn speculate(
+        &self,
+        view: &(dyn StateView + Sync),
+        data_cache: &StateViewCache<'_>,
+        txns: &[Result<SignatureCheckedTransaction, VMStatus>],
+    ) -> Vec<Option<Speculation>> {
+        let snapshot = SnapshotView::new(view, data_cache.overlay());
+        txns.par_iter()
+            .map(|txn| {
+                txn.as_ref()
+                    .ok()
+                    .map(|txn| self.speculate_transaction(&snapshot, txn))
+            })
+            .collect()
+    }
+
+    fn speculate_transaction(
+        &self,
+        snapshot: &SnapshotView<'_>,
+        txn: &SignatureCheckedTransaction,
+    ) -> Speculation {
+        let read_tracker = ReadTrackingView::new(snapshot);
+        let start = Instant::now();
+        let mut diagnostics = TransactionDiagnostics::default();
+        let output = {
+            let mut cache = StateViewCache::new(&read_tracker);
+            if let Some(storage_gas_model) = &self.config.storage_gas_model {
+                cache.set_storage_gas_model(storage_gas_model.clone());
+            }
+            if self.config.record_state_witness {
+                cache.record_witness();
+            }
+            let output = self.execute_user_transaction_with_metadata(
+                &cache,
+                txn,
+                self.user_transaction_metadata(txn),
+                txn.gas_currency_code(),
+                &mut diagnostics,
+            );
+            diagnostics.state_witness = cache.take_witness();
+            output
+        };
+        Speculation {
+            output,
+            diagnostics,
+            reads: read_tracker.into_reads(),
+            elapsed: start.elapsed(),
+        }
+    }
+
+    #[cfg(feature = "chaos")]
+    fn inject_latency(&mut self) {
+        let latency = self.config.chaos.transaction_latency;
+        if latency > Duration::from_secs(0) {
+            std::thread::sleep(latency);
+            self.injected_latency += latency;
+        }
+    }
+
+    /// The observed average execution time of `kind`, zero until one was observed.
+    fn expected_duration(&self, kind: TransactionKind) -> Duration {
+        self.execution_times.average(kind).unwrap_or_default()
+    }
+
+    /// Records the time taken by a transaction of the block that is not a user transaction.
+    fn record_block_time(&mut self, kind: TransactionKind, elapsed: Duration) {
+        self.execution_times.record(kind, elapsed);
+        if let Some(profile) = self.profile.as_mut() {
+            profile.record_kind(kind, elapsed);
+        }
+    }
+
+    fn record_diagnostics(&mut self, diagnostics: TransactionDiagnostics) {
+        if self.config.keep_failed_transaction_events {
+            self.failed_transaction_events
+                .push(diagnostics.pre_failure_events.clone());
+        }
+        if self.config.collect_diagnostics {
+            self.diagnostics.push(diagnostics);
+        }
+    }
+}
+
+/// Values replacing the metadata of a transaction executed outside of a block.
+#[derive(Default)]
+struct MetadataOverrides<'a> {
+    sequence_number: Option<u64>,
+    gas_unit_price: Option<u64>,
+    gas_currency_code: Option<&'a str>,
+}
+
+/// The result of executing a transaction on a snapshot of the block state.
+struct Speculation {
+    output: TransactionOutput,
+    diagnostics: TransactionDiagnostics,
+    reads: BTreeSet<AccessPath>,
+    elapsed: Duration,
+}
+
+/// Returns whether `speculation` read a value written since its snapshot was taken.
+fn is_stale(conflict_tracker: Option<&ConflictTracker>, speculation: &Speculation) -> bool {
+    conflict_tracker.map_or(true, |tracker| tracker.conflicts_with(&speculation.reads))
 }
 
 /// Transactions divided by transaction flow.
@@ -607,41 +2659,66 @@ pub enum TransactionBlock {
 }
 
 pub fn chunk_block_transactions(txns: Vec<Transaction>) -> Vec<TransactionBlock> {
-    let mut blocks = vec![];
-    let mut buf = vec![];
-    for txn in txns {
-        match txn {
-            Transaction::BlockMetadata(data) => {
-                if !buf.is_empty() {
-                    blocks.push(TransactionBlock::UserTransaction(buf));
-                    buf = vec![];
-                }
-                blocks.push(TransactionBlock::BlockPrologue(data));
-            }
-            Transaction::WaypointWriteSet(cs) => {
-                if !buf.is_empty() {
-                    blocks.push(TransactionBlock::UserTransaction(buf));
-                    buf = vec![];
-                }
-                blocks.push(TransactionBlock::WaypointWriteSet(cs));
-            }
-            Transaction::UserTransaction(txn) => {
-                if let TransactionPayload::WriteSet(_) = txn.payload() {
-                    if !buf.is_empty() {
-                        blocks.push(TransactionBlock::UserTransaction(buf));
-                        buf = vec![];
+    chunk_block_transactions_iter(txns.into_iter()).collect()
+}
+
+/// Lazy version of `chunk_block_transactions`: the transactions are only taken from `txns` as
+/// the chunks are consumed, a chunk of user transactions being yielded as soon as the transaction
+/// that ends it is taken.
+pub fn chunk_block_transactions_iter(
+    txns: impl Iterator<Item = Transaction>,
+) -> impl Iterator<Item = TransactionBlock> {
+    TransactionBlocks {
+        txns,
+        buf: vec![],
+        pending: None,
+    }
+}
+
+struct TransactionBlocks<I> {
+    txns: I,
+    /// The user transactions of the current chunk.
+    buf: Vec<SignedTransaction>,
+    /// The chunk that ended `buf`, yielded right after it.
+    pending: Option<TransactionBlock>,
+}
+
+impl<I: Iterator<Item = Transaction>> Iterator for TransactionBlocks<I> {
+    type Item = TransactionBlock;
+
+    fn next(&mut self) -> Option<TransactionBlock> {
+        if let Some(block) = self.pending.take() {
+            return Some(block);
+        }
+        for txn in &mut self.txns {
+            let block = match txn {
+                Transaction::BlockMetadata(data) => TransactionBlock::BlockPrologue(data),
+                Transaction::WaypointWriteSet(cs) => TransactionBlock::WaypointWriteSet(cs),
+                Transaction::UserTransaction(txn) => {
+                    if let TransactionPayload::WriteSet(_) = txn.payload() {
+                        TransactionBlock::WriteSet(Box::new(txn))
+                    } else {
+                        self.buf.push(txn);
+                        continue;
                     }
-                    blocks.push(TransactionBlock::WriteSet(Box::new(txn)));
-                } else {
-                    buf.push(txn);
                 }
+            };
+            if self.buf.is_empty() {
+                return Some(block);
             }
+            self.pending = Some(block);
+            return Some(TransactionBlock::UserTransaction(std::mem::take(
+                &mut self.buf,
+            )));
+        }
+        if self.buf.is_empty() {
+            None
+        } else {
+            Some(TransactionBlock::UserTransaction(std::mem::take(
+                &mut self.buf,
+            )))
         }
     }
-    if !buf.is_empty() {
-        blocks.push(TransactionBlock::UserTransaction(buf));
-    }
-    blocks
 }
 
 // Executor external API
@@ -651,6 +2728,11 @@ impl VMExecutor for LibraVM {
     /// have an empty `WriteSet`. Also `state_view` is immutable, and does not have interior
     /// mutability. Writes to be applied to the data view are encoded in the write set part of a
     /// transaction output.
+    ///
+    /// Execution is deterministic: running the same block against the same state produces the
+    /// same outputs, including the sequence numbers of the emitted events. Transactions in a block
+    /// observe the writes of the transactions before them, so events emitted on the same handle
+    /// get consecutive sequence numbers in block order.
     fn execute_block(
         transactions: Vec<Transaction>,
         state_view: &dyn StateView,
@@ -658,6 +2740,46 @@ impl VMExecutor for LibraVM {
         let mut vm = LibraVM::new();
         vm.execute_block_impl(transactions, state_view)
     }
+
+    /// Like `execute_block`, but once the user transactions of the block used `block_gas_limit`
+    /// gas units in total, the remaining user transactions are not executed and get a `Retry`
+    /// output. The transaction that reaches the limit keeps its output. Block prologues and
+    /// write sets are always executed and not counted.
+    fn execute_block_with_gas_limit(
+        transactions: Vec<Transaction>,
+        state_view: &dyn StateView,
+        block_gas_limit: u64,
+    ) -> Result<Vec<TransactionOutput>, VMStatus> {
+        LibraVM::new().execute_block_with_options(
+            transactions,
+            state_view,
+            BlockOptions {
+                block_gas_limit: Some(block_gas_limit),
+                ..BlockOptions::default()
+            },
+        )
+    }
+}
+
+/// The status of a transaction whose signature does not verify. For a multisignature transaction
+/// with signatures that do not verify, the sub status is the position in the public key of the
+/// first key whose signature fails, and the message lists all of them.
+pub(crate) fn invalid_signature_status(err: &anyhow::Error) -> VMStatus {
+    match err.downcast_ref::<InvalidSignatures>() {
+        Some(invalid) => VMStatus::new(
+            StatusCode::INVALID_SIGNATURE,
+            invalid.key_indices.first().map(|index| u64::from(*index)),
+            Some(invalid.to_string()),
+        ),
+        None => VMStatus::new(StatusCode::INVALID_SIGNATURE, None, None),
+    }
+}
+
+/// Adds the gas charged by the kept `output` of a user transaction to the per-currency counter.
+fn record_gas_used(account_currency_symbol: &IdentStr, output: &TransactionOutput) {
+    TXN_GAS_USED_BY_CURRENCY
+        .with_label_values(&[account_currency_symbol.as_str()])
+        .inc_by(output.gas_used() as i64);
 }
 
 pub(crate) fn discard_error_output(err: VMStatus) -> TransactionOutput {
@@ -670,8 +2792,49 @@ pub(crate) fn discard_error_output(err: VMStatus) -> TransactionOutput {
     )
 }
 
+/// Output for a transaction that was not executed and can be tried again in a later block.
+fn retry_output() -> TransactionOutput {
+    TransactionOutput::new(WriteSet::default(), vec![], 0, TransactionStatus::Retry)
+}
+
+/// Reads the value at `access_path` and extracts a field from it with `decode`. Returns `None`
+/// if there is no value.
+fn read_account_field<T>(
+    state_view: &dyn StateView,
+    access_path: AccessPath,
+    decode: fn(&[u8]) -> Option<T>,
+) -> Result<Option<T>, VMStatus> {
+    match state_view
+        .get(&access_path)
+        .map_err(|_| VMStatus::new(StatusCode::STORAGE_ERROR, None, None))?
+    {
+        Some(blob) => decode(&blob)
+            .map(Some)
+            .ok_or_else(|| VMStatus::new(StatusCode::VALUE_DESERIALIZATION_ERROR, None, None)),
+        None => Ok(None),
+    }
+}
+
+/// Returns whether `output` is kept and emits a new epoch event.
+fn is_reconfiguration(output: &TransactionOutput) -> bool {
+    let new_epoch_event_key = new_epoch_event_key();
+    !output.status().is_discarded()
+        && output
+            .events()
+            .iter()
+            .any(|event| *event.key() == new_epoch_event_key)
+}
+
+fn events_by_key(events: &[ContractEvent]) -> HashMap<EventKey, Vec<&ContractEvent>> {
+    let mut events_by_key: HashMap<EventKey, Vec<&ContractEvent>> = HashMap::new();
+    for event in events {
+        events_by_key.entry(*event.key()).or_default().push(event);
+    }
+    events_by_key
+}
+
 /// Convert the transaction arguments into move values.
-fn convert_txn_args(args: &[TransactionArgument]) -> Vec<Value> {
+pub(crate) fn convert_txn_args(args: &[TransactionArgument]) -> Vec<Value> {
     args.iter()
         .map(|arg| match arg {
             TransactionArgument::U8(i) => Value::u8(*i),
@@ -680,18 +2843,53 @@ fn convert_txn_args(args: &[TransactionArgument]) -> Vec<Value> {
             TransactionArgument::Address(a) => Value::address(*a),
             TransactionArgument::Bool(b) => Value::bool(*b),
             TransactionArgument::U8Vector(v) => Value::vector_u8(v.clone()),
+            TransactionArgument::U64Vector(v) => Value::vector_u64(v.clone()),
+            TransactionArgument::AddressVector(v) => Value::vector_address(v.clone()),
         })
         .collect()
 }
 
+/// The inverse of the conversion of script arguments to Move values: returns the
+/// `TransactionArgument` a script would have to be passed to get `value`, or `None` if `value`
+/// can't be a script argument (e.g. a struct or a reference).
+pub fn value_to_txn_arg(value: &Value) -> Option<TransactionArgument> {
+    let casts: [fn(Value) -> Option<TransactionArgument>; 8] = [
+        |v| v.value_as::<u8>().ok().map(TransactionArgument::U8),
+        |v| v.value_as::<u64>().ok().map(TransactionArgument::U64),
+        |v| v.value_as::<u128>().ok().map(TransactionArgument::U128),
+        |v| {
+            v.value_as::<AccountAddress>()
+                .ok()
+                .map(TransactionArgument::Address)
+        },
+        |v| v.value_as::<bool>().ok().map(TransactionArgument::Bool),
+        |v| {
+            v.value_as::<Vec<u8>>()
+                .ok()
+                .map(TransactionArgument::U8Vector)
+        },
+        |v| {
+            v.value_as::<Vec<u64>>()
+                .ok()
+                .map(TransactionArgument::U64Vector)
+        },
+        |v| {
+            v.value_as::<Vec<AccountAddress>>()
+                .ok()
+                .map(TransactionArgument::AddressVector)
+        },
+    ];
+    casts.iter().find_map(|cast| cast(value.copy_value().ok()?))
+}
+
 impl AsRef<LibraVMImpl> for LibraVM {
     fn as_ref(&self) -> &LibraVMImpl {
-        &self.0
+        &self.inner
     }
 }
 
 impl AsMut<LibraVMImpl> for LibraVM {
     fn as_mut(&mut self) -> &mut LibraVMImpl {
-        &mut self.0
+        &mut self.inner
     }
 }
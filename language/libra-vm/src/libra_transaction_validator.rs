@@ -2,7 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    counters::*, create_access_path, data_cache::StateViewCache, libra_vm::LibraVMImpl,
+    counters::*, create_access_path, data_cache::StateViewCache,
+    libra_transaction_executor::invalid_signature_status, libra_vm::LibraVMImpl,
     transaction_metadata::TransactionMetadata, VMValidator,
 };
 use libra_state_view::StateView;
@@ -115,14 +116,15 @@ impl VMValidator for LibraVMValidator {
             };
 
         let txn_sender = transaction.sender();
-        let signature_verified_txn = if let Ok(t) = transaction.check_signature() {
-            t
-        } else {
-            return VMValidatorResult::new(
-                Some(VMStatus::new(StatusCode::INVALID_SIGNATURE, None, None)),
-                gas_price,
-                false,
-            );
+        let signature_verified_txn = match transaction.check_signature() {
+            Ok(t) => t,
+            Err(err) => {
+                return VMValidatorResult::new(
+                    Some(invalid_signature_status(&err)),
+                    gas_price,
+                    false,
+                )
+            }
         };
 
         let is_prioritized_txn = is_prioritized_txn(txn_sender, &data_cache);
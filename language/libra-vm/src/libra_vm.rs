@@ -17,7 +17,9 @@ use libra_types::{
     account_config,
     contract_event::ContractEvent,
     event::EventKey,
-    on_chain_config::{ConfigStorage, LibraVersion, OnChainConfig, VMConfig},
+    on_chain_config::{
+        ConfigStorage, LibraVersion, OnChainConfig, StorageGasConfig, TransactionLimits, VMConfig,
+    },
     transaction::{ChangeSet, Script, TransactionOutput, TransactionStatus},
     vm_status::{convert_prologue_runtime_error, sub_status, StatusCode, VMStatus},
     write_set::{WriteOp, WriteSet, WriteSetMut},
@@ -37,7 +39,7 @@ use move_vm_types::{
     gas_schedule::{calculate_intrinsic_gas, zero_cost_schedule, CostStrategy},
     values::Value,
 };
-use std::{convert::TryFrom, sync::Arc};
+use std::{collections::BTreeSet, convert::TryFrom, sync::Arc};
 
 #[derive(Clone)]
 /// A wrapper to make VMRuntime standalone and thread safe.
@@ -45,6 +47,9 @@ pub struct LibraVMImpl {
     move_vm: Arc<MoveVM>,
     on_chain_config: Option<VMConfig>,
     version: Option<LibraVersion>,
+    transaction_limits: Option<TransactionLimits>,
+    storage_gas_config: Option<StorageGasConfig>,
+    gas_schedule_override: Option<CostTable>,
 }
 
 macro_rules! gas_schedule {
@@ -70,6 +75,9 @@ impl LibraVMImpl {
             move_vm: Arc::new(inner),
             on_chain_config: None,
             version: None,
+            transaction_limits: None,
+            storage_gas_config: None,
+            gas_schedule_override: None,
         }
     }
 
@@ -79,9 +87,17 @@ impl LibraVMImpl {
             move_vm: Arc::new(inner),
             on_chain_config: Some(on_chain_config),
             version: Some(version),
+            transaction_limits: None,
+            storage_gas_config: None,
+            gas_schedule_override: None,
         }
     }
 
+    /// Drops the modules loaded so far, so that the next transactions read them from storage.
+    pub(crate) fn reset_code_cache(&mut self) {
+        self.move_vm = Arc::new(MoveVM::new());
+    }
+
     /// Provides access to some internal APIs of the Libra VM.
     pub fn internals(&self) -> LibraVMInternals {
         LibraVMInternals(self)
@@ -91,6 +107,20 @@ impl LibraVMImpl {
         self.load_configs_impl(&RemoteStorage::new(state))
     }
 
+    /// The on-chain `TransactionLimits` loaded by the last `load_configs_impl`, if the chain
+    /// published them.
+    pub(crate) fn transaction_limits(&self) -> Option<&TransactionLimits> {
+        self.transaction_limits.as_ref()
+    }
+
+    /// Whether the on-chain `StorageGasConfig` loaded by the last `load_configs_impl` asks for the
+    /// bytes written by transactions to be charged. They are not if the chain didn't publish it.
+    pub(crate) fn charges_written_bytes(&self) -> bool {
+        self.storage_gas_config
+            .as_ref()
+            .map_or(false, |config| config.charge_written_bytes)
+    }
+
     pub(crate) fn on_chain_config(&self) -> Result<&VMConfig, VMStatus> {
         self.on_chain_config
             .as_ref()
@@ -100,12 +130,23 @@ impl LibraVMImpl {
     pub(crate) fn load_configs_impl<S: ConfigStorage>(&mut self, data_cache: &S) {
         self.on_chain_config = VMConfig::fetch_config(data_cache);
         self.version = LibraVersion::fetch_config(data_cache);
+        self.transaction_limits = TransactionLimits::fetch_config(data_cache);
+        self.storage_gas_config = StorageGasConfig::fetch_config(data_cache);
     }
 
     pub fn get_gas_schedule(&self) -> Result<&CostTable, VMStatus> {
+        if let Some(gas_schedule) = &self.gas_schedule_override {
+            return Ok(gas_schedule);
+        }
         gas_schedule!(self)
     }
 
+    /// Uses `gas_schedule` instead of the on-chain gas schedule, or goes back to the on-chain one
+    /// if `None`.
+    pub(crate) fn set_gas_schedule_override(&mut self, gas_schedule: Option<CostTable>) {
+        self.gas_schedule_override = gas_schedule;
+    }
+
     pub fn get_libra_version(&self) -> Result<LibraVersion, VMStatus> {
         self.version.clone().ok_or_else(|| {
             VMStatus::new(
@@ -423,6 +464,10 @@ impl LibraVMImpl {
             .map_err(|e| e.into_vm_status())
     }
 
+    /// Starts a session reading from `r`. The sessions share the code cache of `move_vm`: a
+    /// module is read from `r`, deserialized and verified the first time a session loads it, and
+    /// then reused by every later session until `reset_code_cache`. A module cannot be published
+    /// again once it exists, so a module published mid-block never makes a cached one stale.
     pub fn new_session<'r, R: RemoteCache>(&self, r: &'r R) -> Session<'r, '_, R> {
         self.move_vm.new_session(r)
     }
@@ -477,6 +522,13 @@ fn can_publish_modules(sender: AccountAddress, remote_cache: &StateViewCache) ->
     }
 }
 
+/// Converts the effects of a session into a write set and the events it emitted.
+///
+/// Event sequence numbers are not assigned here: they are the values of the emitting
+/// `EventHandle` counters, which live in the handle owner's resources. They are therefore a pure
+/// function of the state the transaction read and of the transaction itself, and replaying a block
+/// against the same state yields the same sequence numbers. Events keep the order in which they
+/// were emitted.
 pub fn txn_effects_to_writeset_and_events_cached<C: AccessPathCache>(
     ap_cache: &mut C,
     effects: TransactionEffects,
@@ -535,12 +587,21 @@ pub fn txn_effects_to_writeset_and_events_cached<C: AccessPathCache>(
     Ok((ws, events))
 }
 
+/// Sorts `events` by event key, and by sequence number for each key: the canonical order of the
+/// events of a transaction. The events emitted on a key already have increasing sequence numbers,
+/// so this only reorders events emitted on different keys.
+pub(crate) fn sort_events_canonically(events: &mut [ContractEvent]) {
+    events
+        .sort_by(|e1, e2| (e1.key(), e1.sequence_number()).cmp(&(e2.key(), e2.sequence_number())));
+}
+
 pub(crate) fn get_transaction_output<A: AccessPathCache, R: RemoteCache>(
     ap_cache: &mut A,
     session: Session<R>,
     cost_strategy: &CostStrategy,
     txn_data: &TransactionMetadata,
     status: VMStatus,
+    canonical_event_order: bool,
 ) -> Result<TransactionOutput, VMStatus> {
     let gas_used: u64 = txn_data
         .max_gas_amount()
@@ -548,7 +609,11 @@ pub(crate) fn get_transaction_output<A: AccessPathCache, R: RemoteCache>(
         .get();
 
     let effects = session.finish().map_err(|e| e.into_vm_status())?;
-    let (write_set, events) = txn_effects_to_writeset_and_events_cached(ap_cache, effects)?;
+    let (write_set, mut events) = txn_effects_to_writeset_and_events_cached(ap_cache, effects)?;
+    check_unique_write_paths(&write_set)?;
+    if canonical_event_order {
+        sort_events_canonically(&mut events);
+    }
 
     TXN_TOTAL_GAS_USAGE.observe(gas_used as f64);
     Ok(TransactionOutput::new(
@@ -559,6 +624,24 @@ pub(crate) fn get_transaction_output<A: AccessPathCache, R: RemoteCache>(
     ))
 }
 
+/// Checks that `write_set` writes each access path at most once. The effect conversion should
+/// never produce a duplicate, finding one is an invariant violation.
+pub(crate) fn check_unique_write_paths(write_set: &WriteSet) -> Result<(), VMStatus> {
+    let mut paths = BTreeSet::new();
+    if write_set
+        .iter()
+        .all(|(access_path, _)| paths.insert(access_path))
+    {
+        Ok(())
+    } else {
+        Err(VMStatus::new(
+            StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR,
+            None,
+            Some("access path written more than once".to_string()),
+        ))
+    }
+}
+
 pub fn txn_effects_to_writeset_and_events(
     effects: TransactionEffects,
 ) -> Result<(WriteSet, Vec<ContractEvent>), VMStatus> {
@@ -0,0 +1,99 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A programmatic view of the counters the VM reports to Prometheus.
+
+use crate::counters::*;
+use libra_metrics::{Histogram, HistogramVec, IntCounterVec};
+use std::collections::BTreeMap;
+
+/// Labels used by `TRANSACTIONS_EXECUTED`.
+const EXECUTED_LABELS: &[&str] = &["success", "discarded"];
+/// Labels used by `TRANSACTIONS_VERIFIED`.
+const VERIFIED_LABELS: &[&str] = &["success", "failure"];
+/// Labels used by `TXN_TOTAL_SECONDS_BY_PAYLOAD`.
+const PAYLOAD_LABELS: &[&str] = &["script", "module", "write_set"];
+
+/// The number of observations of a histogram and their sum.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HistogramSummary {
+    pub sample_count: u64,
+    pub sample_sum: f64,
+}
+
+impl HistogramSummary {
+    fn of(histogram: &Histogram) -> Self {
+        Self {
+            sample_count: histogram.get_sample_count(),
+            sample_sum: histogram.get_sample_sum(),
+        }
+    }
+}
+
+/// The values of the VM counters at some point in time. The counters are shared by all the VM
+/// instances of the process.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VmMetricsSnapshot {
+    /// Transactions executed, by status label ("success" or "discarded").
+    pub transactions_executed: BTreeMap<String, u64>,
+    /// Transactions verified, by status label ("success" or "failure").
+    pub transactions_verified: BTreeMap<String, u64>,
+    /// Number of transactions in the last block executed.
+    pub block_transaction_count: i64,
+    /// Gas used by the script of each transaction.
+    pub txn_execution_gas_usage: HistogramSummary,
+    /// Total gas used by each transaction.
+    pub txn_total_gas_usage: HistogramSummary,
+    /// Total execution time of each transaction, in seconds.
+    pub txn_total_seconds: HistogramSummary,
+    /// Total execution time of each user transaction, in seconds, by payload label ("script",
+    /// "module" or "write_set").
+    pub txn_total_seconds_by_payload: BTreeMap<String, HistogramSummary>,
+    /// Gas used by each metered block prologue.
+    pub block_prologue_gas_usage: HistogramSummary,
+}
+
+impl VmMetricsSnapshot {
+    pub(crate) fn capture() -> Self {
+        Self {
+            transactions_executed: counts_by_label(&TRANSACTIONS_EXECUTED, EXECUTED_LABELS),
+            transactions_verified: counts_by_label(&TRANSACTIONS_VERIFIED, VERIFIED_LABELS),
+            block_transaction_count: BLOCK_TRANSACTION_COUNT.get(),
+            txn_execution_gas_usage: HistogramSummary::of(&TXN_EXECUTION_GAS_USAGE),
+            txn_total_gas_usage: HistogramSummary::of(&TXN_TOTAL_GAS_USAGE),
+            txn_total_seconds: HistogramSummary::of(&TXN_TOTAL_SECONDS),
+            txn_total_seconds_by_payload: summaries_by_label(
+                &TXN_TOTAL_SECONDS_BY_PAYLOAD,
+                PAYLOAD_LABELS,
+            ),
+            block_prologue_gas_usage: HistogramSummary::of(&BLOCK_PROLOGUE_GAS_USAGE),
+        }
+    }
+}
+
+fn counts_by_label(counter: &IntCounterVec, labels: &[&str]) -> BTreeMap<String, u64> {
+    labels
+        .iter()
+        .map(|label| {
+            (
+                (*label).to_string(),
+                counter.with_label_values(&[label]).get(),
+            )
+        })
+        .collect()
+}
+
+fn summaries_by_label(
+    histogram: &HistogramVec,
+    labels: &[&str],
+) -> BTreeMap<String, HistogramSummary> {
+    labels
+        .iter()
+        .map(|label| {
+            (
+                (*label).to_string(),
+                HistogramSummary::of(&histogram.with_label_values(&[label])),
+            )
+        })
+        .collect()
+}
@@ -0,0 +1,87 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Cache of transaction outputs, to avoid executing the same transaction on the same state twice.
+
+use libra_crypto::HashValue;
+use libra_types::transaction::{TransactionOutput, TransactionStatus, Version};
+use std::collections::HashMap;
+
+/// Outputs of transactions executed on their own, keyed by the transaction hash and the version
+/// of the state they were executed on.
+///
+/// An entry is only valid for that exact state, so nothing has to be invalidated when the state
+/// moves on: a different version is simply a different key. When the cache is full it is cleared
+/// entirely.
+#[derive(Debug)]
+pub struct OutputCache {
+    capacity: usize,
+    outputs: HashMap<(HashValue, Version), TransactionOutput>,
+    hits: u64,
+    misses: u64,
+}
+
+impl OutputCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            outputs: HashMap::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Returns the cached output of `txn_hash` executed on the state at `state_version`.
+    pub fn get(
+        &mut self,
+        txn_hash: HashValue,
+        state_version: Version,
+    ) -> Option<TransactionOutput> {
+        let output = self.outputs.get(&(txn_hash, state_version)).cloned();
+        if output.is_some() {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+        }
+        output
+    }
+
+    /// Caches `output`. `Retry` outputs are not final and are never cached.
+    pub fn insert(
+        &mut self,
+        txn_hash: HashValue,
+        state_version: Version,
+        output: TransactionOutput,
+    ) {
+        if self.capacity == 0 {
+            return;
+        }
+        if let TransactionStatus::Retry = output.status() {
+            return;
+        }
+        if self.outputs.len() >= self.capacity {
+            self.outputs.clear();
+        }
+        self.outputs.insert((txn_hash, state_version), output);
+    }
+
+    pub fn clear(&mut self) {
+        self.outputs.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.outputs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.outputs.is_empty()
+    }
+
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+}
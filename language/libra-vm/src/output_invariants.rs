@@ -0,0 +1,59 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Sanity checks on the outputs produced by the executor, for tests and fuzzing.
+
+use crate::intrinsic_gas::IntrinsicGasParams;
+use libra_types::transaction::{SignedTransaction, TransactionOutput, TransactionStatus};
+
+/// An inconsistency between the status of an output and the rest of it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum OutputInvariantViolation {
+    /// A discarded output charged gas.
+    DiscardedWithGasUsed { gas_used: u64 },
+    /// A discarded output has a non empty write set.
+    DiscardedWithWrites,
+    /// A kept output used less gas than the intrinsic gas of its transaction.
+    BelowIntrinsicGas { gas_used: u64, intrinsic_gas: u64 },
+    /// A kept output used more gas than its transaction allowed.
+    AboveMaxGasAmount { gas_used: u64, max_gas_amount: u64 },
+}
+
+/// Checks that `output`, produced by executing `txn`, is consistent with its status: discarded
+/// outputs (including `Retry`) must not charge gas nor write anything, and kept outputs must have
+/// used between the intrinsic gas of `txn` and its max gas amount. The intrinsic gas is computed
+/// with `intrinsic_gas_params`, see `LibraVM::intrinsic_gas_params`.
+pub fn validate_output_invariants(
+    output: &TransactionOutput,
+    txn: &SignedTransaction,
+    intrinsic_gas_params: &IntrinsicGasParams,
+) -> Result<(), OutputInvariantViolation> {
+    let gas_used = output.gas_used();
+    match output.status() {
+        TransactionStatus::Discard(_) | TransactionStatus::Retry => {
+            if gas_used != 0 {
+                return Err(OutputInvariantViolation::DiscardedWithGasUsed { gas_used });
+            }
+            if !output.write_set().is_empty() {
+                return Err(OutputInvariantViolation::DiscardedWithWrites);
+            }
+        }
+        TransactionStatus::Keep(_) => {
+            let intrinsic_gas = intrinsic_gas_params.intrinsic_gas(txn.raw_txn_bytes_len() as u64);
+            if gas_used < intrinsic_gas {
+                return Err(OutputInvariantViolation::BelowIntrinsicGas {
+                    gas_used,
+                    intrinsic_gas,
+                });
+            }
+            let max_gas_amount = txn.max_gas_amount();
+            if gas_used > max_gas_amount {
+                return Err(OutputInvariantViolation::AboveMaxGasAmount {
+                    gas_used,
+                    max_gas_amount,
+                });
+            }
+        }
+    }
+    Ok(())
+}
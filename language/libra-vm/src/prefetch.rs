@@ -0,0 +1,33 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Prediction of the data a block of transactions is going to read, so that it can be fetched
+//! from the backing store in bulk before execution starts.
+
+use crate::{account_layout::AccountLayout, create_access_path};
+use libra_types::{access_path::AccessPath, account_config, transaction::SignedTransaction};
+use std::collections::BTreeSet;
+
+/// Returns the access paths that `transactions` are expected to read: the account resource of
+/// every sender and its balance in the gas currency, which are read by the prologue. The result is
+/// sorted and has no duplicates. Hints are best effort: the transactions may read more.
+pub fn derive_prefetch_hints(
+    transactions: &[SignedTransaction],
+    layout: &AccountLayout,
+) -> Vec<AccessPath> {
+    let mut hints = BTreeSet::new();
+    for txn in transactions {
+        let sender = txn.sender();
+        hints.insert(create_access_path(sender, layout.account_resource.clone()));
+        if let Ok(currency_code) =
+            account_config::from_currency_code_string(txn.gas_currency_code())
+        {
+            let currency = account_config::type_tag_for_currency_code(currency_code);
+            hints.insert(create_access_path(
+                sender,
+                (layout.balance_resource)(currency),
+            ));
+        }
+    }
+    hints.into_iter().collect()
+}
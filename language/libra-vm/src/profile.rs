@@ -0,0 +1,80 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Time spent executing a block, aggregated by phase in the folded stack format read by
+//! flamegraph tools.
+
+use crate::{diagnostics::PhaseTimes, execution_time::TransactionKind};
+use std::{collections::BTreeMap, fmt, time::Duration};
+
+/// Root frame of every stack of a `FoldedProfile`.
+const ROOT_FRAME: &str = "execute_block";
+
+/// The time spent in each phase of a block, by stack, in microseconds. Returned by
+/// `LibraVM::execute_block_with_profile`.
+///
+/// The stacks are `execute_block;config_load` for the loading of the on-chain configs,
+/// `execute_block;<kind>;<phase>` for the `prologue`, `execution` and `epilogue` of the script
+/// and module transactions, and `execute_block;<kind>` for the other kinds of transactions,
+/// where `<kind>` is one of `script`, `module`, `write_set`, `block_prologue` and
+/// `waypoint_write_set`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FoldedProfile {
+    samples: BTreeMap<String, u64>,
+}
+
+impl FoldedProfile {
+    /// Adds `elapsed` to the stack `execute_block;<frames>`.
+    pub fn record(&mut self, frames: &[&str], elapsed: Duration) {
+        let stack = std::iter::once(ROOT_FRAME)
+            .chain(frames.iter().copied())
+            .collect::<Vec<_>>()
+            .join(";");
+        let micros = elapsed.as_micros() as u64;
+        let total = self.samples.entry(stack).or_insert(0);
+        *total = total.saturating_add(micros);
+    }
+
+    pub(crate) fn record_transaction(&mut self, kind: TransactionKind, phase_times: &PhaseTimes) {
+        let kind = kind_frame(kind);
+        self.record(&[kind, "prologue"], phase_times.prologue);
+        self.record(&[kind, "execution"], phase_times.execution);
+        self.record(&[kind, "epilogue"], phase_times.epilogue);
+    }
+
+    pub(crate) fn record_kind(&mut self, kind: TransactionKind, elapsed: Duration) {
+        self.record(&[kind_frame(kind)], elapsed);
+    }
+
+    /// The time recorded for `stack`, e.g. `execute_block;script;prologue`, in microseconds.
+    pub fn get(&self, stack: &str) -> Option<u64> {
+        self.samples.get(stack).copied()
+    }
+
+    /// The stacks and the time recorded for each of them, in microseconds.
+    pub fn samples(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.samples
+            .iter()
+            .map(|(stack, micros)| (stack.as_str(), *micros))
+    }
+}
+
+/// One `<stack> <microseconds>` line per stack.
+impl fmt::Display for FoldedProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (stack, micros) in self.samples() {
+            writeln!(f, "{} {}", stack, micros)?;
+        }
+        Ok(())
+    }
+}
+
+fn kind_frame(kind: TransactionKind) -> &'static str {
+    match kind {
+        TransactionKind::BlockPrologue => "block_prologue",
+        TransactionKind::WaypointWriteSet => "waypoint_write_set",
+        TransactionKind::Script => "script",
+        TransactionKind::Module => "module",
+        TransactionKind::WriteSet => "write_set",
+    }
+}
@@ -0,0 +1,184 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Caching of the values read from a `StateView` that does not change.
+
+use anyhow::Result;
+use libra_state_view::StateView;
+use libra_types::{access_path::AccessPath, write_set::WriteSet};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Mutex, RwLock},
+};
+
+/// Keeps the values read through it, so that reading a path again does not go to the wrapped
+/// view. It can be shared between threads, e.g. to pass the same warmed cache to successive
+/// `LibraVM::validate_batch` calls against the same state: each transaction is still validated
+/// in its own session, and validation only reads, so the cached values are those of the state.
+///
+/// The wrapped view must not change while the cache is in use: build a new cache for a new
+/// state. Failed reads are not cached.
+pub struct SharedReadCache<'a> {
+    view: &'a (dyn StateView + Sync),
+    values: RwLock<HashMap<AccessPath, Option<Vec<u8>>>>,
+}
+
+impl<'a> SharedReadCache<'a> {
+    pub fn new(view: &'a (dyn StateView + Sync)) -> Self {
+        Self {
+            view,
+            values: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Number of paths cached so far.
+    pub fn len(&self) -> usize {
+        self.values.read().expect("lock poisoned").len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<'a> StateView for SharedReadCache<'a> {
+    fn get(&self, access_path: &AccessPath) -> Result<Option<Vec<u8>>> {
+        if let Some(value) = self.values.read().expect("lock poisoned").get(access_path) {
+            return Ok(value.clone());
+        }
+        let value = self.view.get(access_path)?;
+        self.values
+            .write()
+            .expect("lock poisoned")
+            .insert(access_path.clone(), value.clone());
+        Ok(value)
+    }
+
+    fn multi_get(&self, access_paths: &[AccessPath]) -> Result<Vec<Option<Vec<u8>>>> {
+        access_paths.iter().map(|ap| self.get(ap)).collect()
+    }
+
+    fn is_genesis(&self) -> bool {
+        self.view.is_genesis()
+    }
+}
+
+/// Keeps up to `capacity` of the values read through it, evicting the least recently read ones
+/// first, so that reading a path again does not go to the wrapped view, e.g. a view of cold
+/// storage re-read by every block of a replay.
+///
+/// Blocks are executed on top of it as on any `StateView`. The `StateViewCache` of a block
+/// holds the writes pushed with `push_write_set` and serves them before reading through this
+/// cache, so the cached values are those of the wrapped view, which must not change while a
+/// block runs. Once the outputs of a block are committed to the storage behind the wrapped view,
+/// pass their write sets to `invalidate` before executing the next block. Failed reads are not
+/// cached.
+pub struct LruReadCache<'a> {
+    view: &'a dyn StateView,
+    capacity: usize,
+    state: Mutex<LruState>,
+}
+
+#[derive(Default)]
+struct LruState {
+    /// The cached values, with the tick of their last read.
+    values: HashMap<AccessPath, (Option<Vec<u8>>, u64)>,
+    /// The cached paths by tick of their last read, the least recently read first.
+    recency: BTreeMap<u64, AccessPath>,
+    tick: u64,
+    hits: u64,
+    misses: u64,
+}
+
+impl<'a> LruReadCache<'a> {
+    pub fn new(view: &'a dyn StateView, capacity: usize) -> Self {
+        Self {
+            view,
+            capacity,
+            state: Mutex::new(LruState::default()),
+        }
+    }
+
+    /// Drops the cached values of the paths written by `write_set`, e.g. once it is committed
+    /// to the storage behind the wrapped view.
+    pub fn invalidate(&self, write_set: &WriteSet) {
+        let mut state = self.state.lock().expect("lock poisoned");
+        for (access_path, _) in write_set {
+            if let Some((_, tick)) = state.values.remove(access_path) {
+                state.recency.remove(&tick);
+            }
+        }
+    }
+
+    /// Number of paths cached.
+    pub fn len(&self) -> usize {
+        self.state.lock().expect("lock poisoned").values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of reads served from the cache and number of reads that went to the wrapped view.
+    pub fn hits_and_misses(&self) -> (u64, u64) {
+        let state = self.state.lock().expect("lock poisoned");
+        (state.hits, state.misses)
+    }
+}
+
+impl LruState {
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+}
+
+impl<'a> StateView for LruReadCache<'a> {
+    fn get(&self, access_path: &AccessPath) -> Result<Option<Vec<u8>>> {
+        {
+            let mut state = self.state.lock().expect("lock poisoned");
+            let tick = state.next_tick();
+            if let Some((value, last_read)) = state.values.get_mut(access_path) {
+                let value = value.clone();
+                let last_read = std::mem::replace(last_read, tick);
+                state.recency.remove(&last_read);
+                state.recency.insert(tick, access_path.clone());
+                state.hits += 1;
+                return Ok(value);
+            }
+            state.misses += 1;
+        }
+        // The wrapped view is read without holding the lock.
+        let value = self.view.get(access_path)?;
+        if self.capacity == 0 {
+            return Ok(value);
+        }
+        let mut state = self.state.lock().expect("lock poisoned");
+        let tick = state.next_tick();
+        if let Some((_, last_read)) = state
+            .values
+            .insert(access_path.clone(), (value.clone(), tick))
+        {
+            state.recency.remove(&last_read);
+        }
+        state.recency.insert(tick, access_path.clone());
+        while state.values.len() > self.capacity {
+            let oldest = match state.recency.keys().next() {
+                Some(oldest) => *oldest,
+                None => break,
+            };
+            if let Some(evicted) = state.recency.remove(&oldest) {
+                state.values.remove(&evicted);
+            }
+        }
+        Ok(value)
+    }
+
+    fn multi_get(&self, access_paths: &[AccessPath]) -> Result<Vec<Option<Vec<u8>>>> {
+        access_paths.iter().map(|ap| self.get(ap)).collect()
+    }
+
+    fn is_genesis(&self) -> bool {
+        self.view.is_genesis()
+    }
+}
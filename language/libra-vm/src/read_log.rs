@@ -0,0 +1,100 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Recording of the state read by a block, to replay its execution offline when investigating a
+//! divergence between validators. See `LibraVM::execute_block_recorded`.
+
+use anyhow::{format_err, Result};
+use libra_state_view::StateView;
+use libra_types::access_path::AccessPath;
+use std::{cell::RefCell, collections::BTreeMap};
+
+/// The values read from a `StateView`, by path. It is a `StateView` itself, serving exactly the
+/// recorded reads: executing the same block against it gives the same outputs as the recorded
+/// execution, or fails if the execution reads anything else.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ReadLog {
+    reads: BTreeMap<AccessPath, Option<Vec<u8>>>,
+    is_genesis: bool,
+}
+
+impl ReadLog {
+    /// The recorded reads, with `None` for the paths that held no value.
+    pub fn reads(&self) -> &BTreeMap<AccessPath, Option<Vec<u8>>> {
+        &self.reads
+    }
+
+    /// Serializes the log with LCS, to store it next to the block.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        lcs::to_bytes(&(&self.reads, self.is_genesis)).map_err(Into::into)
+    }
+
+    /// Deserializes a log serialized with `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let (reads, is_genesis) = lcs::from_bytes(bytes)?;
+        Ok(Self { reads, is_genesis })
+    }
+}
+
+impl StateView for ReadLog {
+    fn get(&self, access_path: &AccessPath) -> Result<Option<Vec<u8>>> {
+        self.reads
+            .get(access_path)
+            .cloned()
+            .ok_or_else(|| format_err!("{:?} was not read by the recorded execution", access_path))
+    }
+
+    fn multi_get(&self, access_paths: &[AccessPath]) -> Result<Vec<Option<Vec<u8>>>> {
+        access_paths.iter().map(|ap| self.get(ap)).collect()
+    }
+
+    fn is_genesis(&self) -> bool {
+        self.is_genesis
+    }
+}
+
+/// Records the values read through the wrapped view into a `ReadLog`. Failed reads are not
+/// recorded.
+pub struct RecordingStateView<'a> {
+    view: &'a dyn StateView,
+    reads: RefCell<BTreeMap<AccessPath, Option<Vec<u8>>>>,
+}
+
+impl<'a> RecordingStateView<'a> {
+    pub fn new(view: &'a dyn StateView) -> Self {
+        Self {
+            view,
+            reads: RefCell::new(BTreeMap::new()),
+        }
+    }
+
+    /// Returns the reads recorded so far.
+    pub fn into_log(self) -> ReadLog {
+        ReadLog {
+            reads: self.reads.into_inner(),
+            is_genesis: self.view.is_genesis(),
+        }
+    }
+}
+
+impl<'a> StateView for RecordingStateView<'a> {
+    fn get(&self, access_path: &AccessPath) -> Result<Option<Vec<u8>>> {
+        let value = self.view.get(access_path)?;
+        self.reads
+            .borrow_mut()
+            .insert(access_path.clone(), value.clone());
+        Ok(value)
+    }
+
+    fn multi_get(&self, access_paths: &[AccessPath]) -> Result<Vec<Option<Vec<u8>>>> {
+        let values = self.view.multi_get(access_paths)?;
+        self.reads
+            .borrow_mut()
+            .extend(access_paths.iter().cloned().zip(values.iter().cloned()));
+        Ok(values)
+    }
+
+    fn is_genesis(&self) -> bool {
+        self.view.is_genesis()
+    }
+}
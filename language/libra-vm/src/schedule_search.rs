@@ -0,0 +1,24 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Helpers to explore gas schedules, for gas schedule design. Nothing here is used when executing
+//! blocks.
+
+use move_core_types::gas_schedule::{CostTable, GasAlgebra, GasCost, GasUnits};
+
+/// Precision of the scaling factors tried by `LibraVM::min_viable_schedule`.
+pub const SCALE_DENOMINATOR: u64 = 1_000;
+
+/// Returns `cost_table` with every instruction and native cost multiplied by
+/// `numerator / SCALE_DENOMINATOR`, rounded down. The gas constants are left untouched.
+pub fn scale_cost_table(cost_table: &CostTable, numerator: u64) -> CostTable {
+    let scale = |cost: &GasCost| GasCost {
+        instruction_gas: GasUnits::new(cost.instruction_gas.get() * numerator / SCALE_DENOMINATOR),
+        memory_gas: GasUnits::new(cost.memory_gas.get() * numerator / SCALE_DENOMINATOR),
+    };
+    CostTable {
+        instruction_table: cost_table.instruction_table.iter().map(scale).collect(),
+        native_table: cost_table.native_table.iter().map(scale).collect(),
+        gas_constants: cost_table.gas_constants.clone(),
+    }
+}
@@ -0,0 +1,98 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Application of the outputs of a block to the storage they were computed against.
+
+use anyhow::Result;
+use libra_state_view::StateView;
+use libra_types::{
+    access_path::AccessPath,
+    transaction::{TransactionOutput, TransactionStatus},
+    vm_status::{StatusCode, VMStatus},
+    write_set::{WriteOp, WriteSet},
+};
+use std::collections::BTreeMap;
+
+/// A mutable store the outputs of the VM can be committed to.
+pub trait StateStore {
+    /// Applies `writes` in order as a single batch: either all of them or none are applied.
+    fn write_batch(&mut self, writes: Vec<(AccessPath, WriteOp)>) -> Result<()>;
+}
+
+/// Applies the write sets of the kept outputs of a block to `store`, in order and in a single
+/// batch. Discarded and retried outputs are skipped, they must not change the state.
+pub fn apply_block_outputs<S: StateStore>(
+    store: &mut S,
+    outputs: &[TransactionOutput],
+) -> Result<(), VMStatus> {
+    let writes = outputs
+        .iter()
+        .filter(|output| match output.status() {
+            TransactionStatus::Keep(_) => true,
+            TransactionStatus::Discard(_) | TransactionStatus::Retry => false,
+        })
+        .flat_map(|output| output.write_set().iter().cloned())
+        .collect();
+    store
+        .write_batch(writes)
+        .map_err(|_| VMStatus::new(StatusCode::STORAGE_ERROR, None, None))
+}
+
+/// A state kept in memory that blocks can be executed against and committed to, see
+/// `LibraVM::execute_block_and_apply`. Meant for tests and tooling.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct InMemoryStateView {
+    data: BTreeMap<AccessPath, Vec<u8>>,
+}
+
+impl InMemoryStateView {
+    pub fn new(data: BTreeMap<AccessPath, Vec<u8>>) -> Self {
+        Self { data }
+    }
+
+    /// Applies `write_set` to the state, e.g. the genesis write set.
+    pub fn apply_write_set(&mut self, write_set: &WriteSet) {
+        for (access_path, write_op) in write_set {
+            self.apply(access_path.clone(), write_op.clone());
+        }
+    }
+
+    /// The values in the state.
+    pub fn data(&self) -> &BTreeMap<AccessPath, Vec<u8>> {
+        &self.data
+    }
+
+    fn apply(&mut self, access_path: AccessPath, write_op: WriteOp) {
+        match write_op {
+            WriteOp::Value(blob) => {
+                self.data.insert(access_path, blob);
+            }
+            WriteOp::Deletion => {
+                self.data.remove(&access_path);
+            }
+        }
+    }
+}
+
+impl StateView for InMemoryStateView {
+    fn get(&self, access_path: &AccessPath) -> Result<Option<Vec<u8>>> {
+        Ok(self.data.get(access_path).cloned())
+    }
+
+    fn multi_get(&self, access_paths: &[AccessPath]) -> Result<Vec<Option<Vec<u8>>>> {
+        access_paths.iter().map(|ap| self.get(ap)).collect()
+    }
+
+    fn is_genesis(&self) -> bool {
+        false
+    }
+}
+
+impl StateStore for InMemoryStateView {
+    fn write_batch(&mut self, writes: Vec<(AccessPath, WriteOp)>) -> Result<()> {
+        for (access_path, write_op) in writes {
+            self.apply(access_path, write_op);
+        }
+        Ok(())
+    }
+}
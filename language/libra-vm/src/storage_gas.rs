@@ -0,0 +1,42 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Pluggable pricing of storage reads, to experiment with alternative storage gas models.
+//!
+//! By default no model is set and reads are only paid for through the gas schedule, as before.
+//! When a model is set with `ExecutorConfig::storage_gas_model`, the `StateViewCache` asks it for
+//! the cost of every resource read by a script, and the executor charges the total to the
+//! transaction once the script completes.
+
+use std::fmt::Debug;
+
+/// Prices the resource reads of a transaction, in gas units.
+pub trait StorageGasModel: Debug + Send + Sync {
+    /// Gas charged for reading a resource whose serialized value is `value_size` bytes long. The
+    /// size is 0 if the resource does not exist.
+    fn resource_read_cost(&self, value_size: usize) -> u64;
+}
+
+/// Charges a fixed amount of gas per resource read.
+#[derive(Clone, Copy, Debug)]
+pub struct PerAccessGasModel {
+    pub gas_per_access: u64,
+}
+
+impl StorageGasModel for PerAccessGasModel {
+    fn resource_read_cost(&self, _value_size: usize) -> u64 {
+        self.gas_per_access
+    }
+}
+
+/// Charges gas proportionally to the number of bytes read.
+#[derive(Clone, Copy, Debug)]
+pub struct PerByteGasModel {
+    pub gas_per_byte: u64,
+}
+
+impl StorageGasModel for PerByteGasModel {
+    fn resource_read_cost(&self, value_size: usize) -> u64 {
+        self.gas_per_byte.saturating_mul(value_size as u64)
+    }
+}
@@ -1,7 +1,9 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::libra_transaction_executor::{chunk_block_transactions, TransactionBlock};
+use crate::libra_transaction_executor::{
+    chunk_block_transactions, chunk_block_transactions_iter, TransactionBlock,
+};
 use libra_types::transaction::Transaction;
 use proptest::{collection::vec, prelude::*};
 
@@ -32,4 +34,21 @@ proptest! {
         let result = reconstruct_transaction_vec(chunk_block_transactions(txns.clone()));
         prop_assert_eq!(result, txns);
     }
+
+    #[test]
+    fn chunking_is_lazy(txns in vec(any::<Transaction>(), 1..20)) {
+        // Only the last chunk can need to look past the end of the transactions.
+        let mut eager = chunk_block_transactions(txns.clone());
+        eager.pop();
+        let lazy: Vec<_> = chunk_block_transactions_iter(
+            txns.into_iter()
+                .chain(std::iter::from_fn(|| panic!("chunk asked for too many transactions"))),
+        )
+        .take(eager.len())
+        .collect();
+        prop_assert_eq!(
+            reconstruct_transaction_vec(lazy),
+            reconstruct_transaction_vec(eager)
+        );
+    }
 }
@@ -0,0 +1,73 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{data_cache::StateViewCache, state_store::InMemoryStateView};
+use libra_state_view::StateView;
+use libra_types::{
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    write_set::{WriteOp, WriteSet, WriteSetMut},
+};
+use std::collections::BTreeMap;
+
+fn write_set(ops: Vec<(AccessPath, WriteOp)>) -> WriteSet {
+    WriteSetMut::new(ops).freeze().unwrap()
+}
+
+#[test]
+fn rollback_restores_the_checkpointed_values() {
+    let address = AccountAddress::random();
+    let path = |p: u8| AccessPath::new(address, vec![p]);
+    let mut storage = BTreeMap::new();
+    storage.insert(path(1), vec![1]);
+    storage.insert(path(2), vec![2]);
+    let storage = InMemoryStateView::new(storage);
+    let values = |cache: &StateViewCache| {
+        (1..5)
+            .map(|p| cache.get(&path(p)).unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    // An earlier transaction overwrote path 2 and created path 3.
+    let mut cache = StateViewCache::new(&storage);
+    cache.push_write_set(&write_set(vec![
+        (path(2), WriteOp::Value(vec![20])),
+        (path(3), WriteOp::Value(vec![3])),
+    ]));
+    let before = values(&cache);
+    assert_eq!(
+        before,
+        vec![Some(vec![1]), Some(vec![20]), Some(vec![3]), None]
+    );
+
+    let token = cache.checkpoint();
+    cache.push_write_set(&write_set(vec![
+        (path(1), WriteOp::Deletion),
+        (path(2), WriteOp::Value(vec![200])),
+        (path(4), WriteOp::Value(vec![4])),
+    ]));
+    let nested = cache.checkpoint();
+    cache.push_write_set(&write_set(vec![
+        (path(2), WriteOp::Deletion),
+        (path(3), WriteOp::Value(vec![30])),
+    ]));
+    assert_eq!(
+        values(&cache),
+        vec![None, None, Some(vec![30]), Some(vec![4])]
+    );
+
+    cache.rollback(nested);
+    assert_eq!(
+        values(&cache),
+        vec![None, Some(vec![200]), Some(vec![3]), Some(vec![4])]
+    );
+    cache.rollback(token);
+    assert_eq!(values(&cache), before);
+
+    // The cache can be changed and rolled back again.
+    let token = cache.checkpoint();
+    cache.prefetch(&[path(1)]).unwrap();
+    cache.push_write_set(&write_set(vec![(path(1), WriteOp::Value(vec![10]))]));
+    cache.rollback(token);
+    assert_eq!(values(&cache), before);
+}
@@ -0,0 +1,56 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::event_consistency::{find_event_conflicts, EventConflict};
+use libra_types::{
+    account_address::AccountAddress,
+    contract_event::ContractEvent,
+    event::EventKey,
+    transaction::{TransactionOutput, TransactionStatus},
+    vm_status::VMStatus,
+    write_set::WriteSet,
+};
+use move_core_types::language_storage::TypeTag;
+
+fn output_with_events(events: &[(EventKey, u64)]) -> TransactionOutput {
+    TransactionOutput::new(
+        WriteSet::default(),
+        events
+            .iter()
+            .map(|(key, seq_num)| ContractEvent::new(*key, *seq_num, TypeTag::U64, vec![]))
+            .collect(),
+        0,
+        TransactionStatus::Keep(VMStatus::executed()),
+    )
+}
+
+#[test]
+fn consecutive_streams_have_no_conflicts() {
+    let address = AccountAddress::random();
+    let first = EventKey::new_from_address(&address, 0);
+    let second = EventKey::new_from_address(&address, 1);
+    let outputs = vec![
+        output_with_events(&[(first, 3), (second, 0)]),
+        output_with_events(&[]),
+        output_with_events(&[(first, 4), (first, 5), (second, 1)]),
+    ];
+    assert!(find_event_conflicts(&outputs).is_empty());
+}
+
+#[test]
+fn conflicting_streams_are_reported() {
+    let key = EventKey::new_from_address(&AccountAddress::random(), 0);
+    let outputs = vec![
+        output_with_events(&[(key, 7), (key, 8)]),
+        output_with_events(&[(key, 8)]),
+    ];
+    assert_eq!(
+        find_event_conflicts(&outputs),
+        vec![EventConflict {
+            key,
+            output_index: 1,
+            expected_sequence_number: 9,
+            sequence_number: 8,
+        }]
+    );
+}
@@ -0,0 +1,49 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{data_cache::StateViewCache, transaction_metadata::TransactionMetadata, LibraVM};
+use anyhow::Result;
+use libra_state_view::StateView;
+use libra_types::{
+    access_path::AccessPath, account_config, transaction::TransactionStatus, vm_status::StatusCode,
+};
+use move_core_types::gas_schedule::{GasAlgebra, GasUnits};
+use move_vm_types::gas_schedule::zero_cost_schedule;
+
+struct EmptyStateView;
+
+impl StateView for EmptyStateView {
+    fn get(&self, _access_path: &AccessPath) -> Result<Option<Vec<u8>>> {
+        Ok(None)
+    }
+
+    fn multi_get(&self, access_paths: &[AccessPath]) -> Result<Vec<Option<Vec<u8>>>> {
+        Ok(vec![None; access_paths.len()])
+    }
+
+    fn is_genesis(&self) -> bool {
+        false
+    }
+}
+
+#[test]
+fn retry_status_is_discarded_with_internal_error() {
+    let vm = LibraVM::new();
+    let data_cache = StateViewCache::new(&EmptyStateView);
+    let output = vm.failed_transaction_cleanup_with_status(
+        TransactionStatus::Retry,
+        &zero_cost_schedule(),
+        GasUnits::new(10_000),
+        &TransactionMetadata::default(),
+        &data_cache,
+        &account_config::from_currency_code_string(account_config::LBR_NAME).unwrap(),
+    );
+    assert!(output.write_set().is_empty());
+    assert_eq!(output.gas_used(), 0);
+    match output.status() {
+        TransactionStatus::Discard(status) => {
+            assert_eq!(status.major_status, StatusCode::UNREACHABLE)
+        }
+        status => panic!("unexpected status {:?}", status),
+    }
+}
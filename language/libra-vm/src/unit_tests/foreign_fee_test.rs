@@ -0,0 +1,26 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::foreign_fee::convert_gas_unit_price;
+
+const ONE: u64 = 1 << 32;
+
+#[test]
+fn convert_gas_unit_price_rounds_up() {
+    assert_eq!(convert_gas_unit_price(2, ONE), Some(2));
+    assert_eq!(convert_gas_unit_price(2, 3 * ONE / 2), Some(3));
+    // 3 * 0.5 = 1.5 is rounded up, and so is any nonzero price at a tiny rate.
+    assert_eq!(convert_gas_unit_price(3, ONE / 2), Some(2));
+    assert_eq!(convert_gas_unit_price(1, 1), Some(1));
+    assert_eq!(convert_gas_unit_price(0, ONE), Some(0));
+}
+
+#[test]
+fn convert_gas_unit_price_rejects_invalid_rates() {
+    assert_eq!(convert_gas_unit_price(1, 0), None);
+    assert_eq!(convert_gas_unit_price(u64::max_value(), 2 * ONE), None);
+    assert_eq!(
+        convert_gas_unit_price(u64::max_value(), ONE),
+        Some(u64::max_value())
+    );
+}
@@ -2,3 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod block_chunking_test;
+mod data_cache_test;
+mod event_consistency_test;
+mod failed_transaction_cleanup_test;
+mod foreign_fee_test;
+mod txn_args_test;
+mod unique_write_paths_test;
+mod write_set_categories_test;
@@ -0,0 +1,42 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::libra_transaction_executor::{convert_txn_args, value_to_txn_arg};
+use libra_types::{account_address::AccountAddress, transaction::TransactionArgument};
+use move_vm_types::values::{Struct, Value};
+
+#[test]
+fn txn_args_round_trip() {
+    let args = vec![
+        TransactionArgument::U8(7),
+        TransactionArgument::U64(u64::max_value()),
+        TransactionArgument::U128(1 << 100),
+        TransactionArgument::Address(AccountAddress::random()),
+        TransactionArgument::Bool(true),
+        TransactionArgument::U8Vector(vec![1, 2, 3]),
+        TransactionArgument::U8Vector(vec![]),
+        TransactionArgument::U64Vector(vec![1, 2]),
+        TransactionArgument::U64Vector(vec![]),
+        TransactionArgument::AddressVector(vec![AccountAddress::random()]),
+        TransactionArgument::AddressVector(vec![]),
+    ];
+    let values = convert_txn_args(&args);
+    let round_tripped: Vec<_> = values
+        .iter()
+        .map(|value| value_to_txn_arg(value).unwrap())
+        .collect();
+    assert_eq!(round_tripped, args);
+}
+
+#[test]
+fn non_argument_values_are_not_converted() {
+    let values = vec![
+        Value::struct_(Struct::pack(vec![Value::u64(1)], false)),
+        Value::vector_u128(vec![1, 2]),
+        Value::vector_bool(vec![true]),
+        Value::signer(AccountAddress::random()),
+    ];
+    for value in &values {
+        assert_eq!(value_to_txn_arg(value), None);
+    }
+}
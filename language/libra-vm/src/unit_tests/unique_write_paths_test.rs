@@ -0,0 +1,75 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    access_path_cache::AccessPathCache,
+    libra_vm::{check_unique_write_paths, txn_effects_to_writeset_and_events_cached},
+};
+use libra_types::{
+    access_path::AccessPath, account_address::AccountAddress, vm_status::StatusCode,
+};
+use move_core_types::{
+    identifier::Identifier,
+    language_storage::{ModuleId, StructTag, TypeTag},
+    value::MoveTypeLayout,
+};
+use move_vm_runtime::data_cache::TransactionEffects;
+use move_vm_types::values::Value;
+
+/// A broken cache mapping every resource of an account to the same access path.
+struct CollidingPathCache;
+
+impl AccessPathCache for CollidingPathCache {
+    fn get_module_path(&mut self, module_id: ModuleId) -> AccessPath {
+        AccessPath::from(&module_id)
+    }
+
+    fn get_resource_path(&mut self, address: AccountAddress, _struct_tag: StructTag) -> AccessPath {
+        AccessPath::new(address, vec![0])
+    }
+}
+
+fn effects_with_two_resources() -> TransactionEffects {
+    let resource = |name: &str| {
+        TypeTag::Struct(StructTag {
+            address: AccountAddress::random(),
+            module: Identifier::new("M").unwrap(),
+            name: Identifier::new(name).unwrap(),
+            type_params: vec![],
+        })
+    };
+    TransactionEffects {
+        resources: vec![(
+            AccountAddress::random(),
+            vec![
+                (resource("A"), Some((MoveTypeLayout::U64, Value::u64(1)))),
+                (resource("B"), Some((MoveTypeLayout::U64, Value::u64(2)))),
+            ],
+        )],
+        modules: vec![],
+        events: vec![],
+    }
+}
+
+#[test]
+fn distinct_paths_are_accepted() {
+    let (write_set, _) =
+        txn_effects_to_writeset_and_events_cached(&mut (), effects_with_two_resources()).unwrap();
+    assert_eq!(write_set.iter().count(), 2);
+    assert!(check_unique_write_paths(&write_set).is_ok());
+}
+
+#[test]
+fn duplicate_paths_are_rejected() {
+    let (write_set, _) = txn_effects_to_writeset_and_events_cached(
+        &mut CollidingPathCache,
+        effects_with_two_resources(),
+    )
+    .unwrap();
+    assert_eq!(
+        check_unique_write_paths(&write_set)
+            .unwrap_err()
+            .major_status,
+        StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR
+    );
+}
@@ -0,0 +1,108 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    data_cache::StateViewCache,
+    write_set_categories::{
+        categorize_write_set, write_ops_detail, WriteOpKind, WriteSetCategories,
+    },
+};
+use anyhow::Result;
+use libra_state_view::StateView;
+use libra_types::{
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    transaction::{TransactionOutput, TransactionStatus},
+    vm_status::VMStatus,
+    write_set::{WriteOp, WriteSetMut},
+};
+use std::collections::BTreeMap;
+
+struct MapStateView(BTreeMap<AccessPath, Vec<u8>>);
+
+impl StateView for MapStateView {
+    fn get(&self, access_path: &AccessPath) -> Result<Option<Vec<u8>>> {
+        Ok(self.0.get(access_path).cloned())
+    }
+
+    fn multi_get(&self, access_paths: &[AccessPath]) -> Result<Vec<Option<Vec<u8>>>> {
+        access_paths.iter().map(|ap| self.get(ap)).collect()
+    }
+
+    fn is_genesis(&self) -> bool {
+        false
+    }
+}
+
+#[test]
+fn created_modified_and_deleted() {
+    let address = AccountAddress::random();
+    let path = |p: u8| AccessPath::new(address, vec![p]);
+    let mut pre_state = BTreeMap::new();
+    pre_state.insert(path(1), vec![1]);
+    pre_state.insert(path(2), vec![2]);
+    let pre_state = MapStateView(pre_state);
+
+    let ws = WriteSetMut::new(vec![
+        (path(1), WriteOp::Value(vec![10])),
+        (path(2), WriteOp::Deletion),
+        (path(3), WriteOp::Value(vec![3])),
+        (path(4), WriteOp::Value(vec![4])),
+    ])
+    .freeze()
+    .unwrap();
+
+    assert_eq!(
+        categorize_write_set(&pre_state, &ws).unwrap(),
+        WriteSetCategories {
+            created: vec![path(3), path(4)],
+            modified: vec![path(1)],
+            deleted: vec![path(2)],
+        }
+    );
+}
+
+#[test]
+fn write_ops_detail_against_the_block_cache() {
+    let address = AccountAddress::random();
+    let path = |p: u8| AccessPath::new(address, vec![p]);
+    let mut storage = BTreeMap::new();
+    storage.insert(path(1), vec![1]);
+    storage.insert(path(2), vec![2]);
+    storage.insert(path(3), vec![3]);
+    let storage = MapStateView(storage);
+
+    // An earlier transaction of the block created path 4 and deleted path 3.
+    let mut pre_cache = StateViewCache::new(&storage);
+    pre_cache.push_write_set(
+        &WriteSetMut::new(vec![
+            (path(3), WriteOp::Deletion),
+            (path(4), WriteOp::Value(vec![4])),
+        ])
+        .freeze()
+        .unwrap(),
+    );
+
+    let ws = WriteSetMut::new(vec![
+        (path(1), WriteOp::Value(vec![10])),
+        (path(2), WriteOp::Deletion),
+        (path(3), WriteOp::Value(vec![30])),
+        (path(4), WriteOp::Value(vec![40])),
+        (path(5), WriteOp::Value(vec![5])),
+    ])
+    .freeze()
+    .unwrap();
+    let output =
+        TransactionOutput::new(ws, vec![], 0, TransactionStatus::Keep(VMStatus::executed()));
+
+    assert_eq!(
+        write_ops_detail(&pre_cache, &output).unwrap(),
+        vec![
+            (path(1), WriteOpKind::Modification),
+            (path(2), WriteOpKind::Deletion),
+            (path(3), WriteOpKind::Creation),
+            (path(4), WriteOpKind::Modification),
+            (path(5), WriteOpKind::Creation),
+        ]
+    );
+}
@@ -0,0 +1,105 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Classification of the writes of a transaction against the state they are applied to.
+
+use crate::data_cache::StateViewCache;
+use libra_state_view::StateView;
+use libra_types::{
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    transaction::TransactionOutput,
+    vm_status::{StatusCode, VMStatus},
+    write_set::{WriteOp, WriteSet},
+};
+
+/// The access paths of a `WriteSet`, split by the kind of change they make to the prior state.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct WriteSetCategories {
+    /// Values written to paths that had no value.
+    pub created: Vec<AccessPath>,
+    /// Values written to paths that already had one.
+    pub modified: Vec<AccessPath>,
+    /// Deleted paths.
+    pub deleted: Vec<AccessPath>,
+}
+
+/// The writes of a kept write set transaction, split by where they come from. The write set of
+/// its output is the `epilogue` writes followed by the `payload` writes, which are disjoint.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WriteSetOrigins {
+    pub sender: AccountAddress,
+    /// Sequence number of the transaction, 0 for `LibraVM::process_genesis_write_set`.
+    pub sequence_number: u64,
+    /// The write set of the `ChangeSet` in the payload.
+    pub payload: WriteSet,
+    /// The writes generated by the framework: the sequence number bump of the sender, if any,
+    /// and the writes of the write set epilogue.
+    pub epilogue: WriteSet,
+}
+
+/// Classifies each write of `ws` as a creation, a modification or a deletion, by reading the
+/// values in `pre_state`, the state the write set is applied to. Paths keep the order of `ws`.
+pub fn categorize_write_set(
+    pre_state: &dyn StateView,
+    ws: &WriteSet,
+) -> Result<WriteSetCategories, VMStatus> {
+    let mut categories = WriteSetCategories::default();
+    for (access_path, write_op) in ws {
+        let paths = match write_op_kind(pre_state, access_path, write_op)? {
+            WriteOpKind::Creation => &mut categories.created,
+            WriteOpKind::Modification => &mut categories.modified,
+            WriteOpKind::Deletion => &mut categories.deleted,
+        };
+        paths.push(access_path.clone());
+    }
+    Ok(categories)
+}
+
+/// The kind of change a single write op makes to the prior state.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum WriteOpKind {
+    /// A value written to a path that had no value.
+    Creation,
+    /// A value written to a path that already had one.
+    Modification,
+    /// A deleted path.
+    Deletion,
+}
+
+/// Classifies each write op of `output` like `categorize_write_set`, against `pre_cache`, the
+/// cache of the block as it was before the transaction ran. Paths keep the order of the write
+/// set. Deletions are classified without a read, and values already cached, e.g. written by an
+/// earlier transaction of the block, are not read again from the `StateView` of the cache.
+pub fn write_ops_detail(
+    pre_cache: &StateViewCache<'_>,
+    output: &TransactionOutput,
+) -> Result<Vec<(AccessPath, WriteOpKind)>, VMStatus> {
+    output
+        .write_set()
+        .iter()
+        .map(|(access_path, write_op)| {
+            let kind = write_op_kind(pre_cache, access_path, write_op)?;
+            Ok((access_path.clone(), kind))
+        })
+        .collect()
+}
+
+fn write_op_kind(
+    pre_state: &dyn StateView,
+    access_path: &AccessPath,
+    write_op: &WriteOp,
+) -> Result<WriteOpKind, VMStatus> {
+    if let WriteOp::Deletion = write_op {
+        return Ok(WriteOpKind::Deletion);
+    }
+    let exists = pre_state
+        .get(access_path)
+        .map_err(|_| VMStatus::new(StatusCode::STORAGE_ERROR, None, None))?
+        .is_some();
+    Ok(if exists {
+        WriteOpKind::Modification
+    } else {
+        WriteOpKind::Creation
+    })
+}
@@ -0,0 +1,17 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Checks of the write sets of user transactions before they are committed.
+
+use libra_types::{vm_status::VMStatus, write_set::WriteSet};
+use move_core_types::account_address::AccountAddress;
+
+/// Checks the write set of every kept user transaction output of a block before it is applied to
+/// the block state, e.g. that it only touches paths its sender may modify. An output whose write
+/// set is rejected is discarded with the returned status instead. Set with
+/// `LibraVM::set_write_set_validator`.
+///
+/// Validators reject outputs of a block, so every validator must use the same checks.
+pub trait WriteSetValidator: Send + Sync {
+    fn validate(&self, sender: AccountAddress, write_set: &WriteSet) -> Result<(), VMStatus>;
+}
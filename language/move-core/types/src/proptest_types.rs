@@ -55,6 +55,8 @@ impl Arbitrary for TransactionArgument {
             any::<u64>().prop_map(TransactionArgument::U64),
             any::<AccountAddress>().prop_map(TransactionArgument::Address),
             vec(any::<u8>(), 0..10).prop_map(TransactionArgument::U8Vector),
+            vec(any::<u64>(), 0..10).prop_map(TransactionArgument::U64Vector),
+            vec(any::<AccountAddress>(), 0..10).prop_map(TransactionArgument::AddressVector),
         ]
         .boxed()
     }
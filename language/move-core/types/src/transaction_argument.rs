@@ -13,6 +13,8 @@ pub enum TransactionArgument {
     Address(AccountAddress),
     U8Vector(#[serde(with = "serde_bytes")] Vec<u8>),
     Bool(bool),
+    U64Vector(Vec<u64>),
+    AddressVector(Vec<AccountAddress>),
 }
 
 impl fmt::Debug for TransactionArgument {
@@ -26,6 +28,10 @@ impl fmt::Debug for TransactionArgument {
             TransactionArgument::U8Vector(vector) => {
                 write!(f, "{{U8Vector: 0x{}}}", hex::encode(vector))
             }
+            TransactionArgument::U64Vector(vector) => write!(f, "{{U64Vector: {:?}}}", vector),
+            TransactionArgument::AddressVector(vector) => {
+                write!(f, "{{AddressVector: {:?}}}", vector)
+            }
         }
     }
 }
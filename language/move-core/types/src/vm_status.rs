@@ -129,6 +129,16 @@ impl VMStatus {
     pub fn is(&self, status_type: StatusType) -> bool {
         self.status_type() == status_type
     }
+
+    /// Appends `context` to the message of this status, after the existing message if any. The
+    /// major and sub status are left untouched, so the status is handled as before.
+    pub fn with_context(mut self, context: impl fmt::Display) -> Self {
+        self.message = Some(match self.message {
+            Some(message) => format!("{}; {}", message, context),
+            None => context.to_string(),
+        });
+        self
+    }
 }
 
 /// Error codes that can be emitted by the prologue. These have special significance to the VM when
@@ -282,6 +292,8 @@ pub enum StatusCode {
     INVALID_MODULE_PUBLISHER = 21,
     // The sending account has no role
     NO_ACCOUNT_ROLE = 22,
+    // A user transaction triggered a reconfiguration while the executor does not allow it
+    UNEXPECTED_RECONFIGURATION = 23,
 
     // When a code module/script is published it is verified. These are the
     // possible errors that can arise from the verification process.
@@ -383,6 +395,8 @@ pub enum StatusCode {
     GENERIC_MEMBER_OPCODE_MISMATCH = 1090,
     FUNCTION_RESOLUTION_FAILURE = 1091,
     INVALID_OPERATION_IN_SCRIPT = 1094,
+    // The module being published has a longer chain of dependencies than the executor allows
+    MAX_DEPENDENCY_DEPTH_REACHED = 1095,
 
     // These are errors that the VM might raise if a violation of internal
     // invariants takes place.
@@ -462,6 +476,10 @@ pub enum StatusCode {
     GAS_SCHEDULE_ERROR = 4023,
     VM_MAX_TYPE_DEPTH_REACHED = 4024,
     VM_MAX_VALUE_DEPTH_REACHED = 4025,
+    // A resource was moved to or from global storage more times than the executor allows
+    MAX_RESOURCE_WRITES_REACHED = 4026,
+    // A transaction emitted more events than the executor allows
+    MAX_EVENTS_REACHED = 4027,
 
     // A reserved status to represent an unknown vm status.
     // this is std::u64::MAX, but we can't pattern match on that, so put the hardcoded value in
@@ -0,0 +1,53 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A record of the functions called by the interpreter, for debugging Move programs. Only
+//! collected by `Session::execute_script_traced`.
+
+use crate::loader::Function;
+use move_core_types::{
+    gas_schedule::GasAlgebra, language_storage::ModuleId, vm_status::StatusCode,
+};
+use move_vm_types::gas_schedule::CostStrategy;
+
+/// What happened to a function.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TraceEventKind {
+    /// The function was called.
+    Enter,
+    /// The function returned.
+    Exit,
+    /// The execution stopped in the function with this status, e.g. on an abort or when it ran
+    /// out of gas. No `Exit` event follows for the functions on the call stack.
+    Error(StatusCode),
+}
+
+/// A call to or a return from a function.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TraceEvent {
+    pub kind: TraceEventKind,
+    /// The module of the function, `None` for the main function of a script.
+    pub module: Option<ModuleId>,
+    pub function: String,
+    /// The number of callers of the function on the call stack.
+    pub depth: usize,
+    /// The gas left at the time of the event.
+    pub gas_remaining: u64,
+}
+
+impl TraceEvent {
+    pub(crate) fn new(
+        kind: TraceEventKind,
+        function: &Function,
+        depth: usize,
+        cost_strategy: &CostStrategy,
+    ) -> Self {
+        Self {
+            kind,
+            module: function.module_id().cloned(),
+            function: function.name().to_string(),
+            depth,
+            gas_remaining: cost_strategy.remaining_gas().get(),
+        }
+    }
+}
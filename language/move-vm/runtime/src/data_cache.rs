@@ -62,6 +62,8 @@ pub(crate) struct TransactionDataCache<'r, 'l, R> {
     loader: &'l Loader,
     account_map: BTreeMap<AccountAddress, AccountDataCache>,
     event_data: Vec<(Vec<u8>, u64, Type, Value)>,
+    // Number of `MoveTo`, `MoveFrom` and mutable `BorrowGlobal` done on each resource.
+    resource_writes: BTreeMap<(AccountAddress, Type), u64>,
 }
 
 pub struct TransactionEffects {
@@ -82,9 +84,67 @@ impl<'r, 'l, R: RemoteCache> TransactionDataCache<'r, 'l, R> {
             loader,
             account_map: BTreeMap::new(),
             event_data: vec![],
+            resource_writes: BTreeMap::new(),
         }
     }
 
+    /// Returns the resource written the most times so far, with that number of writes. Moving a
+    /// resource to or from global storage counts as a write, and so does borrowing it mutably,
+    /// whether or not it is then changed through the reference.
+    pub(crate) fn most_written_resource(
+        &self,
+    ) -> PartialVMResult<Option<(AccountAddress, TypeTag, u64)>> {
+        let mut most_written: Option<(&(AccountAddress, Type), u64)> = None;
+        for (resource, count) in &self.resource_writes {
+            if most_written.map_or(true, |(_, max)| *count > max) {
+                most_written = Some((resource, *count));
+            }
+        }
+        match most_written {
+            Some(((addr, ty), count)) => {
+                Ok(Some((*addr, self.loader.type_to_type_tag(ty)?, count)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the number of bytes taken by the values of the resources and the modules written
+    /// so far, once serialized as they would be by `into_effects`. Deletions count for nothing.
+    pub(crate) fn written_bytes(&self) -> PartialVMResult<u64> {
+        let mut bytes = 0;
+        for account_cache in self.account_map.values() {
+            for (ty, gv_opt) in &account_cache.data_map {
+                if let Some(gv) = gv_opt {
+                    if gv.is_dirty()? {
+                        let blob = match self.loader.type_to_type_layout(ty)? {
+                            MoveTypeLayout::Struct(layout) => gv.simple_serialize(&layout),
+                            _ => None,
+                        }
+                        .ok_or_else(|| {
+                            PartialVMError::new(StatusCode::VALUE_SERIALIZATION_ERROR)
+                        })?;
+                        bytes += blob.len() as u64;
+                    }
+                }
+            }
+            bytes += account_cache
+                .module_map
+                .values()
+                .map(|(blob, _)| blob.len() as u64)
+                .sum::<u64>();
+        }
+        Ok(bytes)
+    }
+
+    /// Returns the number of events emitted so far.
+    pub(crate) fn num_events(&self) -> usize {
+        self.event_data.len()
+    }
+
+    fn record_resource_write(&mut self, addr: AccountAddress, ty: &Type) {
+        *self.resource_writes.entry((addr, ty.clone())).or_insert(0) += 1;
+    }
+
     /// Make a write set from the updated (dirty, deleted) global resources along with
     /// published modules.
     ///
@@ -187,6 +247,7 @@ impl<'r, 'l, C: RemoteCache> DataStore for TransactionDataCache<'r, 'l, C> {
         ty: Type,
         g: GlobalValue,
     ) -> PartialVMResult<()> {
+        self.record_resource_write(addr, &ty);
         let account_cache = Self::get_mut_or_insert_with(&mut self.account_map, &addr, || {
             (addr, AccountDataCache::new())
         });
@@ -204,6 +265,10 @@ impl<'r, 'l, C: RemoteCache> DataStore for TransactionDataCache<'r, 'l, C> {
         Ok(self.load_data(addr, ty)?.as_ref())
     }
 
+    fn record_mutable_borrow(&mut self, addr: AccountAddress, ty: &Type) {
+        self.record_resource_write(addr, ty);
+    }
+
     fn move_resource_from(
         &mut self,
         addr: AccountAddress,
@@ -211,7 +276,9 @@ impl<'r, 'l, C: RemoteCache> DataStore for TransactionDataCache<'r, 'l, C> {
     ) -> PartialVMResult<Option<GlobalValue>> {
         // .take() means that the entry is removed from the data map -- this marks the
         // access path for deletion.
-        Ok(self.load_data(addr, ty)?.take())
+        let resource = self.load_data(addr, ty)?.take();
+        self.record_resource_write(addr, ty);
+        Ok(resource)
     }
 
     // REVIEW: The txn data cache isn't really caching the modules read from storage. Is this really desired?
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    call_trace::{TraceEvent, TraceEventKind},
     data_operations::{borrow_global, move_resource_from, move_resource_to, resource_exists},
     loader::{Function, Loader, Resolver},
     native_functions::FunctionContext,
@@ -53,6 +54,8 @@ pub(crate) struct Interpreter {
     operand_stack: Stack,
     /// The stack of active functions.
     call_stack: CallStack,
+    /// The function calls made so far, if they are traced.
+    call_trace: Option<Vec<TraceEvent>>,
 }
 
 impl Interpreter {
@@ -72,12 +75,29 @@ impl Interpreter {
         interp.execute(loader, data_store, cost_strategy, function, ty_args, args)
     }
 
+    /// Like `entrypoint`, and also returns the calls made by the function, up to the error if
+    /// execution fails.
+    pub(crate) fn entrypoint_traced(
+        function: Arc<Function>,
+        ty_args: Vec<Type>,
+        args: Vec<Value>,
+        data_store: &mut dyn DataStore,
+        cost_strategy: &mut CostStrategy,
+        loader: &Loader,
+    ) -> (VMResult<()>, Vec<TraceEvent>) {
+        let mut interp = Self::new();
+        interp.call_trace = Some(vec![]);
+        let result = interp.execute(loader, data_store, cost_strategy, function, ty_args, args);
+        (result, interp.call_trace.unwrap_or_default())
+    }
+
     /// Create a new instance of an `Interpreter` in the context of a transaction with a
     /// given module cache and gas schedule.
     fn new() -> Self {
         Interpreter {
             operand_stack: Stack::new(),
             call_stack: CallStack::new(),
+            call_trace: None,
         }
     }
 
@@ -121,19 +141,35 @@ impl Interpreter {
                 .map_err(|e| self.set_location(e))?;
         }
 
+        self.trace_call(TraceEventKind::Enter, &function, 0, cost_strategy);
         let mut current_frame = Frame::new(function, ty_args, locals);
         loop {
             let resolver = current_frame.resolver(loader);
             let exit_code =
-                current_frame //self
-                    .execute_code(&resolver, self, data_store, cost_strategy)
-                    .map_err(|err| self.maybe_core_dump(err, &current_frame))?;
+                match current_frame.execute_code(&resolver, self, data_store, cost_strategy) {
+                    Ok(exit_code) => exit_code,
+                    Err(err) => {
+                        self.trace_call(
+                            TraceEventKind::Error(err.major_status()),
+                            &current_frame.function,
+                            self.call_stack.0.len(),
+                            cost_strategy,
+                        );
+                        return Err(self.maybe_core_dump(err, &current_frame));
+                    }
+                };
             match exit_code {
                 ExitCode::Return => {
                     current_frame
                         .locals
                         .check_resources_for_return()
                         .map_err(|e| self.set_location(e))?;
+                    self.trace_call(
+                        TraceEventKind::Exit,
+                        &current_frame.function,
+                        self.call_stack.0.len(),
+                        cost_strategy,
+                    );
                     if let Some(frame) = self.call_stack.pop() {
                         current_frame = frame;
                     } else {
@@ -160,6 +196,12 @@ impl Interpreter {
                         let err = self.set_location(err);
                         self.maybe_core_dump(err, &frame)
                     })?;
+                    self.trace_call(
+                        TraceEventKind::Enter,
+                        &frame.function,
+                        self.call_stack.0.len(),
+                        cost_strategy,
+                    );
                     current_frame = frame;
                 }
                 ExitCode::CallGeneric(idx) => {
@@ -188,6 +230,12 @@ impl Interpreter {
                         let err = self.set_location(err);
                         self.maybe_core_dump(err, &frame)
                     })?;
+                    self.trace_call(
+                        TraceEventKind::Enter,
+                        &frame.function,
+                        self.call_stack.0.len(),
+                        cost_strategy,
+                    );
                     current_frame = frame;
                 }
             }
@@ -213,6 +261,18 @@ impl Interpreter {
         Ok(Frame::new(func, ty_args, locals))
     }
 
+    fn trace_call(
+        &mut self,
+        kind: TraceEventKind,
+        function: &Function,
+        depth: usize,
+        cost_strategy: &CostStrategy,
+    ) {
+        if let Some(call_trace) = self.call_trace.as_mut() {
+            call_trace.push(TraceEvent::new(kind, function, depth, cost_strategy));
+        }
+    }
+
     /// Call a native functions.
     fn call_native(
         &mut self,
@@ -223,14 +283,21 @@ impl Interpreter {
         ty_args: Vec<Type>,
     ) -> VMResult<()> {
         // Note: refactor if native functions push a frame on the stack
-        self.call_native_impl(
+        let depth = self.call_stack.0.len() + 1;
+        self.trace_call(TraceEventKind::Enter, &function, depth, cost_strategy);
+        let result = self.call_native_impl(
             resolver,
             data_store,
             cost_strategy,
             function.clone(),
             ty_args,
-        )
-        .map_err(|e| match function.module_id() {
+        );
+        let kind = match &result {
+            Ok(()) => TraceEventKind::Exit,
+            Err(err) => TraceEventKind::Error(err.major_status()),
+        };
+        self.trace_call(kind, &function, depth, cost_strategy);
+        result.map_err(|e| match function.module_id() {
             Some(id) => e.finish(Location::Module(id.clone())),
             None => {
                 let err = PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
@@ -979,6 +1046,9 @@ impl Frame {
                         let ty = resolver.get_struct_type(*sd_idx);
                         let size = interpreter.borrow_global(data_store, addr, &ty)?;
                         cost_strategy.charge_instr_with_size(Opcodes::MUT_BORROW_GLOBAL, size)?;
+                        if matches!(instruction, Bytecode::MutBorrowGlobal(_)) {
+                            data_store.record_mutable_borrow(addr, &ty);
+                        }
                     }
                     Bytecode::MutBorrowGlobalGeneric(si_idx)
                     | Bytecode::ImmBorrowGlobalGeneric(si_idx) => {
@@ -987,6 +1057,9 @@ impl Frame {
                         let size = interpreter.borrow_global(data_store, addr, &ty)?;
                         cost_strategy
                             .charge_instr_with_size(Opcodes::MUT_BORROW_GLOBAL_GENERIC, size)?;
+                        if matches!(instruction, Bytecode::MutBorrowGlobalGeneric(_)) {
+                            data_store.record_mutable_borrow(addr, &ty);
+                        }
                     }
                     Bytecode::Exists(sd_idx) => {
                         let addr = interpreter.operand_stack.pop_as::<AccountAddress>()?;
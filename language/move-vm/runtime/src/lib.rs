@@ -12,6 +12,7 @@
 #[macro_use]
 extern crate mirai_annotations;
 
+pub mod call_trace;
 pub mod data_cache;
 mod data_operations;
 mod interpreter;
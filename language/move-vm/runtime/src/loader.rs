@@ -572,6 +572,40 @@ impl Loader {
         DependencyChecker::verify_module(module, deps)
     }
 
+    // Length of the longest chain of dependencies from `module` to a module without
+    // dependencies, loading the dependencies as needed.
+    pub(crate) fn module_dependency_depth(
+        &self,
+        module: &CompiledModule,
+        data_store: &mut impl DataStore,
+    ) -> VMResult<usize> {
+        let mut depths = HashMap::new();
+        self.dependency_depth_impl(module, data_store, &mut depths)
+    }
+
+    fn dependency_depth_impl(
+        &self,
+        module: &CompiledModule,
+        data_store: &mut impl DataStore,
+        depths: &mut HashMap<ModuleId, usize>,
+    ) -> VMResult<usize> {
+        let mut depth = 0;
+        for dep in load_module_dependencies(module) {
+            let dep_depth = match depths.get(&dep) {
+                Some(dep_depth) => *dep_depth,
+                None => {
+                    let loaded = self.load_module(&dep, data_store)?;
+                    let dep_depth =
+                        self.dependency_depth_impl(loaded.module(), data_store, depths)?;
+                    depths.insert(dep, dep_depth);
+                    dep_depth
+                }
+            };
+            depth = depth.max(dep_depth + 1);
+        }
+        Ok(depth)
+    }
+
     // All native functions must be known to the loader
     fn check_natives(module: &CompiledModule) -> VMResult<()> {
         fn check_natives_impl(module: &CompiledModule) -> PartialVMResult<()> {
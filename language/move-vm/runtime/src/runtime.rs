@@ -2,9 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    call_trace::TraceEvent,
     data_cache::{RemoteCache, TransactionDataCache},
     interpreter::Interpreter,
-    loader::Loader,
+    loader::{Function, Loader},
     session::Session,
 };
 use libra_logger::prelude::*;
@@ -14,7 +15,11 @@ use move_core_types::{
     language_storage::{ModuleId, TypeTag},
     vm_status::StatusCode,
 };
-use move_vm_types::{data_store::DataStore, gas_schedule::CostStrategy, values::Value};
+use move_vm_types::{
+    data_store::DataStore, gas_schedule::CostStrategy, loaded_data::runtime_types::Type,
+    values::Value,
+};
+use std::sync::Arc;
 use vm::{
     access::ModuleAccess,
     errors::{verification_error, Location, PartialVMError, PartialVMResult, VMResult},
@@ -85,15 +90,74 @@ impl VMRuntime {
         data_store.publish_module(module, compiled_module)
     }
 
+    pub(crate) fn module_dependency_depth(
+        &self,
+        module: &[u8],
+        data_store: &mut impl DataStore,
+    ) -> VMResult<usize> {
+        let compiled_module =
+            CompiledModule::deserialize(module).map_err(|e| e.finish(Location::Undefined))?;
+        self.loader
+            .module_dependency_depth(&compiled_module, data_store)
+    }
+
     pub(crate) fn execute_script(
         &self,
         script: Vec<u8>,
         ty_args: Vec<TypeTag>,
-        mut args: Vec<Value>,
+        args: Vec<Value>,
         sender: AccountAddress,
         data_store: &mut impl DataStore,
         cost_strategy: &mut CostStrategy,
     ) -> VMResult<()> {
+        let (main, type_params, args) =
+            self.prepare_script(&script, &ty_args, args, sender, data_store)?;
+
+        // run the script
+        Interpreter::entrypoint(
+            main,
+            type_params,
+            args,
+            data_store,
+            cost_strategy,
+            &self.loader,
+        )
+    }
+
+    pub(crate) fn execute_script_traced(
+        &self,
+        script: Vec<u8>,
+        ty_args: Vec<TypeTag>,
+        args: Vec<Value>,
+        sender: AccountAddress,
+        data_store: &mut impl DataStore,
+        cost_strategy: &mut CostStrategy,
+    ) -> (VMResult<()>, Vec<TraceEvent>) {
+        let (main, type_params, args) =
+            match self.prepare_script(&script, &ty_args, args, sender, data_store) {
+                Ok(prepared) => prepared,
+                Err(err) => return (Err(err), vec![]),
+            };
+
+        // run the script, recording the calls
+        Interpreter::entrypoint_traced(
+            main,
+            type_params,
+            args,
+            data_store,
+            cost_strategy,
+            &self.loader,
+        )
+    }
+
+    fn prepare_script(
+        &self,
+        script: &[u8],
+        ty_args: &[TypeTag],
+        mut args: Vec<Value>,
+        sender: AccountAddress,
+        data_store: &mut impl DataStore,
+    ) -> VMResult<(Arc<Function>, Vec<Type>, Vec<Value>)> {
         // signer helper closure
         fn is_signer_reference(s: &SignatureToken) -> bool {
             use SignatureToken as S;
@@ -104,7 +168,7 @@ impl VMRuntime {
         }
 
         // load the script, perform verification
-        let (main, type_params) = self.loader.load_script(&script, &ty_args, data_store)?;
+        let (main, type_params) = self.loader.load_script(script, ty_args, data_store)?;
 
         // build the arguments list for the main and check the arguments are of restricted types
         let first_param_opt = main.parameters().0.get(0);
@@ -112,16 +176,7 @@ impl VMRuntime {
             args.insert(0, Value::transaction_argument_signer_reference(sender))
         }
         check_args(&args).map_err(|e| e.finish(Location::Script))?;
-
-        // run the script
-        Interpreter::entrypoint(
-            main,
-            type_params,
-            args,
-            data_store,
-            cost_strategy,
-            &self.loader,
-        )
+        Ok((main, type_params, args))
     }
 
     pub(crate) fn execute_function(
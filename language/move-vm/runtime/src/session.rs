@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    call_trace::TraceEvent,
     data_cache::{RemoteCache, TransactionDataCache, TransactionEffects},
     runtime::VMRuntime,
 };
@@ -56,6 +57,34 @@ impl<'r, 'l, R: RemoteCache> Session<'r, 'l, R> {
         )
     }
 
+    /// Like `execute_script`, and also returns the calls made by the script, up to the error if
+    /// execution fails.
+    pub fn execute_script_traced(
+        &mut self,
+        script: Vec<u8>,
+        ty_args: Vec<TypeTag>,
+        args: Vec<Value>,
+        sender: AccountAddress,
+        cost_strategy: &mut CostStrategy,
+    ) -> (VMResult<()>, Vec<TraceEvent>) {
+        self.runtime.execute_script_traced(
+            script,
+            ty_args,
+            args,
+            sender,
+            &mut self.data_cache,
+            cost_strategy,
+        )
+    }
+
+    /// Returns the length of the longest chain of dependencies from the serialized `module` to a
+    /// module without dependencies. The dependencies are loaded, so this fails if one of them is
+    /// missing or does not verify.
+    pub fn module_dependency_depth(&mut self, module: &[u8]) -> VMResult<usize> {
+        self.runtime
+            .module_dependency_depth(module, &mut self.data_cache)
+    }
+
     pub fn publish_module(
         &mut self,
         module: Vec<u8>,
@@ -66,6 +95,27 @@ impl<'r, 'l, R: RemoteCache> Session<'r, 'l, R> {
             .publish_module(module, sender, &mut self.data_cache, cost_strategy)
     }
 
+    /// Returns the resource written the most times so far in the session, with that number of
+    /// writes: its moves to and from global storage and its mutable borrows.
+    pub fn most_written_resource(&self) -> VMResult<Option<(AccountAddress, TypeTag, u64)>> {
+        self.data_cache
+            .most_written_resource()
+            .map_err(|e| e.finish(Location::Undefined))
+    }
+
+    /// Returns the number of events emitted so far in the session.
+    pub fn num_events(&self) -> usize {
+        self.data_cache.num_events()
+    }
+
+    /// Returns the number of bytes taken by the values of the resources and the modules written
+    /// so far in the session, once serialized.
+    pub fn written_bytes(&self) -> VMResult<u64> {
+        self.data_cache
+            .written_bytes()
+            .map_err(|e| e.finish(Location::Undefined))
+    }
+
     pub fn finish(self) -> VMResult<TransactionEffects> {
         self.data_cache
             .into_effects()
@@ -37,6 +37,10 @@ pub trait DataStore {
         ty: &Type,
     ) -> PartialVMResult<Option<&GlobalValue>>;
 
+    /// Record that a resource borrowed with `borrow_resource` is borrowed mutably, so it may be
+    /// changed in place.
+    fn record_mutable_borrow(&mut self, addr: AccountAddress, ty: &Type);
+
     /// Transfer ownership of a resource to the VM.
     fn move_resource_from(
         &mut self,
@@ -32,6 +32,8 @@ pub struct CostStrategy<'a> {
     cost_table: &'a CostTable,
     gas_left: GasUnits<GasCarrier>,
     charge: bool,
+    #[cfg(feature = "instruction-count")]
+    instructions_executed: u64,
 }
 
 impl<'a> CostStrategy<'a> {
@@ -44,6 +46,8 @@ impl<'a> CostStrategy<'a> {
             cost_table,
             gas_left,
             charge: true,
+            #[cfg(feature = "instruction-count")]
+            instructions_executed: 0,
         }
     }
 
@@ -56,6 +60,8 @@ impl<'a> CostStrategy<'a> {
             cost_table,
             gas_left,
             charge: false,
+            #[cfg(feature = "instruction-count")]
+            instructions_executed: 0,
         }
     }
 
@@ -69,6 +75,12 @@ impl<'a> CostStrategy<'a> {
         self.gas_left
     }
 
+    /// Return the number of instructions charged so far, including while metering is disabled.
+    #[cfg(feature = "instruction-count")]
+    pub fn instructions_executed(&self) -> u64 {
+        self.instructions_executed
+    }
+
     /// Charge a given amount of gas and fail if not enough gas units are left.
     pub fn deduct_gas(&mut self, amount: GasUnits<GasCarrier>) -> PartialVMResult<()> {
         if !self.charge {
@@ -93,6 +105,10 @@ impl<'a> CostStrategy<'a> {
         opcode: Opcodes,
         size: AbstractMemorySize<GasCarrier>,
     ) -> PartialVMResult<()> {
+        #[cfg(feature = "instruction-count")]
+        {
+            self.instructions_executed += 1;
+        }
         self.deduct_gas(
             self.cost_table
                 .instruction_cost(opcode as u8)
@@ -103,6 +119,10 @@ impl<'a> CostStrategy<'a> {
 
     /// Charge an instruction and fail if not enough gas units are left.
     pub fn charge_instr(&mut self, opcode: Opcodes) -> PartialVMResult<()> {
+        #[cfg(feature = "instruction-count")]
+        {
+            self.instructions_executed += 1;
+        }
         self.deduct_gas(self.cost_table.instruction_cost(opcode as u8).total())
     }
 
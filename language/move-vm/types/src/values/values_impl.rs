@@ -1223,6 +1223,34 @@ impl VMValueCast<Vec<u8>> for Value {
     }
 }
 
+impl VMValueCast<Vec<u64>> for Value {
+    fn cast(self) -> PartialVMResult<Vec<u64>> {
+        match self.0 {
+            ValueImpl::Container(r) => match take_unique_ownership(r)? {
+                Container::U64(v) => Ok(v),
+                v => Err(PartialVMError::new(StatusCode::INTERNAL_TYPE_ERROR)
+                    .with_message(format!("cannot cast {:?} to vector<u64>", v,))),
+            },
+            v => Err(PartialVMError::new(StatusCode::INTERNAL_TYPE_ERROR)
+                .with_message(format!("cannot cast {:?} to vector<u64>", v,))),
+        }
+    }
+}
+
+impl VMValueCast<Vec<AccountAddress>> for Value {
+    fn cast(self) -> PartialVMResult<Vec<AccountAddress>> {
+        match self.0 {
+            ValueImpl::Container(r) => match take_unique_ownership(r)? {
+                Container::Address(v) => Ok(v),
+                v => Err(PartialVMError::new(StatusCode::INTERNAL_TYPE_ERROR)
+                    .with_message(format!("cannot cast {:?} to vector<address>", v,))),
+            },
+            v => Err(PartialVMError::new(StatusCode::INTERNAL_TYPE_ERROR)
+                .with_message(format!("cannot cast {:?} to vector<address>", v,))),
+        }
+    }
+}
+
 impl VMValueCast<SignerRef> for Value {
     fn cast(self) -> PartialVMResult<SignerRef> {
         match self.0 {
@@ -2496,6 +2524,17 @@ impl Struct {
     }
 }
 
+impl GlobalValue {
+    /// Serializes the value as it would be written back to storage, without taking it out.
+    pub fn simple_serialize(&self, layout: &MoveStructLayout) -> Option<Vec<u8>> {
+        lcs::to_bytes(&AnnotatedValue {
+            layout,
+            val: &*self.container.borrow(),
+        })
+        .ok()
+    }
+}
+
 struct AnnotatedValue<'a, 'b, T1, T2> {
     layout: &'a T1,
     val: &'b T2,
@@ -22,7 +22,9 @@ module Genesis {
     use 0x1::LibraVersion;
     use 0x1::LibraWriteSetManager;
     use 0x1::Signer;
+    use 0x1::StorageGasConfig;
     use 0x1::TransactionFee;
+    use 0x1::TransactionLimits;
     use 0x1::Roles;
     use 0x1::LibraVMConfig;
 
@@ -86,6 +88,12 @@ module Genesis {
         LibraVersion::initialize(
             lr_account,
         );
+        TransactionLimits::initialize(
+            lr_account,
+        );
+        StorageGasConfig::initialize(
+            lr_account,
+        );
         DualAttestation::initialize(
             lr_account,
         );
@@ -0,0 +1,42 @@
+address 0x1 {
+
+module StorageGasConfig {
+    use 0x1::CoreAddresses;
+    use 0x1::LibraConfig;
+    use 0x1::LibraTimestamp;
+    use 0x1::Signer;
+
+    // Whether the VM charges script and module transactions for the bytes they write, at the
+    // `global_memory_per_byte_write_cost` of the gas constants of `LibraVMConfig`. The charge
+    // comes before the epilogue, and a transaction without enough gas left for it fails with
+    // OUT_OF_GAS.
+    struct StorageGasConfig {
+        charge_written_bytes: bool,
+    }
+
+    const ENOT_GENESIS: u64 = 0;
+    const EINVALID_SINGLETON_ADDRESS: u64 = 1;
+
+    // Publish the config under the libra root account. The written bytes are not charged until
+    // the libra root account turns the charge on with `set_charge_written_bytes`.
+    public fun initialize(
+        lr_account: &signer,
+    ) {
+        assert(LibraTimestamp::is_genesis(), ENOT_GENESIS);
+        assert(Signer::address_of(lr_account) == CoreAddresses::LIBRA_ROOT_ADDRESS(), EINVALID_SINGLETON_ADDRESS);
+
+        LibraConfig::publish_new_config<StorageGasConfig>(
+            lr_account,
+            StorageGasConfig { charge_written_bytes: false },
+        );
+    }
+
+    public fun set_charge_written_bytes(account: &signer, charge_written_bytes: bool) {
+        LibraConfig::set<StorageGasConfig>(
+            account,
+            StorageGasConfig { charge_written_bytes }
+        );
+    }
+}
+
+}
@@ -0,0 +1,43 @@
+address 0x1 {
+
+module TransactionLimits {
+    use 0x1::CoreAddresses;
+    use 0x1::LibraConfig;
+    use 0x1::LibraTimestamp;
+    use 0x1::Signer;
+
+    // Limits the VM puts on a single user transaction, on top of its gas.
+    // * max_events_per_transaction: The most events a script or module transaction can emit,
+    //   not counting the ones of its epilogue. The transaction fails with MAX_EVENTS_REACHED
+    //   past it, and is charged the gas it used.
+    struct TransactionLimits {
+        max_events_per_transaction: u64,
+    }
+
+    const ENOT_GENESIS: u64 = 0;
+    const EINVALID_SINGLETON_ADDRESS: u64 = 1;
+
+    const U64_MAX: u64 = 18446744073709551615;
+
+    // Publish the limits under the libra root account. Genesis sets no limit, the libra root
+    // account sets one with `set_max_events_per_transaction`.
+    public fun initialize(
+        lr_account: &signer,
+    ) {
+        assert(LibraTimestamp::is_genesis(), ENOT_GENESIS);
+        assert(Signer::address_of(lr_account) == CoreAddresses::LIBRA_ROOT_ADDRESS(), EINVALID_SINGLETON_ADDRESS);
+
+        LibraConfig::publish_new_config<TransactionLimits>(
+            lr_account,
+            TransactionLimits { max_events_per_transaction: U64_MAX },
+        );
+    }
+
+    public fun set_max_events_per_transaction(account: &signer, max_events_per_transaction: u64) {
+        let config = LibraConfig::get<TransactionLimits>();
+        config.max_events_per_transaction = max_events_per_transaction;
+        LibraConfig::set<TransactionLimits>(account, config);
+    }
+}
+
+}
@@ -171,6 +171,8 @@ fn convert_txn_args(args: &[TransactionArgument]) -> Vec<Value> {
             TransactionArgument::Address(a) => Value::address(*a),
             TransactionArgument::Bool(b) => Value::bool(*b),
             TransactionArgument::U8Vector(v) => Value::vector_u8(v.clone()),
+            TransactionArgument::U64Vector(v) => Value::vector_u64(v.clone()),
+            TransactionArgument::AddressVector(v) => Value::vector_address(v.clone()),
         })
         .collect()
 }
@@ -18,12 +18,16 @@ use std::{collections::HashMap, sync::Arc};
 
 mod libra_version;
 mod registered_currencies;
+mod storage_gas;
+mod transaction_limits;
 mod validator_set;
 mod vm_config;
 
 pub use self::{
     libra_version::LibraVersion,
     registered_currencies::RegisteredCurrencies,
+    storage_gas::StorageGasConfig,
+    transaction_limits::TransactionLimits,
     validator_set::ValidatorSet,
     vm_config::{VMConfig, VMPublishingOption},
 };
@@ -56,6 +60,8 @@ pub const ON_CHAIN_CONFIG_REGISTRY: &[ConfigID] = &[
     LibraVersion::CONFIG_ID,
     ValidatorSet::CONFIG_ID,
     RegisteredCurrencies::CONFIG_ID,
+    TransactionLimits::CONFIG_ID,
+    StorageGasConfig::CONFIG_ID,
 ];
 
 #[derive(Clone, Debug, PartialEq)]
@@ -0,0 +1,17 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::on_chain_config::OnChainConfig;
+use serde::{Deserialize, Serialize};
+
+/// Defined in the `StorageGasConfig` Move module: whether the bytes written by script and module
+/// transactions are charged for, at the `global_memory_per_byte_write_cost` of the gas constants.
+/// The charge is off at genesis.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StorageGasConfig {
+    pub charge_written_bytes: bool,
+}
+
+impl OnChainConfig for StorageGasConfig {
+    const IDENTIFIER: &'static str = "StorageGasConfig";
+}
@@ -0,0 +1,18 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::on_chain_config::OnChainConfig;
+use serde::{Deserialize, Serialize};
+
+/// Defined in the `TransactionLimits` Move module: limits the VM puts on a single user
+/// transaction, on top of its gas. Genesis publishes it without a limit, and the libra root
+/// account lowers `max_events_per_transaction` like any other config, with a reconfiguration.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TransactionLimits {
+    /// The most events a script or module transaction can emit, not counting the epilogue.
+    pub max_events_per_transaction: u64,
+}
+
+impl OnChainConfig for TransactionLimits {
+    const IDENTIFIER: &'static str = "TransactionLimits";
+}
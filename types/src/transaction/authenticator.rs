@@ -101,7 +101,14 @@ impl TransactionAuthenticator {
             Self::MultiEd25519 {
                 public_key,
                 signature,
-            } => signature.verify(message, public_key),
+            } => signature.verify(message, public_key).map_err(|err| {
+                let key_indices = invalid_signature_keys(message, public_key, signature);
+                if key_indices.is_empty() {
+                    err
+                } else {
+                    Error::new(InvalidSignatures { key_indices })
+                }
+            }),
         }
     }
 
@@ -132,6 +139,35 @@ impl TransactionAuthenticator {
     }
 }
 
+/// The error returned by `TransactionAuthenticator::verify` when some of the signatures of a
+/// multisignature authenticator do not verify.
+#[derive(Clone, Debug, Eq, PartialEq, thiserror::Error)]
+#[error("invalid signatures for the keys at {key_indices:?}")]
+pub struct InvalidSignatures {
+    /// Positions in the multisignature public key of the keys whose signature does not verify, in
+    /// ascending order.
+    pub key_indices: Vec<u8>,
+}
+
+/// Returns the positions of the keys of `public_key` whose signature in `signature` does not
+/// verify `message`. Signatures for positions out of the range of `public_key` are ignored.
+fn invalid_signature_keys<T: Serialize + CryptoHash>(
+    message: &T,
+    public_key: &MultiEd25519PublicKey,
+    signature: &MultiEd25519Signature,
+) -> Vec<u8> {
+    let bitmap = signature.bitmap();
+    let signed_keys = (0..bitmap.len() * 8).filter(|i| bitmap[i / 8] & (128 >> (i % 8)) != 0);
+    signed_keys
+        .zip(signature.signatures())
+        .filter(|(i, sig)| match public_key.public_keys().get(*i) {
+            Some(key) => sig.verify(message, key).is_err(),
+            None => false,
+        })
+        .map(|(i, _)| i as u8)
+        .collect()
+}
+
 /// A struct that represents an account authentication key. An account's address is the last 16
 /// bytes of authentication key used to create it
 #[derive(
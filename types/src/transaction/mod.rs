@@ -670,6 +670,16 @@ impl TransactionOutput {
     pub fn status(&self) -> &TransactionStatus {
         &self.status
     }
+
+    /// Returns whether committing this output changes the state, i.e. whether the transaction is
+    /// kept and its write set is not empty. Deletions are state changes, so a write set made of
+    /// deletions only counts as one.
+    pub fn has_state_change(&self) -> bool {
+        match self.status {
+            TransactionStatus::Keep(_) => !self.write_set.is_empty(),
+            TransactionStatus::Discard(_) | TransactionStatus::Retry => false,
+        }
+    }
 }
 
 /// `TransactionInfo` is the object we store in the transaction accumulator. It consists of the
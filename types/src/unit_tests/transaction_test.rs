@@ -2,12 +2,16 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    access_path::AccessPath,
     account_address::AccountAddress,
     account_config::LBR_NAME,
     transaction::{
         RawTransaction, Script, SignedTransaction, Transaction, TransactionInfo,
-        TransactionListWithProof, TransactionPayload, TransactionWithProof,
+        TransactionListWithProof, TransactionOutput, TransactionPayload, TransactionStatus,
+        TransactionWithProof,
     },
+    vm_status::{StatusCode, VMStatus},
+    write_set::{WriteOp, WriteSet, WriteSetMut},
 };
 use lcs::test_helpers::assert_canonical_encode_decode;
 use libra_crypto::{
@@ -36,6 +40,31 @@ fn test_invalid_signature() {
         .expect_err("signature checking should fail");
 }
 
+#[test]
+fn test_output_has_state_change() {
+    let output = |write_set, status| TransactionOutput::new(write_set, vec![], 0, status);
+    let keep = || TransactionStatus::Keep(VMStatus::executed());
+    let access_path = AccessPath::new(AccountAddress::random(), vec![1, 2, 3]);
+    let deletion_only = WriteSetMut::new(vec![(access_path.clone(), WriteOp::Deletion)])
+        .freeze()
+        .unwrap();
+    let with_value = WriteSetMut::new(vec![(access_path, WriteOp::Value(vec![4, 5, 6]))])
+        .freeze()
+        .unwrap();
+
+    assert!(!output(WriteSet::default(), keep()).has_state_change());
+    assert!(output(deletion_only, keep()).has_state_change());
+    assert!(output(with_value.clone(), keep()).has_state_change());
+
+    let discard = TransactionStatus::Discard(VMStatus::new(
+        StatusCode::SEQUENCE_NUMBER_TOO_OLD,
+        None,
+        None,
+    ));
+    assert!(!output(with_value.clone(), discard).has_state_change());
+    assert!(!output(with_value, TransactionStatus::Retry).has_state_change());
+}
+
 proptest! {
     #[test]
     fn test_sign_raw_transaction(raw_txn in any::<RawTransaction>(), keypair in ed25519::keypair_strategy()) {